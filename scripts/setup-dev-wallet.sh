@@ -34,4 +34,4 @@ fi
 
 echo ""
 echo "🎯 Setup complete!"
-echo "Run: cargo run"
+echo "Run: SOLANA_KEYPAIR_PATH=.wallets/devnet-keypair.json cargo run"
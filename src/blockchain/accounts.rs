@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use super::{idl, DEFAULT_RPC_URL};
+use crate::config::Config;
+
+type AccountCache = Arc<Mutex<HashMap<Pubkey, (Vec<SubscriptionAccount>, Instant)>>>;
+
+static CACHE: OnceLock<AccountCache> = OnceLock::new();
+
+/// Byte offset of `user` in a `SubscriptionAccount`, right after the discriminator.
+const USER_OFFSET: usize = 8;
+
+/// A decoded `SubscriptionAccount`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionAccount {
+    #[serde(default)]
+    pub address: String,
+    pub user: String,
+    pub plan_tier: u8,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub is_active: bool,
+}
+
+impl SubscriptionAccount {
+    /// Decodes raw account data, rejecting anything without the `SubscriptionAccount` discriminator.
+    pub fn decode(address: &Pubkey, data: &[u8]) -> Result<Self, String> {
+        let value = idl::subscription_program().decode_account("SubscriptionAccount", data)?;
+        let mut account: Self = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        account.address = address.to_string();
+        Ok(account)
+    }
+
+    /// Active and not yet expired at `now` (Unix seconds).
+    pub fn is_current(&self, now: i64) -> bool {
+        self.is_active && self.end_time > now
+    }
+}
+
+/// What a wallet is entitled to, derived from its current subscriptions.
+#[derive(Debug, Clone, Serialize)]
+pub struct Entitlement {
+    pub active: bool,
+    /// Highest tier among current subscriptions.
+    pub plan_tier: Option<u8>,
+    pub expires_at: Option<i64>,
+}
+
+impl Entitlement {
+    pub fn from_accounts(accounts: &[SubscriptionAccount], now: i64) -> Self {
+        let best = accounts.iter()
+            .filter(|a| a.is_current(now))
+            .max_by_key(|a| (a.plan_tier, a.end_time));
+
+        Self {
+            active: best.is_some(),
+            plan_tier: best.map(|a| a.plan_tier),
+            expires_at: best.map(|a| a.end_time),
+        }
+    }
+}
+
+/// Read-only access to `SubscriptionAccount`s. Works without a wallet key.
+pub struct SubscriptionReader {
+    rpc: RpcClient,
+    program_id: Pubkey,
+    cache_ttl: Duration,
+}
+
+impl SubscriptionReader {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let program_id = match config.solana_program_id.as_deref() {
+            Some(id) => Pubkey::from_str(id).map_err(|e| format!("Invalid SOLANA_PROGRAM_ID: {}", e))?,
+            None => idl::subscription_program().program_id(),
+        };
+        let rpc_url = config.solana_rpc_url.clone().unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
+
+        Ok(Self::new(rpc_url, program_id, Duration::from_secs(config.subscription_cache_seconds)))
+    }
+
+    pub fn new(rpc_url: String, program_id: Pubkey, cache_ttl: Duration) -> Self {
+        Self {
+            rpc: RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed()),
+            program_id,
+            cache_ttl,
+        }
+    }
+
+    /// Every `SubscriptionAccount` whose `user` is `wallet`, cached for `SUBSCRIPTION_CACHE_SECONDS`.
+    pub async fn subscriptions_for(&self, wallet: &Pubkey) -> Result<Vec<SubscriptionAccount>, String> {
+        let cache = CACHE.get_or_init(|| Arc::new(Mutex::new(HashMap::new())));
+        if let Some((accounts, fetched_at)) = cache.lock().unwrap().get(wallet) {
+            if fetched_at.elapsed() < self.cache_ttl {
+                return Ok(accounts.clone());
+            }
+        }
+
+        let user_filter = RpcFilterType::Memcmp(Memcmp::new_base58_encoded(USER_OFFSET, &wallet.to_bytes()));
+        let accounts = self.fetch(Some(user_filter)).await?;
+
+        cache.lock().unwrap().insert(*wallet, (accounts.clone(), Instant::now()));
+        Ok(accounts)
+    }
+
+    /// Every `SubscriptionAccount` of the program. Not cached.
+    pub async fn all_subscriptions(&self) -> Result<Vec<SubscriptionAccount>, String> {
+        self.fetch(None).await
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    pub(super) fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub async fn latest_blockhash(&self) -> Result<Hash, String> {
+        self.rpc.get_latest_blockhash()
+            .await
+            .map_err(|e| format!("getLatestBlockhash failed: {}", e))
+    }
+
+    async fn fetch(&self, filter: Option<RpcFilterType>) -> Result<Vec<SubscriptionAccount>, String> {
+        let discriminator = idl::subscription_program()
+            .account_discriminator("SubscriptionAccount")
+            .ok_or("IDL has no SubscriptionAccount")?;
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &discriminator))];
+        filters.extend(filter);
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+
+        let raw = self.rpc.get_program_accounts_with_config(&self.program_id, config)
+            .await
+            .map_err(|e| format!("getProgramAccounts failed: {}", e))?;
+
+        Ok(raw.iter()
+            .filter_map(|(address, account)| match SubscriptionAccount::decode(address, &account.data) {
+                Ok(decoded) => Some(decoded),
+                Err(e) => {
+                    println!("⚠️ Skipping account {}: {}", address, e);
+                    None
+                },
+            })
+            .collect())
+    }
+}
+
+/// Drops the cached subscriptions of `wallet`, e.g. after sending it a transaction.
+pub fn invalidate(wallet: &Pubkey) {
+    if let Some(cache) = CACHE.get() {
+        cache.lock().unwrap().remove(wallet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(user: &Pubkey, plan_tier: u8, start_time: i64, end_time: i64, is_active: bool) -> Vec<u8> {
+        let mut data = idl::subscription_program().account_discriminator("SubscriptionAccount").unwrap().to_vec();
+        data.extend_from_slice(&user.to_bytes());
+        data.push(plan_tier);
+        data.extend_from_slice(&start_time.to_le_bytes());
+        data.extend_from_slice(&end_time.to_le_bytes());
+        data.push(is_active as u8);
+        data
+    }
+
+    fn account(plan_tier: u8, end_time: i64, is_active: bool) -> SubscriptionAccount {
+        SubscriptionAccount {
+            address: Pubkey::new_unique().to_string(),
+            user: Pubkey::new_unique().to_string(),
+            plan_tier,
+            start_time: 0,
+            end_time,
+            is_active,
+        }
+    }
+
+    #[test]
+    fn decodes_subscription_accounts() {
+        let (address, user) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = encoded(&user, 2, 1_700_000_000, 1_702_592_000, true);
+        assert_eq!(&data[..8], &[247, 1, 6, 72, 172, 66, 24, 128]);
+
+        let account = SubscriptionAccount::decode(&address, &data).unwrap();
+        assert_eq!((account.address, account.user), (address.to_string(), user.to_string()));
+        assert_eq!((account.plan_tier, account.start_time, account.end_time, account.is_active), (2, 1_700_000_000, 1_702_592_000, true));
+
+        let mut foreign = data.clone();
+        foreign[0] ^= 1;
+        assert_eq!(SubscriptionAccount::decode(&address, &foreign).unwrap_err(), "Account is not a SubscriptionAccount");
+        assert!(SubscriptionAccount::decode(&address, &data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn entitlement_comes_from_the_best_current_subscription() {
+        let now = 1_700_000_000;
+        let accounts = [
+            account(3, now - 1, true),
+            account(3, now + 100, false),
+            account(1, now + 500, true),
+            account(2, now + 100, true),
+            account(2, now + 200, true),
+        ];
+
+        let entitlement = Entitlement::from_accounts(&accounts, now);
+        assert_eq!((entitlement.active, entitlement.plan_tier, entitlement.expires_at), (true, Some(2), Some(now + 200)));
+        // Expired exactly at `now`
+        let entitlement = Entitlement::from_accounts(&[account(1, now, true)], now);
+        assert_eq!((entitlement.active, entitlement.plan_tier, entitlement.expires_at), (false, None, None));
+        assert!(!Entitlement::from_accounts(&[], now).active);
+    }
+}
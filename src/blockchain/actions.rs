@@ -0,0 +1,379 @@
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::str::FromStr;
+
+use super::errors::{SendError, SubscriptionError};
+use super::{accounts, idl, SolanaClient};
+use crate::events;
+use crate::storage::actions::{ActionRun, ActionSpec, ConditionAction, RunMode, RunStatus};
+use crate::storage::conditions::{ConditionTrigger, PriceCondition};
+use crate::storage::Database;
+
+// Templates in action accounts and args are filled from the trigger:
+// {{price}}, {{reference_price}}, {{symbol}}, {{condition_id}}, {{action_id}},
+// {{trigger_id}}, {{triggered_at}}, {{window}} and {{wallet}} (the backend wallet).
+
+/// As an account, a fresh keypair that co-signs, e.g. the `subscription` of `subscribe`.
+pub const NEW_ACCOUNT: &str = "{{new_account}}";
+/// One send per action per idempotency window unless the action sets its own key.
+const DEFAULT_IDEMPOTENCY_KEY: &str = "action-{{action_id}}-{{window}}";
+const DEFAULT_IDEMPOTENCY_WINDOW_SECONDS: i64 = 86_400;
+
+fn variables(action: &ConditionAction, trigger: &ConditionTrigger, wallet: Pubkey) -> Vec<(&'static str, String)> {
+    let window = action.spec.idempotency_window_seconds.unwrap_or(DEFAULT_IDEMPOTENCY_WINDOW_SECONDS).max(1);
+    vec![
+        ("price", trigger.price.to_string()),
+        ("reference_price", trigger.reference_price.map(|p| p.to_string()).unwrap_or_default()),
+        ("symbol", trigger.symbol.clone()),
+        ("condition_id", action.condition_id.to_string()),
+        ("action_id", action.id.to_string()),
+        ("trigger_id", trigger.id.to_string()),
+        ("triggered_at", trigger.triggered_at.to_string()),
+        ("window", (trigger.triggered_at / window).to_string()),
+        ("wallet", wallet.to_string()),
+    ]
+}
+
+fn render(template: &str, variables: &[(&str, String)]) -> String {
+    variables.iter().fold(template.to_string(), |rendered, (name, value)| {
+        rendered.replace(&format!("{{{{{}}}}}", name), value)
+    })
+}
+
+fn render_value(value: &Value, variables: &[(&str, String)]) -> Value {
+    match value {
+        Value::String(s) => Value::String(render(s, variables)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| render_value(v, variables)).collect()),
+        Value::Object(map) => Value::Object(map.iter().map(|(k, v)| (k.clone(), render_value(v, variables))).collect()),
+        other => other.clone(),
+    }
+}
+
+/// An action rendered for one trigger, ready to simulate or send.
+pub struct PreparedAction {
+    pub instruction: Instruction,
+    /// Keypair behind `{{new_account}}`, co-signing the transaction.
+    pub new_account: Option<Keypair>,
+    /// Rendered accounts and args, as written to the action log.
+    pub rendered: Value,
+}
+
+/// Renders `spec` and builds its instruction. Signer accounts must be the backend
+/// wallet or `{{new_account}}`, since nothing else can sign.
+fn prepare(spec: &ActionSpec, program_id: Pubkey, variables: &[(&str, String)], wallet: Pubkey) -> Result<PreparedAction, String> {
+    let idl = idl::subscription_program();
+    let definition = idl.instruction(&spec.instruction)?;
+    if let Some(unknown) = spec.accounts.keys().find(|name| !definition.accounts.iter().any(|a| &a.name == *name)) {
+        return Err(format!("{}: unknown account {}", spec.instruction, unknown));
+    }
+
+    let mut new_account: Option<Keypair> = None;
+    let mut named = Vec::new();
+    let mut rendered_accounts = Map::new();
+    for account in &definition.accounts {
+        // Missing ones are reported by build_instruction unless the IDL fixes them
+        let Some(template) = spec.accounts.get(&account.name) else {
+            continue;
+        };
+        let pubkey = if template.trim() == NEW_ACCOUNT {
+            new_account.get_or_insert_with(Keypair::new).pubkey()
+        } else {
+            let rendered = render(template, variables);
+            Pubkey::from_str(rendered.trim())
+                .map_err(|_| format!("{}: account {} is not a pubkey: {}", spec.instruction, account.name, rendered))?
+        };
+        let can_sign = pubkey == wallet || new_account.as_ref().is_some_and(|k| k.pubkey() == pubkey);
+        if account.signer && !can_sign {
+            return Err(format!(
+                "{}: account {} signs the transaction, so it must be {{{{wallet}}}} or {}",
+                spec.instruction, account.name, NEW_ACCOUNT
+            ));
+        }
+        rendered_accounts.insert(account.name.clone(), json!(pubkey.to_string()));
+        named.push((account.name.as_str(), pubkey));
+    }
+
+    let args = render_value(&Value::Object(spec.args.clone()), variables);
+    let instruction = idl.build_instruction(program_id, &spec.instruction, &named, &args)?;
+    Ok(PreparedAction {
+        instruction,
+        new_account,
+        rendered: json!({"accounts": rendered_accounts, "args": args}),
+    })
+}
+
+/// Checks that `spec` renders into a valid instruction, using the condition's own
+/// level as the trigger price.
+pub fn validate(spec: &ActionSpec, condition: &PriceCondition, solana: Option<&SolanaClient>) -> Result<(), String> {
+    if spec.idempotency_window_seconds.is_some_and(|w| w <= 0) {
+        return Err("idempotency_window_seconds must be positive".to_string());
+    }
+    if spec.idempotency_key.as_deref().is_some_and(|k| k.trim().is_empty()) {
+        return Err("idempotency_key must not be empty".to_string());
+    }
+
+    let action = ConditionAction { id: 0, condition_id: condition.id, spec: spec.clone(), created_at: 0 };
+    let trigger = sample_trigger(condition, None);
+    let (program_id, wallet) = match solana {
+        Some(client) => (client.program_id, client.wallet()),
+        None => (idl::subscription_program().program_id(), Pubkey::default()),
+    };
+    prepare(spec, program_id, &variables(&action, &trigger, wallet), wallet).map(|_| ())
+}
+
+/// A trigger at `price`, or at the condition's level, for simulations outside the engine.
+pub fn sample_trigger(condition: &PriceCondition, price: Option<f64>) -> ConditionTrigger {
+    ConditionTrigger {
+        id: 0,
+        condition_id: condition.id,
+        symbol: condition.spec.symbol.clone(),
+        price: price.unwrap_or(condition.spec.value),
+        reference_price: None,
+        triggered_at: Utc::now().timestamp(),
+    }
+}
+
+/// Result of `simulateTransaction`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Simulation {
+    pub error: Option<String>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+}
+
+impl SolanaClient {
+    /// Dry-runs `prepared` with the backend wallet as fee payer. Signatures are not
+    /// verified and the blockhash is replaced, so nothing is signed.
+    pub async fn simulate(&self, prepared: &PreparedAction) -> Result<Simulation, String> {
+        let transaction = Transaction::new_with_payer(std::slice::from_ref(&prepared.instruction), Some(&self.wallet()));
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+
+        let result = self.program()?
+            .async_rpc()
+            .simulate_transaction_with_config(&transaction, config)
+            .await
+            .map_err(|e| format!("simulateTransaction failed: {}", e))?
+            .value;
+
+        Ok(Simulation {
+            error: result.err.map(|e| match SubscriptionError::from_transaction_error(&e) {
+                Some(program_error) => program_error.to_string(),
+                None => e.to_string(),
+            }),
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+        })
+    }
+
+    /// Signs `prepared` with the backend wallet (and its new account) and sends it.
+    pub async fn send(&self, name: &str, prepared: &PreparedAction) -> Result<Signature, SendError> {
+        let program = self.program()?;
+        let mut request = program.request().instruction(prepared.instruction.clone());
+        if let Some(new_account) = &prepared.new_account {
+            request = request.signer(new_account);
+        }
+
+        let signature = request.send()
+            .await
+            .map_err(|e| SendError::from_client(name, e))?;
+        accounts::invalidate(&self.wallet());
+        Ok(signature)
+    }
+}
+
+/// Runs `action` for `trigger`, recording it in the action log. Dry-run actions are
+/// always simulated. Sends are claimed under their idempotency key first and return
+/// `None` when the key was already used, so a flapping price cannot send twice.
+pub async fn execute(
+    action: &ConditionAction,
+    trigger: &ConditionTrigger,
+    mode: RunMode,
+    db: &Database,
+    solana: Option<&SolanaClient>,
+) -> Result<Option<ActionRun>, String> {
+    let client = solana.ok_or("No Solana wallet is configured, condition actions are disabled")?;
+    let mode = if action.spec.dry_run { RunMode::Simulate } else { mode };
+    let variables = variables(action, trigger, client.wallet());
+    let now = Utc::now().timestamp();
+
+    let prepared = prepare(&action.spec, client.program_id, &variables, client.wallet());
+    let mut run = ActionRun {
+        id: 0,
+        action_id: action.id,
+        condition_id: action.condition_id,
+        idempotency_key: (mode == RunMode::Send).then(|| {
+            render(action.spec.idempotency_key.as_deref().unwrap_or(DEFAULT_IDEMPOTENCY_KEY), &variables)
+        }),
+        mode,
+        status: RunStatus::Pending,
+        instruction: action.spec.instruction.clone(),
+        args: prepared.as_ref().map(|p| p.rendered.clone()).unwrap_or_else(|_| json!({})),
+        price: Some(trigger.price),
+        signature: None,
+        error: None,
+        logs: Vec::new(),
+        units_consumed: None,
+        attempts: 1,
+        duplicates: 0,
+        created_at: now,
+        updated_at: now,
+    };
+
+    let Some(id) = db.claim_action_run(&run)? else {
+        println!("⏭️ Action {} skipped, idempotency key {} already used", action.id, run.idempotency_key.as_deref().unwrap_or_default());
+        return Ok(None);
+    };
+    run.id = id;
+
+    match prepared {
+        Err(e) => {
+            run.status = RunStatus::Failed;
+            run.error = Some(e);
+        },
+        Ok(prepared) if mode == RunMode::Simulate => match client.simulate(&prepared).await {
+            Ok(simulation) => {
+                run.status = if simulation.error.is_some() { RunStatus::Failed } else { RunStatus::Simulated };
+                run.error = simulation.error;
+                run.logs = simulation.logs;
+                run.units_consumed = simulation.units_consumed;
+            },
+            Err(e) => {
+                run.status = RunStatus::Failed;
+                run.error = Some(e);
+            },
+        },
+        Ok(prepared) => match client.send(&action.spec.instruction, &prepared).await {
+            Ok(signature) => {
+                run.status = RunStatus::Submitted;
+                run.signature = Some(signature.to_string());
+            },
+            Err(SendError::Program(e)) => {
+                run.status = RunStatus::Failed;
+                run.error = Some(e.to_string());
+            },
+            Err(SendError::Client(e)) => {
+                run.status = RunStatus::Unconfirmed;
+                run.error = Some(e);
+            },
+        },
+    }
+
+    run.updated_at = Utc::now().timestamp();
+    db.finish_action_run(&run)?;
+    Ok(Some(db.action_run(id)?.unwrap_or(run)))
+}
+
+/// Runs every action of a condition that just fired.
+pub async fn run_triggered(trigger: &ConditionTrigger, db: &Database, solana: Option<&SolanaClient>) {
+    let actions = match db.actions(Some(trigger.condition_id)) {
+        Ok(actions) => actions,
+        Err(e) => {
+            println!("❌ Loading actions of condition {} failed: {}", trigger.condition_id, e);
+            return;
+        },
+    };
+
+    for action in actions {
+        match execute(&action, trigger, RunMode::Send, db, solana).await {
+            Ok(Some(run)) => {
+                match run.status {
+                    RunStatus::Submitted => println!("⛓️ Action {} sent {}: {}", action.id, run.instruction, run.signature.as_deref().unwrap_or_default()),
+                    RunStatus::Simulated => println!("🧪 Action {} simulated {} ({} units)", action.id, run.instruction, run.units_consumed.unwrap_or_default()),
+                    _ => println!("❌ Action {} {}: {}", action.id, run.status.as_str(), run.error.as_deref().unwrap_or_default()),
+                }
+                events::publish("conditions", &trigger.symbol, "action_run", json!(run));
+            },
+            Ok(None) => {},
+            Err(e) => println!("❌ Action {} failed: {}", action.id, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_key(trigger_id: i64, triggered_at: i64) -> String {
+        let spec: ActionSpec = serde_json::from_value(json!({"instruction": "subscribe"})).unwrap();
+        let action = ConditionAction { id: 7, condition_id: 3, spec, created_at: 0 };
+        let trigger = ConditionTrigger {
+            id: trigger_id,
+            condition_id: 3,
+            symbol: "BTC".to_string(),
+            price: 100_000.0,
+            reference_price: None,
+            triggered_at,
+        };
+        render(DEFAULT_IDEMPOTENCY_KEY, &variables(&action, &trigger, Pubkey::default()))
+    }
+
+    #[test]
+    fn default_key_follows_the_window_not_the_firing() {
+        let midnight = 1_700_006_400;
+        assert_eq!(default_key(41, midnight - 1), "action-7-19675");
+        // Firing again within the same day reuses the key
+        assert_eq!(default_key(41, midnight - 1), default_key(42, midnight - 3600));
+        // and the next day starts a new window
+        assert_ne!(default_key(41, midnight - 1), default_key(42, midnight + 1));
+    }
+
+    fn claim(db: &Database, key: String) -> Option<i64> {
+        db.claim_action_run(&ActionRun {
+            id: 0,
+            action_id: 7,
+            condition_id: 3,
+            idempotency_key: Some(key),
+            mode: RunMode::Send,
+            status: RunStatus::Pending,
+            instruction: "subscribe".to_string(),
+            args: json!({}),
+            price: Some(100_000.0),
+            signature: None,
+            error: None,
+            logs: Vec::new(),
+            units_consumed: None,
+            attempts: 1,
+            duplicates: 0,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap()
+    }
+
+    #[test]
+    fn a_flapping_price_sends_once_per_window() {
+        let db = Database::in_memory().unwrap();
+        let midnight = 1_700_006_400;
+
+        let first = claim(&db, default_key(41, midnight - 60)).unwrap();
+        assert_eq!(claim(&db, default_key(42, midnight - 30)), None);
+        assert_eq!(db.action_run(first).unwrap().unwrap().duplicates, 1);
+        assert!(claim(&db, default_key(43, midnight + 30)).is_some());
+    }
+
+    #[test]
+    fn only_provable_failures_are_claimed_again() {
+        let db = Database::in_memory().unwrap();
+        for (status, retried) in [(RunStatus::Failed, true), (RunStatus::Unconfirmed, false), (RunStatus::Submitted, false)] {
+            let key = format!("key-{}", status.as_str());
+            let id = claim(&db, key.clone()).unwrap();
+            let mut run = db.action_run(id).unwrap().unwrap();
+            run.status = status;
+            db.finish_action_run(&run).unwrap();
+
+            assert_eq!(claim(&db, key).is_some(), retried, "{:?}", status);
+        }
+        assert_eq!(RunStatus::parse("unconfirmed"), Some(RunStatus::Unconfirmed));
+    }
+}
@@ -0,0 +1,131 @@
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::transaction::TransactionError;
+use std::fmt;
+
+use super::idl;
+
+/// Custom errors of `subscription_program`, mapped from the IDL `errors` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionError {
+    InvalidStatus,
+    InvalidPayment,
+    AlreadyActive,
+}
+
+impl SubscriptionError {
+    /// Error name in the IDL.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SubscriptionError::InvalidStatus => "InvalidStatus",
+            SubscriptionError::InvalidPayment => "InvalidPayment",
+            SubscriptionError::AlreadyActive => "AlreadyActive",
+        }
+    }
+
+    /// Maps a `Custom(code)` program error. Unknown codes are not ours.
+    pub fn from_code(code: u32) -> Option<Self> {
+        match idl::subscription_program().error(code)?.name.as_str() {
+            "InvalidStatus" => Some(SubscriptionError::InvalidStatus),
+            "InvalidPayment" => Some(SubscriptionError::InvalidPayment),
+            "AlreadyActive" => Some(SubscriptionError::AlreadyActive),
+            _ => None,
+        }
+    }
+
+    /// The program error behind a failed transaction, if it is one of ours.
+    pub fn from_transaction_error(error: &TransactionError) -> Option<Self> {
+        match error {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> u32 {
+        self.idl_entry().map(|e| e.code).unwrap_or_default()
+    }
+
+    pub fn message(&self) -> &'static str {
+        self.idl_entry().and_then(|e| e.msg.as_deref()).unwrap_or(self.name())
+    }
+
+    fn idl_entry(&self) -> Option<&'static idl::IdlErrorCode> {
+        idl::subscription_program().errors.iter().find(|e| e.name == self.name())
+    }
+}
+
+impl fmt::Display for SubscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.name(), self.code(), self.message())
+    }
+}
+
+/// Why a `subscription_program` transaction was not confirmed.
+#[derive(Debug, Clone)]
+pub enum SendError {
+    /// The program rejected the instruction.
+    Program(SubscriptionError),
+    /// Building, signing or submitting the transaction failed.
+    Client(String),
+}
+
+impl SendError {
+    /// Picks the program's custom error out of a failed send, whether it surfaced
+    /// in preflight simulation or in the confirmed transaction.
+    pub fn from_client(instruction: &str, error: anchor_client::ClientError) -> Self {
+        let program_error = match &error {
+            anchor_client::ClientError::SolanaClientError(e) => e.get_transaction_error()
+                .and_then(|e| SubscriptionError::from_transaction_error(&e)),
+            anchor_client::ClientError::ProgramError(ProgramError::Custom(code)) => SubscriptionError::from_code(*code),
+            _ => None,
+        };
+
+        match program_error {
+            Some(program_error) => SendError::Program(program_error),
+            None => SendError::Client(format!("{} failed: {}", instruction, error)),
+        }
+    }
+}
+
+impl From<String> for SendError {
+    fn from(message: String) -> Self {
+        SendError::Client(message)
+    }
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendError::Program(e) => write!(f, "Program error {}", e),
+            SendError::Client(message) => f.write_str(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_program_codes_to_typed_errors() {
+        assert_eq!(SubscriptionError::from_code(6000), Some(SubscriptionError::InvalidStatus));
+        assert_eq!(SubscriptionError::from_code(6001), Some(SubscriptionError::InvalidPayment));
+        assert_eq!(SubscriptionError::from_code(6002), Some(SubscriptionError::AlreadyActive));
+        assert_eq!(SubscriptionError::from_code(6003), None);
+        assert_eq!(SubscriptionError::from_code(0), None);
+
+        let error = SubscriptionError::AlreadyActive;
+        assert_eq!((error.code(), error.message()), (6002, "Subscription already active"));
+        assert_eq!(error.to_string(), "AlreadyActive (6002): Subscription already active");
+    }
+
+    #[test]
+    fn picks_our_error_out_of_failed_transactions() {
+        let custom = |code| TransactionError::InstructionError(1, InstructionError::Custom(code));
+
+        assert_eq!(SubscriptionError::from_transaction_error(&custom(6001)), Some(SubscriptionError::InvalidPayment));
+        assert_eq!(SubscriptionError::from_transaction_error(&custom(1)), None);
+        assert_eq!(SubscriptionError::from_transaction_error(&TransactionError::InstructionError(0, InstructionError::InvalidArgument)), None);
+        assert_eq!(SubscriptionError::from_transaction_error(&TransactionError::BlockhashNotFound), None);
+    }
+}
@@ -0,0 +1,249 @@
+use serde::Serialize;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::native_token::lamports_to_sol;
+use solana_sdk::pubkey::Pubkey;
+use std::time::Instant;
+
+use super::accounts::SubscriptionReader;
+
+/// Whether the RPC node answers, and what it reports.
+#[derive(Debug, Serialize)]
+pub struct RpcHealth {
+    pub healthy: bool,
+    /// `solana-core` version of the node.
+    pub version: Option<String>,
+    pub slot: Option<u64>,
+    /// Round trip of `getVersion`.
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// The configured program id as seen on-chain.
+#[derive(Debug, Serialize)]
+pub struct ProgramStatus {
+    pub program_id: String,
+    pub deployed: bool,
+    pub executable: bool,
+    /// Loader owning the program account.
+    pub owner: Option<String>,
+    pub upgradeable: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecentTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WalletInfo {
+    pub address: String,
+    pub balance_lamports: u64,
+    pub balance_sol: f64,
+    /// Newest first.
+    pub recent_transactions: Vec<RecentTransaction>,
+}
+
+impl SubscriptionReader {
+    /// Formats an RPC error without the node URL, which may carry an API key.
+    fn rpc_error(&self, call: &str, error: impl std::fmt::Display) -> String {
+        format!("{} failed: {}", call, error).replace(&self.rpc().url(), "<rpc>")
+    }
+
+    /// Never fails: an unreachable node is reported as unhealthy.
+    pub async fn rpc_health(&self) -> RpcHealth {
+        let started = Instant::now();
+        let version = self.rpc().get_version().await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let version = match version {
+            Ok(version) => version.solana_core,
+            Err(e) => return RpcHealth {
+                healthy: false,
+                version: None,
+                slot: None,
+                latency_ms,
+                error: Some(self.rpc_error("getVersion", e)),
+            },
+        };
+
+        match self.rpc().get_slot().await {
+            Ok(slot) => RpcHealth { healthy: true, version: Some(version), slot: Some(slot), latency_ms, error: None },
+            Err(e) => RpcHealth {
+                healthy: false,
+                version: Some(version),
+                slot: None,
+                latency_ms,
+                error: Some(self.rpc_error("getSlot", e)),
+            },
+        }
+    }
+
+    pub async fn program_status(&self) -> Result<ProgramStatus, String> {
+        let account = self.rpc()
+            .get_account_with_commitment(&self.program_id(), CommitmentConfig::confirmed())
+            .await
+            .map_err(|e| self.rpc_error("getAccountInfo", e))?
+            .value;
+
+        Ok(ProgramStatus {
+            program_id: self.program_id().to_string(),
+            deployed: account.is_some(),
+            executable: account.as_ref().is_some_and(|a| a.executable),
+            owner: account.as_ref().map(|a| a.owner.to_string()),
+            upgradeable: account.as_ref().is_some_and(|a| a.owner == bpf_loader_upgradeable::id()),
+        })
+    }
+
+    /// SOL balance and the `limit` most recent transactions of `wallet`.
+    pub async fn wallet_info(&self, wallet: &Pubkey, limit: usize) -> Result<WalletInfo, String> {
+        let balance_lamports = self.rpc()
+            .get_balance(wallet)
+            .await
+            .map_err(|e| self.rpc_error("getBalance", e))?;
+
+        let config = GetConfirmedSignaturesForAddress2Config {
+            limit: Some(limit),
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..GetConfirmedSignaturesForAddress2Config::default()
+        };
+        let signatures = self.rpc()
+            .get_signatures_for_address_with_config(wallet, config)
+            .await
+            .map_err(|e| self.rpc_error("getSignaturesForAddress", e))?;
+
+        Ok(WalletInfo {
+            address: wallet.to_string(),
+            balance_lamports,
+            balance_sol: lamports_to_sol(balance_lamports),
+            recent_transactions: signatures.into_iter()
+                .map(|s| RecentTransaction {
+                    success: s.err.is_none(),
+                    error: s.err.map(|e| e.to_string()),
+                    signature: s.signature,
+                    slot: s.slot,
+                    block_time: s.block_time,
+                    memo: s.memo,
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use serde_json::{json, Value};
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    const PROGRAM: &str = "CSZVnUNFz2nDrbCrLZgPH8m7PPLVNLvJoRU8bREh4FHA";
+    const WALLET: &str = "3SRZvZ22TGMfwV2jna2Dij1ZtT5ZuHw29yiQUycPEPiY";
+
+    fn context(value: Value) -> Value {
+        json!({"context": {"slot": 250_000_000u64}, "value": value})
+    }
+
+    async fn json_rpc(request: web::Json<Value>) -> HttpResponse {
+        let params = &request["params"];
+        let result = match request["method"].as_str().unwrap() {
+            "getVersion" => json!({"solana-core": "1.18.26", "feature-set": 3_469_865_029u64}),
+            "getSlot" => json!(250_000_123u64),
+            "getBalance" => context(json!(1_500_000_000u64)),
+            "getAccountInfo" if params[0] == PROGRAM => context(json!({
+                "data": ["", "base64"],
+                "executable": true,
+                "lamports": 1_141_440,
+                "owner": bpf_loader_upgradeable::id().to_string(),
+                "rentEpoch": 0,
+                "space": 36,
+            })),
+            "getAccountInfo" => context(Value::Null),
+            "getSignaturesForAddress" => {
+                assert_eq!(params[1]["limit"], 2);
+                json!([
+                    {"signature": "5XHZeTpN2xg3vBFikTV8bos8gheBUeDX1htQWrvYc2zDrmy4dQkhk3AbooDYUfcEL9sLFVbZdZt9GqmexmPbKCBB",
+                     "slot": 250_000_100u64, "err": null, "memo": null, "blockTime": 1_792_000_000, "confirmationStatus": "finalized"},
+                    {"signature": "289Ne9qEAE2SSSDQw9ZzeQWvb9cYBKF6cEz9jYMxMk8v7JwdDyq4Fzp773QkZEpuFQ4H66Yyy39PybEiv1ZxU6vg",
+                     "slot": 250_000_050u64, "err": {"InstructionError": [0, {"Custom": 6001}]}, "memo": "renewal",
+                     "blockTime": 1_791_999_000, "confirmationStatus": "finalized"},
+                ])
+            },
+            other => panic!("unexpected RPC method {}", other),
+        };
+        HttpResponse::Ok().json(json!({"jsonrpc": "2.0", "id": request["id"], "result": result}))
+    }
+
+    fn start_mock_rpc() -> String {
+        let server = HttpServer::new(|| App::new().route("/", web::post().to(json_rpc)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let addr = server.addrs()[0];
+        actix_rt::spawn(server.run());
+        format!("http://{}", addr)
+    }
+
+    fn reader(rpc_url: String, program_id: &str) -> SubscriptionReader {
+        SubscriptionReader::new(rpc_url, Pubkey::from_str(program_id).unwrap(), Duration::from_secs(60))
+    }
+
+    #[actix_rt::test]
+    async fn reports_rpc_version_and_slot() {
+        let health = reader(start_mock_rpc(), PROGRAM).rpc_health().await;
+
+        assert!(health.healthy, "{:?}", health.error);
+        assert_eq!(health.version.as_deref(), Some("1.18.26"));
+        assert_eq!(health.slot, Some(250_000_123));
+    }
+
+    #[actix_rt::test]
+    async fn reports_unreachable_rpc_as_unhealthy() {
+        let health = reader("http://127.0.0.1:1".to_string(), PROGRAM).rpc_health().await;
+
+        assert!(!health.healthy);
+        assert_eq!(health.slot, None);
+        let error = health.error.unwrap();
+        assert!(error.contains("getVersion"), "{}", error);
+        assert!(!error.contains("127.0.0.1:1"), "{}", error);
+    }
+
+    #[actix_rt::test]
+    async fn reports_deployed_program() {
+        let status = reader(start_mock_rpc(), PROGRAM).program_status().await.unwrap();
+
+        assert!(status.deployed);
+        assert!(status.executable);
+        assert!(status.upgradeable);
+    }
+
+    #[actix_rt::test]
+    async fn reports_missing_program() {
+        let status = reader(start_mock_rpc(), WALLET).program_status().await.unwrap();
+
+        assert!(!status.deployed);
+        assert!(!status.executable);
+        assert_eq!(status.owner, None);
+    }
+
+    #[actix_rt::test]
+    async fn reports_wallet_balance_and_recent_transactions() {
+        let wallet = Pubkey::from_str(WALLET).unwrap();
+
+        let info = reader(start_mock_rpc(), PROGRAM).wallet_info(&wallet, 2).await.unwrap();
+
+        assert_eq!(info.balance_lamports, 1_500_000_000);
+        assert_eq!(info.balance_sol, 1.5);
+        assert_eq!(info.recent_transactions.len(), 2);
+        assert!(info.recent_transactions[0].success);
+        assert!(!info.recent_transactions[1].success);
+        assert_eq!(info.recent_transactions[1].memo.as_deref(), Some("renewal"));
+    }
+}
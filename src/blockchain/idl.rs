@@ -0,0 +1,224 @@
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+static SUBSCRIPTION_IDL: OnceLock<Idl> = OnceLock::new();
+
+/// The Anchor IDL of `subscription_program`, embedded at build time.
+pub fn subscription_program() -> &'static Idl {
+    SUBSCRIPTION_IDL.get_or_init(|| {
+        serde_json::from_str(include_str!("../../idl/subscription_program.json"))
+            .expect("idl/subscription_program.json is not a valid Anchor IDL")
+    })
+}
+
+/// The subset of the Anchor IDL format (spec 0.1.0) this backend needs.
+#[derive(Debug, Deserialize)]
+pub struct Idl {
+    pub address: String,
+    pub instructions: Vec<IdlInstruction>,
+    #[serde(default)]
+    pub accounts: Vec<IdlAccount>,
+    #[serde(default)]
+    pub types: Vec<IdlTypeDef>,
+    #[serde(default)]
+    pub errors: Vec<IdlErrorCode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlInstruction {
+    pub name: String,
+    pub discriminator: [u8; 8],
+    pub accounts: Vec<IdlInstructionAccount>,
+    pub args: Vec<IdlField>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlInstructionAccount {
+    pub name: String,
+    #[serde(default)]
+    pub writable: bool,
+    #[serde(default)]
+    pub signer: bool,
+    /// Fixed address, e.g. the system program.
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlAccount {
+    pub name: String,
+    pub discriminator: [u8; 8],
+}
+
+/// A custom program error, reported on-chain as `Custom(code)`.
+#[derive(Debug, Deserialize)]
+pub struct IdlErrorCode {
+    pub code: u32,
+    pub name: String,
+    pub msg: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlTypeDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: IdlTypeBody,
+}
+
+/// Struct types only; enums are not used by `subscription_program`.
+#[derive(Debug, Deserialize)]
+pub struct IdlTypeBody {
+    #[serde(default)]
+    pub fields: Vec<IdlField>,
+}
+
+impl Idl {
+    pub fn program_id(&self) -> Pubkey {
+        Pubkey::from_str(&self.address).expect("IDL address is not a valid pubkey")
+    }
+
+    pub fn instruction(&self, name: &str) -> Result<&IdlInstruction, String> {
+        self.instructions.iter()
+            .find(|ix| ix.name == name)
+            .ok_or_else(|| format!("Unknown instruction: {}", name))
+    }
+
+    /// Builds an instruction from named accounts and a JSON object of arguments.
+    /// Accounts with a fixed address in the IDL need not be passed.
+    pub fn build_instruction(
+        &self,
+        program_id: Pubkey,
+        name: &str,
+        accounts: &[(&str, Pubkey)],
+        args: &Value,
+    ) -> Result<Instruction, String> {
+        let ix = self.instruction(name)?;
+
+        let metas = ix.accounts.iter()
+            .map(|account| {
+                let pubkey = match accounts.iter().find(|(n, _)| *n == account.name) {
+                    Some((_, pubkey)) => *pubkey,
+                    None => account.address.as_deref()
+                        .and_then(|a| Pubkey::from_str(a).ok())
+                        .ok_or_else(|| format!("{}: missing account {}", name, account.name))?,
+                };
+                Ok(if account.writable {
+                    AccountMeta::new(pubkey, account.signer)
+                } else {
+                    AccountMeta::new_readonly(pubkey, account.signer)
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut data = ix.discriminator.to_vec();
+        for field in &ix.args {
+            let value = args.get(&field.name)
+                .ok_or_else(|| format!("{}: missing argument {}", name, field.name))?;
+            encode(&field.ty, value, &mut data)
+                .map_err(|e| format!("{}: argument {}: {}", name, field.name, e))?;
+        }
+
+        Ok(Instruction { program_id, accounts: metas, data })
+    }
+
+    /// Matches instruction data against the IDL discriminators and decodes its args.
+    pub fn decode_instruction(&self, data: &[u8]) -> Option<Result<(&IdlInstruction, Value), String>> {
+        let ix = self.instructions.iter().find(|ix| data.starts_with(&ix.discriminator))?;
+        Some(decode_fields(&ix.args, &mut &data[8..]).map(|args| (ix, args)))
+    }
+
+    pub fn error(&self, code: u32) -> Option<&IdlErrorCode> {
+        self.errors.iter().find(|e| e.code == code)
+    }
+
+    pub fn account_discriminator(&self, name: &str) -> Option<[u8; 8]> {
+        self.accounts.iter().find(|a| a.name == name).map(|a| a.discriminator)
+    }
+
+    /// Decodes an account of type `name` into a JSON object, checking its discriminator.
+    pub fn decode_account(&self, name: &str, data: &[u8]) -> Result<Value, String> {
+        let discriminator = self.account_discriminator(name)
+            .ok_or_else(|| format!("Unknown account type: {}", name))?;
+        if !data.starts_with(&discriminator) {
+            return Err(format!("Account is not a {}", name));
+        }
+
+        let ty = self.types.iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| format!("Missing type definition for {}", name))?;
+        decode_fields(&ty.ty.fields, &mut &data[8..])
+    }
+}
+
+/// Borsh-encodes one argument. Integers may be given as JSON numbers or strings.
+fn encode(ty: &str, value: &Value, out: &mut Vec<u8>) -> Result<(), String> {
+    let int = || -> Result<i128, String> {
+        match value {
+            Value::Number(n) => n.as_i64().map(i128::from)
+                .or_else(|| n.as_u64().map(i128::from))
+                .ok_or_else(|| format!("expected an integer, got {}", n)),
+            Value::String(s) => s.trim().parse().map_err(|_| format!("expected an integer, got {:?}", s)),
+            other => Err(format!("expected an integer, got {}", other)),
+        }
+    };
+    let out_of_range = |v: i128| format!("{} is out of range for {}", v, ty);
+
+    match ty {
+        "u8" => { let v = int()?; out.push(u8::try_from(v).map_err(|_| out_of_range(v))?) },
+        "u16" => { let v = int()?; out.extend(u16::try_from(v).map_err(|_| out_of_range(v))?.to_le_bytes()) },
+        "u32" => { let v = int()?; out.extend(u32::try_from(v).map_err(|_| out_of_range(v))?.to_le_bytes()) },
+        "u64" => { let v = int()?; out.extend(u64::try_from(v).map_err(|_| out_of_range(v))?.to_le_bytes()) },
+        "i64" => { let v = int()?; out.extend(i64::try_from(v).map_err(|_| out_of_range(v))?.to_le_bytes()) },
+        "bool" => out.push(value.as_bool().ok_or_else(|| format!("expected a boolean, got {}", value))? as u8),
+        "pubkey" => {
+            let key = value.as_str()
+                .and_then(|s| Pubkey::from_str(s).ok())
+                .ok_or_else(|| format!("expected a base58 pubkey, got {}", value))?;
+            out.extend(key.to_bytes());
+        },
+        other => return Err(format!("unsupported IDL type {}", other)),
+    }
+    Ok(())
+}
+
+fn decode_fields(fields: &[IdlField], data: &mut &[u8]) -> Result<Value, String> {
+    let mut object = Map::new();
+    for field in fields {
+        let value = decode(&field.ty, data).map_err(|e| format!("{}: {}", field.name, e))?;
+        object.insert(field.name.clone(), value);
+    }
+    Ok(Value::Object(object))
+}
+
+/// Borsh-decodes one value, advancing `data` past it.
+fn decode(ty: &str, data: &mut &[u8]) -> Result<Value, String> {
+    fn take<const N: usize>(data: &mut &[u8]) -> Result<[u8; N], String> {
+        if data.len() < N {
+            return Err("data too short".to_string());
+        }
+        let (head, tail) = data.split_at(N);
+        *data = tail;
+        Ok(head.try_into().unwrap())
+    }
+
+    Ok(match ty {
+        "u8" => json!(take::<1>(data)?[0]),
+        "u16" => json!(u16::from_le_bytes(take(data)?)),
+        "u32" => json!(u32::from_le_bytes(take(data)?)),
+        "u64" => json!(u64::from_le_bytes(take(data)?)),
+        "i64" => json!(i64::from_le_bytes(take(data)?)),
+        "bool" => json!(take::<1>(data)?[0] != 0),
+        "pubkey" => json!(Pubkey::new_from_array(take(data)?).to_string()),
+        other => return Err(format!("unsupported IDL type {}", other)),
+    })
+}
@@ -0,0 +1,337 @@
+use actix_web::web;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::accounts::SubscriptionReader;
+use super::idl;
+use crate::config::Config;
+use crate::storage::history::{IndexerGap, SubscriptionEvent};
+use crate::storage::Database;
+
+/// Signatures fetched and indexed per run; the rest are picked up by later runs.
+const MAX_TRANSACTIONS_PER_RUN: usize = 500;
+
+/// What one indexer run did.
+#[derive(Debug, Default, Serialize)]
+pub struct IndexSummary {
+    pub transactions: usize,
+    pub events: usize,
+    /// Older transactions are still being backfilled.
+    pub backfilling: bool,
+}
+
+/// Indexes new program transactions every `INDEXER_INTERVAL_SECONDS`.
+pub fn spawn(config: &Config, db: web::Data<Database>, reader: web::Data<SubscriptionReader>) {
+    let interval = Duration::from_secs(config.indexer_interval_seconds);
+
+    tokio::spawn(async move {
+        loop {
+            match run(&db, &reader).await {
+                Ok(summary) if summary.transactions > 0 => println!(
+                    "📚 Indexed {} transactions ({} subscription events{})",
+                    summary.transactions, summary.events,
+                    if summary.backfilling { ", still backfilling" } else { "" }
+                ),
+                Ok(_) => {},
+                Err(e) => println!("❌ Subscription indexer failed: {}", e),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// Indexes one page of the program's transaction signatures and stores every
+/// decoded top-level `subscription_program` instruction.
+///
+/// A run fetches the newest page back to the cursor. When that page is full, the
+/// older signatures between it and the cursor are recorded as a gap, and later
+/// runs walk the gap one page at a time before looking for new transactions, so
+/// no signature is fetched twice however large the backlog.
+pub async fn run(db: &Database, reader: &SubscriptionReader) -> Result<IndexSummary, String> {
+    let program_id = reader.program_id();
+    let address = program_id.to_string();
+    let mut summary = IndexSummary::default();
+
+    if let Some(gap) = db.indexer_gap(&address)? {
+        let page = signatures(reader, Some(&gap.before), gap.until.as_deref()).await?;
+        index(db, reader, &page, &mut summary).await?;
+
+        let gap = match page.last() {
+            Some(oldest) if page.len() == MAX_TRANSACTIONS_PER_RUN => Some(IndexerGap { before: oldest.signature.clone(), ..gap }),
+            _ => None,
+        };
+        summary.backfilling = gap.is_some();
+        db.set_indexer_gap(&address, gap.as_ref(), Utc::now().timestamp())?;
+        return Ok(summary);
+    }
+
+    let cursor = db.indexer_cursor(&address)?;
+    let page = signatures(reader, None, cursor.as_deref()).await?;
+    index(db, reader, &page, &mut summary).await?;
+
+    let (Some(newest), Some(oldest)) = (page.first(), page.last()) else {
+        return Ok(summary);
+    };
+    // The gap goes in before the cursor moves, so a crash in between repeats
+    // this page rather than skipping what lies behind it
+    if page.len() == MAX_TRANSACTIONS_PER_RUN {
+        let gap = IndexerGap { before: oldest.signature.clone(), until: cursor };
+        db.set_indexer_gap(&address, Some(&gap), Utc::now().timestamp())?;
+        summary.backfilling = true;
+    }
+    db.set_indexer_cursor(&address, &newest.signature, newest.slot, Utc::now().timestamp())?;
+
+    Ok(summary)
+}
+
+/// One page of the program's signatures, newest first, between `before` and `until`.
+async fn signatures(
+    reader: &SubscriptionReader,
+    before: Option<&str>,
+    until: Option<&str>,
+) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, String> {
+    let parse = |s: &str| Signature::from_str(s).map_err(|e| format!("Invalid indexer signature {}: {}", s, e));
+    let config = GetConfirmedSignaturesForAddress2Config {
+        before: before.map(parse).transpose()?,
+        until: until.map(parse).transpose()?,
+        limit: Some(MAX_TRANSACTIONS_PER_RUN),
+        commitment: Some(CommitmentConfig::confirmed()),
+    };
+    reader.rpc().get_signatures_for_address_with_config(&reader.program_id(), config)
+        .await
+        .map_err(|e| format!("getSignaturesForAddress failed: {}", e))
+}
+
+/// Fetches and decodes each transaction of `page`. Events are keyed by signature,
+/// so indexing a page again after a failed run stores nothing twice.
+async fn index(
+    db: &Database,
+    reader: &SubscriptionReader,
+    page: &[RpcConfirmedTransactionStatusWithSignature],
+    summary: &mut IndexSummary,
+) -> Result<(), String> {
+    for status in page {
+        let signature = Signature::from_str(&status.signature).map_err(|e| e.to_string())?;
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let transaction = reader.rpc().get_transaction_with_config(&signature, config)
+            .await
+            .map_err(|e| format!("getTransaction {} failed: {}", status.signature, e))?;
+
+        for event in decode_transaction(&status.signature, &transaction, &reader.program_id()) {
+            if db.insert_subscription_event(&event)? {
+                summary.events += 1;
+            }
+        }
+        summary.transactions += 1;
+    }
+    Ok(())
+}
+
+/// Decodes the top-level instructions addressed to `program_id`. Instructions
+/// reached through CPI are not indexed.
+pub(super) fn decode_transaction(
+    signature: &str,
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    program_id: &Pubkey,
+) -> Vec<SubscriptionEvent> {
+    let Some(decoded) = transaction.transaction.transaction.decode() else {
+        println!("⚠️ Cannot decode transaction {}", signature);
+        return Vec::new();
+    };
+    let meta = transaction.transaction.meta.as_ref();
+
+    // Versioned transactions append address-table lookups after the static keys
+    let mut keys = decoded.message.static_account_keys().to_vec();
+    if let Some(OptionSerializer::Some(loaded)) = meta.map(|m| &m.loaded_addresses) {
+        keys.extend(loaded.writable.iter().chain(&loaded.readonly).filter_map(|k| Pubkey::from_str(k).ok()));
+    }
+
+    let idl = idl::subscription_program();
+    decoded.message.instructions()
+        .iter()
+        .enumerate()
+        .filter(|(_, ix)| keys.get(ix.program_id_index as usize) == Some(program_id))
+        .filter_map(|(index, ix)| {
+            let (definition, args) = match idl.decode_instruction(&ix.data)? {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    println!("⚠️ Cannot decode instruction {} of {}: {}", index, signature, e);
+                    return None;
+                },
+            };
+            let account = |name: &str| definition.accounts.iter()
+                .position(|a| a.name == name)
+                .and_then(|position| ix.accounts.get(position))
+                .and_then(|key_index| keys.get(*key_index as usize))
+                .map(Pubkey::to_string);
+
+            Some(SubscriptionEvent {
+                signature: signature.to_string(),
+                instruction_index: index as u32,
+                slot: transaction.slot,
+                block_time: transaction.block_time,
+                instruction: definition.name.clone(),
+                user: account("user"),
+                subscription: account("subscription"),
+                plan_tier: args.get("plan_tier").or_else(|| args.get("new_tier"))
+                    .and_then(Value::as_u64)
+                    .and_then(|tier| u8::try_from(tier).ok()),
+                amount_paid: args.get("amount_paid").and_then(Value::as_u64),
+                period_days: args.get("period_days").and_then(Value::as_i64),
+                success: meta.is_some_and(|m| m.err.is_none()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::history::HistoryQuery;
+    use actix_web::{App, HttpResponse, HttpServer};
+    use base64::Engine;
+    use serde_json::json;
+    use solana_sdk::{system_instruction, transaction::Transaction};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// A `subscribe` after an unrelated transfer, as `getTransaction` returns it.
+    fn fixture(program_id: Pubkey, user: Pubkey, subscription: Pubkey, failed: bool) -> Value {
+        let subscribe = idl::subscription_program().build_instruction(
+            program_id,
+            "subscribe",
+            &[("user", user), ("subscription", subscription)],
+            &json!({"plan_tier": 2, "amount_paid": 99_000_000, "period_days": 30}),
+        ).unwrap();
+        let transfer = system_instruction::transfer(&user, &Pubkey::new_unique(), 1);
+        let transaction = Transaction::new_with_payer(&[transfer, subscribe], Some(&user));
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&transaction).unwrap());
+
+        json!({
+            "slot": 42,
+            "blockTime": 1_700_000_000,
+            "transaction": [encoded, "base64"],
+            "meta": {
+                "err": if failed { json!({"InstructionError": [1, {"Custom": 6000}]}) } else { Value::Null },
+                "status": if failed { json!({"Err": {"InstructionError": [1, {"Custom": 6000}]}}) } else { json!({"Ok": null}) },
+                "fee": 5000,
+                "preBalances": [],
+                "postBalances": [],
+            },
+        })
+    }
+
+    fn decode(transaction: &Value, program_id: &Pubkey) -> Vec<SubscriptionEvent> {
+        decode_transaction("sig", &serde_json::from_value(transaction.clone()).unwrap(), program_id)
+    }
+
+    #[test]
+    fn decodes_program_instructions_of_a_transaction() {
+        let (program_id, user, subscription) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let events = decode(&fixture(program_id, user, subscription, false), &program_id);
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!((event.signature.as_str(), event.instruction_index, event.instruction.as_str()), ("sig", 1, "subscribe"));
+        assert_eq!((event.slot, event.block_time), (42, Some(1_700_000_000)));
+        assert_eq!(event.user, Some(user.to_string()));
+        assert_eq!(event.subscription, Some(subscription.to_string()));
+        assert_eq!((event.plan_tier, event.amount_paid, event.period_days), (Some(2), Some(99_000_000), Some(30)));
+        assert!(event.success);
+
+        let failed = decode(&fixture(program_id, user, subscription, true), &program_id);
+        assert!(!failed[0].success);
+        assert!(decode(&fixture(program_id, user, subscription, false), &Pubkey::new_unique()).is_empty());
+    }
+
+    /// Program signatures newest first, and how often each RPC method was called.
+    #[derive(Default)]
+    struct Chain {
+        signatures: Mutex<Vec<String>>,
+        transaction: Mutex<Value>,
+        signature_pages: AtomicUsize,
+        transactions: AtomicUsize,
+    }
+
+    async fn rpc(body: web::Json<Value>, chain: web::Data<Arc<Chain>>) -> HttpResponse {
+        let result = match body["method"].as_str() {
+            Some("getSignaturesForAddress") => {
+                chain.signature_pages.fetch_add(1, Ordering::SeqCst);
+                let config = &body["params"][1];
+                let signatures = chain.signatures.lock().unwrap();
+                let start = config["before"].as_str()
+                    .map_or(0, |before| signatures.iter().position(|s| s == before).unwrap() + 1);
+                let page: Vec<Value> = signatures[start..].iter()
+                    .take_while(|s| Some(s.as_str()) != config["until"].as_str())
+                    .take(config["limit"].as_u64().unwrap() as usize)
+                    .map(|s| json!({ "signature": s, "slot": 42, "err": null, "memo": null, "blockTime": null }))
+                    .collect();
+                json!(page)
+            },
+            Some("getTransaction") => {
+                chain.transactions.fetch_add(1, Ordering::SeqCst);
+                chain.transaction.lock().unwrap().clone()
+            },
+            Some("getVersion") => json!({ "solana-core": "1.18.26", "feature-set": 0 }),
+            _ => Value::Null,
+        };
+        HttpResponse::Ok().json(json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }))
+    }
+
+    fn reader(chain: Arc<Chain>) -> SubscriptionReader {
+        let server = HttpServer::new(move || App::new()
+                .app_data(web::Data::new(chain.clone()))
+                .route("/", web::post().to(rpc)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let url = format!("http://{}", server.addrs()[0]);
+        actix_rt::spawn(server.run());
+        SubscriptionReader::new(url, Pubkey::new_unique(), Duration::from_secs(0))
+    }
+
+    fn new_signatures(count: usize) -> Vec<String> {
+        (0..count).map(|_| Signature::new_unique().to_string()).collect()
+    }
+
+    #[actix_rt::test]
+    async fn backfills_a_large_backlog_one_page_per_run() {
+        let chain = Arc::new(Chain::default());
+        *chain.signatures.lock().unwrap() = new_signatures(1200);
+        let (db, reader) = (Database::in_memory().unwrap(), reader(chain.clone()));
+        *chain.transaction.lock().unwrap() = fixture(reader.program_id(), Pubkey::new_unique(), Pubkey::new_unique(), false);
+
+        let mut runs = Vec::new();
+        for _ in 0..4 {
+            let summary = run(&db, &reader).await.unwrap();
+            runs.push((summary.transactions, summary.events, summary.backfilling));
+        }
+        assert_eq!(runs, [(500, 500, true), (500, 500, true), (200, 200, false), (0, 0, false)]);
+
+        // Transactions that land later are picked up from the cursor
+        chain.signatures.lock().unwrap().splice(0..0, new_signatures(3));
+        let summary = run(&db, &reader).await.unwrap();
+        assert_eq!((summary.transactions, summary.backfilling), (3, false));
+
+        // Every signature was fetched once, one page per run
+        assert_eq!(chain.transactions.load(Ordering::SeqCst), 1203);
+        assert_eq!(chain.signature_pages.load(Ordering::SeqCst), 5);
+        let (_, totals) = db.query_subscription_events(&HistoryQuery::default()).unwrap();
+        assert_eq!(totals.events, 1203);
+    }
+}
@@ -0,0 +1,354 @@
+use actix_web::web;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::accounts::{self, SubscriptionAccount, SubscriptionReader};
+use super::errors::{SendError, SubscriptionError};
+use super::subscription::{self, SubscriptionInstruction};
+use super::SolanaClient;
+use crate::config::Config;
+use crate::events;
+use crate::storage::lifecycle::{AutoRenew, LifecycleRecord, LifecycleStatus};
+use crate::storage::Database;
+
+/// Renewals that could not be sent, or were handed to the user and not signed,
+/// are retried after this long.
+const RENEWAL_RETRY_SECONDS: i64 = 6 * 3600;
+
+/// What one pass over the program's subscriptions did.
+#[derive(Debug, Default, Serialize)]
+pub struct ScanSummary {
+    pub scanned: usize,
+    pub reminders: usize,
+    pub lapsed: usize,
+    pub renewals: usize,
+    pub errors: usize,
+}
+
+/// Scans every `SubscriptionAccount` each `LIFECYCLE_INTERVAL_SECONDS`. Runs on the
+/// actix system arbiter since anchor-client's request builder is not `Send`.
+pub fn spawn(
+    config: Config,
+    db: web::Data<Database>,
+    reader: web::Data<SubscriptionReader>,
+    solana: web::Data<Option<SolanaClient>>,
+) {
+    let interval = Duration::from_secs(config.lifecycle_interval_seconds);
+
+    actix_rt::spawn(async move {
+        loop {
+            match scan(&config, &db, &reader, solana.as_ref().as_ref()).await {
+                Ok(summary) => println!(
+                    "🔁 Lifecycle scan: {} subscriptions, {} reminders, {} lapsed, {} renewals, {} errors",
+                    summary.scanned, summary.reminders, summary.lapsed, summary.renewals, summary.errors
+                ),
+                Err(e) => println!("❌ Lifecycle scan failed: {}", e),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+pub async fn scan(
+    config: &Config,
+    db: &Database,
+    reader: &SubscriptionReader,
+    solana: Option<&SolanaClient>,
+) -> Result<ScanSummary, String> {
+    let now = Utc::now().timestamp();
+    let mut summary = ScanSummary::default();
+
+    for account in reader.all_subscriptions().await? {
+        summary.scanned += 1;
+        if let Err(e) = process(&account, now, config, db, reader, solana, &mut summary).await {
+            println!("⚠️ Lifecycle of {} failed: {}", account.address, e);
+            summary.errors += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+async fn process(
+    account: &SubscriptionAccount,
+    now: i64,
+    config: &Config,
+    db: &Database,
+    reader: &SubscriptionReader,
+    solana: Option<&SolanaClient>,
+    summary: &mut ScanSummary,
+) -> Result<(), String> {
+    // A new end_time (renewed or resubscribed) starts the lifecycle over
+    let mut record = match db.lifecycle_record(&account.address)? {
+        Some(record) if record.end_time == account.end_time => record,
+        _ => LifecycleRecord {
+            address: account.address.clone(),
+            user: account.user.clone(),
+            plan_tier: account.plan_tier,
+            end_time: account.end_time,
+            status: LifecycleStatus::Active,
+            reminder_sent_at: None,
+            lapsed_at: None,
+            renewal_attempted_at: None,
+            renewal_signature: None,
+            last_error: None,
+            updated_at: now,
+        },
+    };
+    record.plan_tier = account.plan_tier;
+
+    if account.end_time > now {
+        if !account.is_active {
+            record.status = LifecycleStatus::Cancelled;
+        } else if record.reminder_sent_at.is_none() && account.end_time - now <= config.expiry_reminder_days * 86400 {
+            println!("⏰ Subscription {} of {} expires at {}", account.address, account.user, account.end_time);
+            events::publish("subscriptions", &account.user, "expiry_reminder", json!({
+                "subscription": account.address,
+                "user": account.user,
+                "plan_tier": account.plan_tier,
+                "end_time": account.end_time,
+                "days_left": (account.end_time - now) / 86400,
+            }));
+            record.reminder_sent_at = Some(now);
+            summary.reminders += 1;
+        }
+    } else {
+        if record.lapsed_at.is_none() {
+            println!("⌛ Subscription {} of {} lapsed", account.address, account.user);
+            record.lapsed_at = Some(now);
+            record.status = LifecycleStatus::Lapsed;
+            if let Ok(user) = Pubkey::from_str(&account.user) {
+                accounts::invalidate(&user);
+            }
+            events::publish("subscriptions", &account.user, "lapsed", json!({
+                "subscription": account.address,
+                "user": account.user,
+                "plan_tier": account.plan_tier,
+                "end_time": account.end_time,
+            }));
+            summary.lapsed += 1;
+        }
+
+        // Cancelled subscriptions are left to lapse
+        let retry_due = record.renewal_attempted_at.is_none_or(|at| now - at >= RENEWAL_RETRY_SECONDS);
+        if account.is_active && record.status != LifecycleStatus::RenewalFailed && retry_due {
+            if let Some(auto_renew) = db.auto_renew(&account.user)? {
+                renew(account, &auto_renew, now, &mut record, reader, solana).await;
+                summary.renewals += 1;
+            }
+        }
+    }
+
+    record.updated_at = now;
+    db.save_lifecycle_record(&record)
+}
+
+/// Sends `retry_payment` when the backend wallet owns the subscription; otherwise
+/// publishes it unsigned on `subscriptions:<wallet>` for the user to sign.
+async fn renew(
+    account: &SubscriptionAccount,
+    auto_renew: &AutoRenew,
+    now: i64,
+    record: &mut LifecycleRecord,
+    reader: &SubscriptionReader,
+    solana: Option<&SolanaClient>,
+) {
+    let instruction = SubscriptionInstruction::RetryPayment {
+        amount_paid: auto_renew.amount_paid,
+        period_days: auto_renew.period_days,
+    };
+    let (Ok(user), Ok(address)) = (Pubkey::from_str(&account.user), Pubkey::from_str(&account.address)) else {
+        record.last_error = Some("Invalid account address".to_string());
+        return;
+    };
+    record.renewal_attempted_at = Some(now);
+
+    if let Some(client) = solana.filter(|client| client.wallet() == user) {
+        match client.update_subscription(address, instruction).await {
+            Ok(tx) => {
+                println!("🔁 Renewed {}: {}", account.address, tx.signature);
+                record.status = LifecycleStatus::RenewalPending;
+                record.renewal_signature = Some(tx.signature.clone());
+                record.last_error = None;
+                events::publish("subscriptions", &account.user, "renewal_submitted", json!(tx));
+            },
+            Err(SendError::Program(SubscriptionError::AlreadyActive)) => {
+                record.status = LifecycleStatus::Active;
+                record.last_error = Some(SubscriptionError::AlreadyActive.to_string());
+            },
+            Err(SendError::Program(e)) => {
+                println!("❌ Renewal of {} rejected: {}", account.address, e);
+                record.status = LifecycleStatus::RenewalFailed;
+                record.last_error = Some(e.to_string());
+                events::publish("subscriptions", &account.user, "renewal_failed", json!({
+                    "subscription": account.address,
+                    "error": e.name(),
+                    "code": e.code(),
+                    "message": e.message(),
+                }));
+            },
+            Err(SendError::Client(e)) => {
+                println!("⚠️ Renewal of {} not sent, will retry: {}", account.address, e);
+                record.last_error = Some(e);
+            },
+        }
+        return;
+    }
+
+    let transaction = match reader.latest_blockhash().await {
+        Ok(blockhash) => subscription::unsigned_transaction(reader.program_id(), user, address, &instruction, blockhash),
+        Err(e) => Err(e),
+    };
+    match transaction {
+        Ok(transaction) => {
+            record.status = LifecycleStatus::RenewalPending;
+            record.last_error = None;
+            events::publish("subscriptions", &account.user, "renewal_ready", json!({
+                "subscription": account.address,
+                "instruction": instruction.name(),
+                "amount_paid": auto_renew.amount_paid,
+                "period_days": auto_renew.period_days,
+                "transaction": transaction,
+            }));
+        },
+        Err(e) => {
+            println!("⚠️ Could not build renewal for {}: {}", account.address, e);
+            record.last_error = Some(e);
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, HttpServer};
+    use serde_json::Value;
+
+    /// Answers `getLatestBlockhash` like a validator would.
+    async fn rpc(body: web::Json<Value>) -> HttpResponse {
+        let result = match body["method"].as_str() {
+            Some("getLatestBlockhash") => json!({
+                "context": { "slot": 1 },
+                "value": { "blockhash": solana_sdk::hash::Hash::new_unique().to_string(), "lastValidBlockHeight": 100 },
+            }),
+            Some("getVersion") => json!({ "solana-core": "1.18.26", "feature-set": 0 }),
+            _ => Value::Null,
+        };
+        HttpResponse::Ok().json(json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }))
+    }
+
+    fn reader() -> SubscriptionReader {
+        let server = HttpServer::new(|| App::new().route("/", web::post().to(rpc)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let url = format!("http://{}", server.addrs()[0]);
+        actix_rt::spawn(server.run());
+        SubscriptionReader::new(url, Pubkey::new_unique(), Duration::from_secs(0))
+    }
+
+    fn account(end_time: i64, is_active: bool) -> SubscriptionAccount {
+        SubscriptionAccount {
+            address: Pubkey::new_unique().to_string(),
+            user: Pubkey::new_unique().to_string(),
+            plan_tier: 1,
+            start_time: end_time - 30 * 86400,
+            end_time,
+            is_active,
+        }
+    }
+
+    fn config() -> Config {
+        let mut config = Config::from_env().unwrap();
+        config.expiry_reminder_days = 3;
+        config
+    }
+
+    async fn run(account: &SubscriptionAccount, now: i64, db: &Database, reader: &SubscriptionReader) -> (ScanSummary, LifecycleRecord) {
+        let mut summary = ScanSummary::default();
+        process(account, now, &config(), db, reader, None, &mut summary).await.unwrap();
+        (summary, db.lifecycle_record(&account.address).unwrap().unwrap())
+    }
+
+    #[actix_rt::test]
+    async fn reminds_once_before_expiry() {
+        let (db, reader) = (Database::in_memory().unwrap(), reader());
+        let now = 1_700_000_000;
+        let account = account(now + 5 * 86400, true);
+
+        let (summary, record) = run(&account, now, &db, &reader).await;
+        assert_eq!((summary.reminders, record.reminder_sent_at), (0, None));
+
+        let (summary, record) = run(&account, now + 3 * 86400, &db, &reader).await;
+        assert_eq!((summary.reminders, record.reminder_sent_at), (1, Some(now + 3 * 86400)));
+        assert_eq!(record.status, LifecycleStatus::Active);
+
+        let (summary, _) = run(&account, now + 4 * 86400, &db, &reader).await;
+        assert_eq!(summary.reminders, 0);
+
+        let cancelled = SubscriptionAccount { is_active: false, ..account };
+        let (summary, record) = run(&cancelled, now + 4 * 86400, &db, &reader).await;
+        assert_eq!((summary.reminders, record.status), (0, LifecycleStatus::Cancelled));
+    }
+
+    #[actix_rt::test]
+    async fn lapses_once_without_auto_renew() {
+        let (db, reader) = (Database::in_memory().unwrap(), reader());
+        let now = 1_700_000_000;
+        let account = account(now - 1, true);
+
+        let (summary, record) = run(&account, now, &db, &reader).await;
+        assert_eq!((summary.lapsed, summary.renewals), (1, 0));
+        assert_eq!((record.status, record.lapsed_at), (LifecycleStatus::Lapsed, Some(now)));
+
+        let (summary, record) = run(&account, now + 60, &db, &reader).await;
+        assert_eq!((summary.lapsed, record.lapsed_at), (0, Some(now)));
+    }
+
+    #[actix_rt::test]
+    async fn hands_out_renewals_and_starts_over_once_renewed() {
+        let (db, reader) = (Database::in_memory().unwrap(), reader());
+        let now = 1_700_000_000;
+        let account = account(now - 1, true);
+        db.set_auto_renew(&AutoRenew { wallet: account.user.clone(), amount_paid: 29_000_000, period_days: 30, updated_at: now }).unwrap();
+
+        // Without the backend wallet the renewal is published for the user to sign
+        let (summary, record) = run(&account, now, &db, &reader).await;
+        assert_eq!((summary.lapsed, summary.renewals), (1, 1));
+        assert_eq!(record.status, LifecycleStatus::RenewalPending);
+        assert_eq!((record.renewal_attempted_at, record.last_error), (Some(now), None));
+
+        // Not retried until RENEWAL_RETRY_SECONDS have passed
+        let (summary, _) = run(&account, now + 60, &db, &reader).await;
+        assert_eq!(summary.renewals, 0);
+        let (summary, record) = run(&account, now + RENEWAL_RETRY_SECONDS, &db, &reader).await;
+        assert_eq!((summary.renewals, record.renewal_attempted_at), (1, Some(now + RENEWAL_RETRY_SECONDS)));
+
+        // The renewal landed: a new end_time resets the record
+        let renewed = SubscriptionAccount { end_time: now + 30 * 86400, ..account };
+        let (summary, record) = run(&renewed, now + RENEWAL_RETRY_SECONDS + 60, &db, &reader).await;
+        assert_eq!((summary.lapsed, summary.renewals), (0, 0));
+        assert_eq!(record.status, LifecycleStatus::Active);
+        assert_eq!((record.lapsed_at, record.renewal_attempted_at), (None, None));
+    }
+
+    #[actix_rt::test]
+    async fn keeps_the_error_when_a_renewal_cannot_be_built() {
+        let db = Database::in_memory().unwrap();
+        // Nothing listens on port 9
+        let reader = SubscriptionReader::new("http://127.0.0.1:9".to_string(), Pubkey::new_unique(), Duration::from_secs(0));
+        let now = 1_700_000_000;
+        let account = account(now - 1, true);
+        db.set_auto_renew(&AutoRenew { wallet: account.user.clone(), amount_paid: 1, period_days: 30, updated_at: now }).unwrap();
+
+        let (summary, record) = run(&account, now, &db, &reader).await;
+        assert_eq!(summary.renewals, 1);
+        assert_eq!(record.status, LifecycleStatus::Lapsed);
+        assert!(record.last_error.is_some());
+        assert_eq!(record.renewal_attempted_at, Some(now));
+    }
+}
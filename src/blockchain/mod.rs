@@ -0,0 +1,86 @@
+pub mod accounts;
+pub mod actions;
+pub mod errors;
+pub mod health;
+pub mod idl;
+pub mod indexer;
+pub mod lifecycle;
+pub mod payments;
+pub mod signer;
+pub mod subscription;
+
+use anchor_client::{Client, Cluster, Program};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use self::signer::WalletSigner;
+use crate::config::Config;
+
+pub const DEFAULT_RPC_URL: &str = "https://api.devnet.solana.com";
+
+/// Signs and submits `subscription_program` transactions with the backend wallet.
+pub struct SolanaClient {
+    client: Client<Arc<Keypair>>,
+    signer: WalletSigner,
+    pub program_id: Pubkey,
+    /// Named after the RPC URL, which is never logged since it may carry an API key.
+    pub cluster: &'static str,
+}
+
+impl SolanaClient {
+    /// Builds the client from the configured wallet key, `SOLANA_RPC_URL` and
+    /// `SOLANA_PROGRAM_ID`. Returns `None` when no wallet is configured.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, String> {
+        let Some(wallet_key) = config.solana_wallet.as_ref() else {
+            return Ok(None);
+        };
+        let signer = WalletSigner::load(wallet_key)?;
+
+        let program_id = match config.solana_program_id.as_deref() {
+            Some(id) => Pubkey::from_str(id).map_err(|e| format!("Invalid SOLANA_PROGRAM_ID: {}", e))?,
+            None => idl::subscription_program().program_id(),
+        };
+
+        let rpc_url = config.solana_rpc_url.clone().unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
+        let ws_url = rpc_url.replacen("http", "ws", 1);
+        let cluster = cluster_name(&rpc_url);
+        let client = Client::new_with_options(
+            Cluster::Custom(rpc_url, ws_url),
+            signer.keypair(),
+            CommitmentConfig::confirmed(),
+        );
+
+        Ok(Some(Self { client, signer, program_id, cluster }))
+    }
+
+    pub fn wallet(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    pub fn signer(&self) -> &WalletSigner {
+        &self.signer
+    }
+
+    fn program(&self) -> Result<Program<Arc<Keypair>>, String> {
+        self.client.program(self.program_id).map_err(|e| e.to_string())
+    }
+}
+
+/// The Solana cluster an RPC URL points at, as far as the URL tells.
+pub fn cluster_name(rpc_url: &str) -> &'static str {
+    let url = rpc_url.to_lowercase();
+    if url.contains("devnet") {
+        "devnet"
+    } else if url.contains("testnet") {
+        "testnet"
+    } else if url.contains("mainnet") {
+        "mainnet-beta"
+    } else if url.contains("localhost") || url.contains("127.0.0.1") {
+        "localnet"
+    } else {
+        "custom"
+    }
+}
@@ -0,0 +1,665 @@
+use actix_web::web;
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token::spl_token;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding, UiTransactionTokenBalance};
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::accounts::{self, SubscriptionReader};
+use super::{idl, indexer, subscription};
+use crate::config::Config;
+use crate::events;
+use crate::plans::PlanTier;
+use crate::storage::payments::{PaymentStatus, SubscriptionPayment};
+use crate::storage::Database;
+
+/// USDC on devnet, the default payment mint off mainnet.
+pub const DEVNET_USDC: &str = "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU";
+/// Longest period a single payment may cover.
+pub const MAX_PERIOD_DAYS: i64 = 366;
+/// Signatures of a subscription account searched for its `subscribe`.
+const SIGNATURE_LIMIT: usize = 20;
+/// Checkouts not seen on-chain this long after their transaction was last built are expired.
+/// Its blockhash is long gone by then, so it can no longer land.
+pub const CHECKOUT_TTL_SECONDS: i64 = 600;
+
+/// An SPL token (owned by the classic token program) accepted for plan payments.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaymentMint {
+    /// Name used in quotes and requests, e.g. `USDC`.
+    pub symbol: String,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    /// Registry symbol the token is priced by. Pegged to $1 when unset.
+    pub price_symbol: Option<String>,
+}
+
+impl PaymentMint {
+    /// Parses a `PAYMENT_MINTS` entry: `SYMBOL:mint:decimals[:price_symbol]`.
+    pub fn parse(entry: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = entry.split(':').map(str::trim).collect();
+        let (symbol, mint, decimals, price_symbol) = match parts[..] {
+            [symbol, mint, decimals] => (symbol, mint, decimals, None),
+            [symbol, mint, decimals, price_symbol] => (symbol, mint, decimals, Some(price_symbol.to_uppercase())),
+            _ => return Err(format!("Invalid PAYMENT_MINTS entry {}: expected SYMBOL:mint:decimals[:price_symbol]", entry)),
+        };
+
+        Ok(Self {
+            symbol: symbol.to_uppercase(),
+            mint: Pubkey::from_str(mint).map_err(|e| format!("Invalid mint for {}: {}", symbol, e))?,
+            decimals: decimals.parse().map_err(|e| format!("Invalid decimals for {}: {}", symbol, e))?,
+            price_symbol,
+        })
+    }
+
+    /// USDC on `cluster`, as named by `cluster_name`.
+    pub fn default_usdc(cluster: &str) -> Self {
+        let mint = if cluster == "mainnet-beta" {
+            anchor_spl::mint::USDC
+        } else {
+            Pubkey::from_str(DEVNET_USDC).expect("DEVNET_USDC is a valid pubkey")
+        };
+        Self { symbol: "USDC".to_string(), mint, decimals: 6, price_symbol: None }
+    }
+}
+
+/// A configured mint by symbol or mint address.
+pub fn find_mint<'a>(mints: &'a [PaymentMint], currency: &str) -> Option<&'a PaymentMint> {
+    mints.iter().find(|m| m.symbol.eq_ignore_ascii_case(currency) || m.mint.to_string() == currency)
+}
+
+/// What a plan costs for a period, in one payment mint.
+#[derive(Debug, Clone, Serialize)]
+pub struct PaymentQuote {
+    pub plan_tier: u8,
+    pub plan: String,
+    pub period_days: i64,
+    pub price_usd: f64,
+    pub currency: String,
+    pub mint: String,
+    pub decimals: u8,
+    /// Base units of `mint`, rounded up.
+    pub amount: u64,
+    pub ui_amount: f64,
+    pub token_price_usd: f64,
+    /// Wallet whose associated token account receives the payment.
+    pub treasury: String,
+    pub quoted_at: i64,
+}
+
+/// Prices `period_days` of `plan` pro rata from its 30-day price.
+pub fn quote(plan: &PlanTier, period_days: i64, mint: &PaymentMint, token_price_usd: f64, treasury: Pubkey) -> Result<PaymentQuote, String> {
+    if plan.monthly_price_usd <= 0.0 {
+        return Err(format!("The {} plan is not for sale", plan.name));
+    }
+    if !(1..=MAX_PERIOD_DAYS).contains(&period_days) {
+        return Err(format!("period_days must be between 1 and {}", MAX_PERIOD_DAYS));
+    }
+    if !token_price_usd.is_finite() || token_price_usd <= 0.0 {
+        return Err(format!("No usable price for {}", mint.symbol));
+    }
+
+    let price_usd = plan.monthly_price_usd * period_days as f64 / 30.0;
+    let scale = 10f64.powi(mint.decimals as i32);
+    let amount = (price_usd / token_price_usd * scale).ceil() as u64;
+
+    Ok(PaymentQuote {
+        plan_tier: plan.tier,
+        plan: plan.name.clone(),
+        period_days,
+        price_usd,
+        currency: mint.symbol.clone(),
+        mint: mint.mint.to_string(),
+        decimals: mint.decimals,
+        amount,
+        ui_amount: amount as f64 / scale,
+        token_price_usd,
+        treasury: treasury.to_string(),
+        quoted_at: Utc::now().timestamp(),
+    })
+}
+
+/// Creates `owner`'s associated token account for `mint` unless it exists.
+/// `spl_associated_token_account`'s instruction module is not re-exported by anchor-spl.
+fn create_associated_token_account_idempotent(payer: &Pubkey, owner: &Pubkey, mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: associated_token::ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(get_associated_token_address(owner, mint), false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        // AssociatedTokenAccountInstruction::CreateIdempotent
+        data: vec![1],
+    }
+}
+
+/// The treasury's token account, the transfer from `user` and `subscribe`, in that order.
+/// `amount_paid` is the transferred amount in base units of the mint. A Solana Pay
+/// `reference` is attached to the transfer as a read-only account.
+pub fn payment_instructions(
+    program_id: Pubkey,
+    user: Pubkey,
+    subscription: Pubkey,
+    mint: &PaymentMint,
+    quote: &PaymentQuote,
+    treasury: Pubkey,
+    reference: Option<Pubkey>,
+) -> Result<Vec<Instruction>, String> {
+    let mut transfer = spl_token::instruction::transfer_checked(
+        &spl_token::id(),
+        &get_associated_token_address(&user, &mint.mint),
+        &mint.mint,
+        &get_associated_token_address(&treasury, &mint.mint),
+        &user,
+        &[],
+        quote.amount,
+        mint.decimals,
+    )
+    .map_err(|e| format!("transfer_checked: {}", e))?;
+    transfer.accounts.extend(reference.map(|r| AccountMeta::new_readonly(r, false)));
+
+    let subscribe = idl::subscription_program().build_instruction(
+        program_id,
+        "subscribe",
+        &[("user", user), ("subscription", subscription)],
+        &json!({"plan_tier": quote.plan_tier, "amount_paid": quote.amount, "period_days": quote.period_days}),
+    )?;
+
+    Ok(vec![
+        create_associated_token_account_idempotent(&user, &treasury, &mint.mint),
+        transfer,
+        subscribe,
+    ])
+}
+
+/// A payment transaction partially signed by its new subscription account.
+#[derive(Debug, Clone, Serialize)]
+pub struct PaymentTransaction {
+    /// Base64 wire format, for the user's wallet to sign and send.
+    pub transaction: String,
+    pub subscription: String,
+    pub reference: Option<String>,
+}
+
+/// Builds the payment transaction with `user` as fee payer. The program takes
+/// `subscription` as a signer, so a fresh keypair signs here and is then dropped.
+pub fn payment_transaction(
+    program_id: Pubkey,
+    user: Pubkey,
+    mint: &PaymentMint,
+    quote: &PaymentQuote,
+    treasury: Pubkey,
+    reference: Option<Pubkey>,
+    recent_blockhash: Hash,
+) -> Result<PaymentTransaction, String> {
+    let subscription = Keypair::new();
+    let instructions = payment_instructions(program_id, user, subscription.pubkey(), mint, quote, treasury, reference)?;
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&user));
+    transaction.try_partial_sign(&[&subscription], recent_blockhash).map_err(|e| e.to_string())?;
+
+    Ok(PaymentTransaction {
+        transaction: subscription::encode_transaction(&transaction)?,
+        subscription: subscription.pubkey().to_string(),
+        reference: reference.map(|r| r.to_string()),
+    })
+}
+
+/// The pending payment record for a transaction built from `quote`.
+pub fn pending_payment(quote: &PaymentQuote, user: Pubkey, built: &PaymentTransaction, now: i64) -> SubscriptionPayment {
+    SubscriptionPayment {
+        subscription: built.subscription.clone(),
+        user: user.to_string(),
+        plan_tier: quote.plan_tier,
+        period_days: quote.period_days,
+        currency: quote.currency.clone(),
+        mint: quote.mint.clone(),
+        treasury: quote.treasury.clone(),
+        amount: quote.amount,
+        price_usd: quote.price_usd,
+        status: PaymentStatus::Pending,
+        signature: None,
+        error: None,
+        created_at: now,
+        updated_at: now,
+        reference: built.reference.clone(),
+    }
+}
+
+/// What the chain says about a recorded payment. `subscription` is the account the
+/// landed `subscribe` created.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaymentCheck {
+    /// No successful `subscribe` of the subscription by its user yet.
+    NotFound,
+    Paid { signature: String, subscription: String, received: u64 },
+    Underpaid { signature: String, subscription: String, received: u64 },
+}
+
+/// Increase of `owner`'s balances of `mint` over the transaction, in base units.
+fn received(transaction: &EncodedConfirmedTransactionWithStatusMeta, owner: &str, mint: &str) -> u64 {
+    let Some(meta) = transaction.transaction.meta.as_ref() else {
+        return 0;
+    };
+    let balances = |balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>| -> Vec<(u8, u64)> {
+        match balances {
+            OptionSerializer::Some(balances) => balances.iter()
+                .filter(|b| b.mint == mint && b.owner == OptionSerializer::Some(owner.to_string()))
+                .map(|b| (b.account_index, b.ui_token_amount.amount.parse().unwrap_or(0)))
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+
+    // Accounts created by the transaction have no pre balance
+    let pre = balances(&meta.pre_token_balances);
+    balances(&meta.post_token_balances).into_iter()
+        .map(|(index, post)| {
+            let before = pre.iter().find(|(i, _)| *i == index).map_or(0, |(_, amount)| *amount);
+            post.saturating_sub(before)
+        })
+        .sum()
+}
+
+impl SubscriptionReader {
+    /// Looks for the successful `subscribe` of `payment.subscription` by `payment.user`
+    /// and checks that the same transaction moved `payment.amount` of the mint to the treasury.
+    /// Checkouts are found through their reference key, other payments through the subscription.
+    /// A checkout may have been rebuilt after the wallet signed an earlier transaction, so
+    /// any `subscribe` by the user under the reference counts, whichever account it created.
+    pub async fn check_payment(&self, payment: &SubscriptionPayment) -> Result<PaymentCheck, String> {
+        let lookup = payment.reference.as_deref().unwrap_or(&payment.subscription);
+        let address = Pubkey::from_str(lookup)
+            .map_err(|e| format!("Invalid payment address {}: {}", lookup, e))?;
+        let config = GetConfirmedSignaturesForAddress2Config {
+            limit: Some(SIGNATURE_LIMIT),
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..GetConfirmedSignaturesForAddress2Config::default()
+        };
+        let signatures = self.rpc()
+            .get_signatures_for_address_with_config(&address, config)
+            .await
+            .map_err(|e| format!("getSignaturesForAddress failed: {}", e))?;
+
+        for status in signatures.iter().filter(|s| s.err.is_none()) {
+            let signature = Signature::from_str(&status.signature).map_err(|e| e.to_string())?;
+            let config = RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            };
+            let transaction = self.rpc().get_transaction_with_config(&signature, config)
+                .await
+                .map_err(|e| format!("getTransaction {} failed: {}", status.signature, e))?;
+
+            let subscribed = indexer::decode_transaction(&status.signature, &transaction, &self.program_id())
+                .into_iter()
+                .filter(|event| event.success
+                    && event.instruction == "subscribe"
+                    && event.user.as_deref() == Some(payment.user.as_str()))
+                .find_map(|event| event.subscription
+                    .filter(|s| payment.reference.is_some() || *s == payment.subscription));
+            let Some(subscription) = subscribed else {
+                continue;
+            };
+
+            let received = received(&transaction, &payment.treasury, &payment.mint);
+            let signature = status.signature.clone();
+            return Ok(if received >= payment.amount {
+                PaymentCheck::Paid { signature, subscription, received }
+            } else {
+                PaymentCheck::Underpaid { signature, subscription, received }
+            });
+        }
+
+        Ok(PaymentCheck::NotFound)
+    }
+}
+
+/// Checks `payment` on-chain and records the outcome. A payment that is not found
+/// stays pending. Verified payments activate the subscription straight away.
+pub async fn verify(db: &Database, reader: &SubscriptionReader, payment: &SubscriptionPayment) -> Result<SubscriptionPayment, String> {
+    if payment.status == PaymentStatus::Verified {
+        return Ok(payment.clone());
+    }
+
+    let now = Utc::now().timestamp();
+    let (signature, address, received, paid) = match reader.check_payment(payment).await? {
+        PaymentCheck::NotFound => return Ok(payment.clone()),
+        PaymentCheck::Paid { signature, subscription, received } => (signature, subscription, received, true),
+        PaymentCheck::Underpaid { signature, subscription, received } => (signature, subscription, received, false),
+    };
+    if let Some(reference) = payment.reference.as_deref().filter(|_| address != payment.subscription) {
+        db.set_checkout_subscription(reference, &address)?;
+    }
+
+    if paid {
+        println!("✅ Payment for {} verified: {} base units of {} in {}", address, received, payment.currency, signature);
+        db.set_payment_status(&address, PaymentStatus::Verified, Some(&signature), None, now)?;
+        if let Ok(user) = Pubkey::from_str(&payment.user) {
+            accounts::invalidate(&user);
+        }
+    } else {
+        let error = format!("Received {} of {} base units", received, payment.amount);
+        println!("❌ Payment for {} rejected: {}", address, error);
+        db.set_payment_status(&address, PaymentStatus::Failed, Some(&signature), Some(&error), now)?;
+    }
+
+    let updated = db.payment(&address)?.ok_or_else(|| format!("Payment {} disappeared", address))?;
+    let event = match updated.status {
+        PaymentStatus::Verified => "payment_verified",
+        _ => "payment_failed",
+    };
+    events::publish("subscriptions", &updated.user, event, json!(updated));
+    Ok(updated)
+}
+
+/// Polls the reference key of every pending checkout every `CHECKOUT_POLL_SECONDS`
+/// and marks the checkout paid once its transaction lands.
+pub fn spawn(config: &Config, db: web::Data<Database>, reader: web::Data<SubscriptionReader>) {
+    let interval = Duration::from_secs(config.checkout_poll_seconds);
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = poll_checkouts(&db, &reader).await {
+                println!("❌ Checkout poll failed: {}", e);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+async fn poll_checkouts(db: &Database, reader: &SubscriptionReader) -> Result<(), String> {
+    for checkout in db.pending_checkouts()? {
+        let checkout = match verify(db, reader, &checkout).await {
+            Ok(checkout) => checkout,
+            Err(e) => {
+                println!("⚠️ Cannot check checkout {}: {}", checkout.subscription, e);
+                continue;
+            },
+        };
+
+        expire_unpaid(db, &checkout, Utc::now().timestamp())?;
+    }
+    Ok(())
+}
+
+/// Whether `checkout` is still pending past `CHECKOUT_TTL_SECONDS`.
+pub fn checkout_expired(checkout: &SubscriptionPayment, now: i64) -> bool {
+    checkout.status == PaymentStatus::Pending && now - checkout.created_at > CHECKOUT_TTL_SECONDS
+}
+
+/// Marks `checkout` expired once it can no longer land. Returns whether it was.
+fn expire_unpaid(db: &Database, checkout: &SubscriptionPayment, now: i64) -> Result<bool, String> {
+    if !checkout_expired(checkout, now) {
+        return Ok(false);
+    }
+    println!("⌛ Checkout {} expired unpaid", checkout.subscription);
+    db.set_payment_status(&checkout.subscription, PaymentStatus::Expired, None, None, now)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plans::default_catalogue;
+
+    fn checkout(db: &Database, user: Pubkey, reference: Pubkey, now: i64) -> SubscriptionPayment {
+        let quote = quote(&default_catalogue()[1], 30, &PaymentMint::default_usdc("devnet"), 1.0, Pubkey::new_unique()).unwrap();
+        let built = PaymentTransaction {
+            transaction: String::new(),
+            subscription: Pubkey::new_unique().to_string(),
+            reference: Some(reference.to_string()),
+        };
+        let payment = pending_payment(&quote, user, &built, now);
+        assert!(db.save_checkout(&payment, Some("203.0.113.7")).unwrap());
+        payment
+    }
+
+    #[test]
+    fn payment_instructions_attach_the_reference_to_the_transfer() {
+        let usdc = PaymentMint::default_usdc("devnet");
+        let quote = quote(&default_catalogue()[1], 30, &usdc, 1.0, Pubkey::new_unique()).unwrap();
+        let (user, reference) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let build = |reference| payment_instructions(
+            idl::subscription_program().program_id(), user, Pubkey::new_unique(), &usdc, &quote, Pubkey::new_unique(), reference,
+        ).unwrap();
+
+        let plain = build(None);
+        let with_reference = build(Some(reference));
+        let transfer = &with_reference[1];
+        assert_eq!(transfer.accounts.len(), plain[1].accounts.len() + 1);
+        assert_eq!(transfer.accounts.last(), Some(&AccountMeta::new_readonly(reference, false)));
+    }
+
+    #[test]
+    fn pending_payment_records_the_quote_and_reference() {
+        let quote = quote(&default_catalogue()[2], 60, &PaymentMint::default_usdc("devnet"), 1.0, Pubkey::new_unique()).unwrap();
+        let user = Pubkey::new_unique();
+        let built = PaymentTransaction {
+            transaction: String::new(),
+            subscription: Pubkey::new_unique().to_string(),
+            reference: Some(Pubkey::new_unique().to_string()),
+        };
+
+        let payment = pending_payment(&quote, user, &built, 1_700_000_000);
+        assert_eq!(payment.subscription, built.subscription);
+        assert_eq!(payment.user, user.to_string());
+        assert_eq!((payment.plan_tier, payment.period_days, payment.amount), (2, 60, 198_000_000));
+        assert_eq!((payment.mint.as_str(), payment.treasury.as_str()), (DEVNET_USDC, quote.treasury.as_str()));
+        assert_eq!(payment.status, PaymentStatus::Pending);
+        assert_eq!((payment.created_at, payment.updated_at), (1_700_000_000, 1_700_000_000));
+        assert_eq!(payment.reference, built.reference);
+    }
+
+    #[test]
+    fn unpaid_checkouts_expire_after_the_ttl() {
+        let db = Database::in_memory().unwrap();
+        let built_at = 1_700_000_000;
+        let payment = checkout(&db, Pubkey::new_unique(), Pubkey::new_unique(), built_at);
+
+        assert!(!expire_unpaid(&db, &payment, built_at + CHECKOUT_TTL_SECONDS).unwrap());
+        assert_eq!(db.pending_checkouts().unwrap().len(), 1);
+
+        assert!(expire_unpaid(&db, &payment, built_at + CHECKOUT_TTL_SECONDS + 1).unwrap());
+        assert_eq!(db.payment(&payment.subscription).unwrap().unwrap().status, PaymentStatus::Expired);
+        assert!(db.pending_checkouts().unwrap().is_empty());
+    }
+
+    #[test]
+    fn checkouts_are_rebuilt_only_while_pending_for_the_same_wallet() {
+        let db = Database::in_memory().unwrap();
+        let (user, reference) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let first = checkout(&db, user, reference, 1_700_000_000);
+
+        // A second post replaces the pending record
+        let rebuilt = checkout(&db, user, reference, 1_700_000_030);
+        let saved = db.payment_by_reference(&reference.to_string()).unwrap().unwrap();
+        assert_eq!((saved.subscription.as_str(), saved.created_at), (rebuilt.subscription.as_str(), 1_700_000_030));
+        assert!(db.payment(&first.subscription).unwrap().is_none());
+        assert_eq!(db.pending_checkout_counts(&user.to_string(), Some("203.0.113.7"), 0).unwrap(), (1, 1, 1));
+        assert_eq!(db.pending_checkout_counts(&user.to_string(), None, 0).unwrap(), (1, 0, 1));
+
+        let mut other = rebuilt.clone();
+        other.subscription = Pubkey::new_unique().to_string();
+        other.user = Pubkey::new_unique().to_string();
+        assert!(!db.save_checkout(&other, None).unwrap());
+
+        db.set_payment_status(&rebuilt.subscription, PaymentStatus::Verified, Some("signature"), None, 1_700_000_060).unwrap();
+        let mut again = rebuilt.clone();
+        again.subscription = Pubkey::new_unique().to_string();
+        assert!(!db.save_checkout(&again, None).unwrap());
+        assert_eq!(db.pending_checkout_counts(&user.to_string(), Some("203.0.113.7"), 0).unwrap(), (0, 0, 0));
+    }
+
+    #[test]
+    fn parses_payment_mints() {
+        let mint = PaymentMint::parse(&format!("sol:{}:9:sol", spl_token::native_mint::id())).unwrap();
+        assert_eq!((mint.symbol.as_str(), mint.decimals, mint.price_symbol.as_deref()), ("SOL", 9, Some("SOL")));
+        assert_eq!(PaymentMint::parse(&format!("USDC:{}:6", DEVNET_USDC)).unwrap().price_symbol, None);
+
+        for entry in ["USDC", "USDC:not-a-mint:6", &format!("USDC:{}:six", DEVNET_USDC), &format!("A:{}:6:B:C", DEVNET_USDC)] {
+            assert!(PaymentMint::parse(entry).is_err(), "{}", entry);
+        }
+    }
+
+    #[test]
+    fn quotes_pro_rata_and_rounds_up() {
+        let plans = default_catalogue();
+        let usdc = PaymentMint::default_usdc("devnet");
+        let treasury = Pubkey::new_unique();
+
+        let month = quote(&plans[1], 30, &usdc, 1.0, treasury).unwrap();
+        assert_eq!((month.amount, month.ui_amount), (29_000_000, 29.0));
+        assert_eq!(month.treasury, treasury.to_string());
+        // $29 / 30 * 7 = $6.7666..., charged up to the next base unit
+        assert_eq!(quote(&plans[1], 7, &usdc, 1.0, treasury).unwrap().amount, 6_766_667);
+        assert_eq!(quote(&plans[1], 30, &usdc, 0.5, treasury).unwrap().amount, 58_000_000);
+
+        assert!(quote(&plans[0], 30, &usdc, 1.0, treasury).is_err());
+        assert!(quote(&plans[1], 0, &usdc, 1.0, treasury).is_err());
+        assert!(quote(&plans[1], MAX_PERIOD_DAYS + 1, &usdc, 1.0, treasury).is_err());
+        assert!(quote(&plans[1], 30, &usdc, 0.0, treasury).is_err());
+        assert!(quote(&plans[1], 30, &usdc, f64::NAN, treasury).is_err());
+    }
+
+    fn balance(index: u8, mint: &str, owner: &str, amount: u64) -> serde_json::Value {
+        json!({
+            "accountIndex": index,
+            "mint": mint,
+            "owner": owner,
+            "uiTokenAmount": {"amount": amount.to_string(), "decimals": 6, "uiAmount": null, "uiAmountString": ""},
+        })
+    }
+
+    #[test]
+    fn counts_only_the_treasury_balance_increase_of_the_mint() {
+        let (treasury, payer) = ("Treasury", "Payer");
+        let transaction: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(json!({
+            "slot": 1,
+            "blockTime": null,
+            "transaction": ["", "base64"],
+            "meta": {
+                "err": null,
+                "status": {"Ok": null},
+                "fee": 5000,
+                "preBalances": [],
+                "postBalances": [],
+                "preTokenBalances": [
+                    balance(1, DEVNET_USDC, treasury, 10_000_000),
+                    balance(2, DEVNET_USDC, payer, 50_000_000),
+                ],
+                "postTokenBalances": [
+                    balance(1, DEVNET_USDC, treasury, 39_000_000),
+                    balance(2, DEVNET_USDC, payer, 21_000_000),
+                    // Created by the transaction, so without a pre balance
+                    balance(3, DEVNET_USDC, treasury, 1_000_000),
+                    balance(4, "OtherMint", treasury, 7_000_000),
+                ],
+            },
+        })).unwrap();
+
+        assert_eq!(received(&transaction, treasury, DEVNET_USDC), 30_000_000);
+        assert_eq!(received(&transaction, payer, DEVNET_USDC), 0);
+        assert_eq!(received(&transaction, treasury, "OtherMint"), 7_000_000);
+    }
+
+    /// Answers `getSignaturesForAddress` with one signature whose transaction is `transaction`.
+    async fn rpc(body: web::Json<serde_json::Value>, transaction: web::Data<std::sync::Mutex<serde_json::Value>>) -> actix_web::HttpResponse {
+        let result = match body["method"].as_str() {
+            Some("getSignaturesForAddress") => json!([{
+                "signature": Signature::new_unique().to_string(), "slot": 1, "err": null, "memo": null, "blockTime": null,
+            }]),
+            Some("getTransaction") => transaction.lock().unwrap().clone(),
+            Some("getVersion") => json!({ "solana-core": "1.18.26", "feature-set": 0 }),
+            _ => serde_json::Value::Null,
+        };
+        actix_web::HttpResponse::Ok().json(json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }))
+    }
+
+    fn reader(transaction: web::Data<std::sync::Mutex<serde_json::Value>>) -> SubscriptionReader {
+        let server = actix_web::HttpServer::new(move || actix_web::App::new()
+                .app_data(transaction.clone())
+                .route("/", web::post().to(rpc)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let url = format!("http://{}", server.addrs()[0]);
+        actix_rt::spawn(server.run());
+        SubscriptionReader::new(url, Pubkey::new_unique(), Duration::from_secs(0))
+    }
+
+    /// `user` subscribing `subscription` while `amount` of `mint` reaches `owner`.
+    fn subscribe_transaction(program_id: Pubkey, user: Pubkey, subscription: Pubkey, mint: &str, owner: &str, amount: u64) -> serde_json::Value {
+        let ix = idl::subscription_program().build_instruction(
+            program_id,
+            "subscribe",
+            &[("user", user), ("subscription", subscription)],
+            &json!({"plan_tier": 1, "amount_paid": amount, "period_days": 30}),
+        ).unwrap();
+        let transaction = Transaction::new_with_payer(&[ix], Some(&user));
+
+        json!({
+            "slot": 1,
+            "blockTime": null,
+            "transaction": [subscription::encode_transaction(&transaction).unwrap(), "base64"],
+            "meta": {
+                "err": null,
+                "status": {"Ok": null},
+                "fee": 5000,
+                "preBalances": [],
+                "postBalances": [],
+                "preTokenBalances": [],
+                "postTokenBalances": [balance(3, mint, owner, amount)],
+            },
+        })
+    }
+
+    #[actix_rt::test]
+    async fn checks_the_subscribe_and_transfer_of_a_payment() {
+        let transaction = web::Data::new(std::sync::Mutex::new(serde_json::Value::Null));
+        let reader = reader(transaction.clone());
+        let db = Database::in_memory().unwrap();
+        let (user, treasury) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut payment = checkout(&db, user, Pubkey::new_unique(), 1_700_000_000);
+        payment.treasury = treasury.to_string();
+        let subscription = Pubkey::from_str(&payment.subscription).unwrap();
+
+        let treasury = treasury.to_string();
+        let amount = payment.amount;
+        let cases = [
+            (user, DEVNET_USDC, treasury.as_str(), amount, Some((true, amount))),
+            (user, DEVNET_USDC, treasury.as_str(), amount - 1, Some((false, amount - 1))),
+            (user, "OtherMint", treasury.as_str(), amount, Some((false, 0))),
+            (user, DEVNET_USDC, "SomeoneElse", amount, Some((false, 0))),
+            // A subscribe of the same subscription for another user pays nothing
+            (Pubkey::new_unique(), DEVNET_USDC, treasury.as_str(), amount, None),
+        ];
+        for (signer, mint, owner, transferred, expected) in cases {
+            *transaction.lock().unwrap() = subscribe_transaction(reader.program_id(), signer, subscription, mint, owner, transferred);
+            let outcome = match reader.check_payment(&payment).await.unwrap() {
+                PaymentCheck::Paid { received, .. } => Some((true, received)),
+                PaymentCheck::Underpaid { received, .. } => Some((false, received)),
+                PaymentCheck::NotFound => None,
+            };
+            assert_eq!(outcome, expected, "{} to {} of {}", transferred, owner, mint);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{keypair_from_seed, Keypair, Signer};
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Where the backend wallet's secret key is read from. `Debug` never prints the secret.
+#[derive(Clone, Deserialize)]
+pub enum WalletKey {
+    /// A `solana-keygen` keypair file: a JSON array of 64 bytes.
+    File(PathBuf),
+    /// A base58 secret key or a JSON array of 64 bytes.
+    Inline(String),
+}
+
+impl WalletKey {
+    /// `SOLANA_KEYPAIR_PATH` if set, else `SOLANA_WALLET_KEY`, else the older `SOLANA_PRIVATE_KEY`.
+    pub fn from_env() -> Option<Self> {
+        if let Ok(path) = env::var("SOLANA_KEYPAIR_PATH") {
+            return Some(WalletKey::File(PathBuf::from(path)));
+        }
+        if let Ok(secret) = env::var("SOLANA_WALLET_KEY") {
+            return Some(WalletKey::Inline(secret));
+        }
+        env::var("SOLANA_PRIVATE_KEY").ok().map(|secret| {
+            println!("⚠️ SOLANA_PRIVATE_KEY is deprecated, use SOLANA_WALLET_KEY or SOLANA_KEYPAIR_PATH");
+            WalletKey::Inline(secret)
+        })
+    }
+}
+
+impl fmt::Debug for WalletKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalletKey::File(path) => f.debug_tuple("File").field(path).finish(),
+            WalletKey::Inline(_) => f.write_str("Inline(<redacted>)"),
+        }
+    }
+}
+
+/// The backend wallet's keypair. Only the public key is ever displayed or returned,
+/// and load errors never echo the key material.
+#[derive(Clone)]
+pub struct WalletSigner {
+    keypair: Arc<Keypair>,
+    source: &'static str,
+}
+
+impl WalletSigner {
+    pub fn load(key: &WalletKey) -> Result<Self, String> {
+        let (bytes, source) = match key {
+            WalletKey::File(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Cannot read keypair file {}: {}", path.display(), e))?;
+                let bytes = byte_array(&text)
+                    .ok_or_else(|| format!("Keypair file {} is not a JSON array of 64 bytes", path.display()))?;
+                (bytes, "keypair_file")
+            },
+            WalletKey::Inline(secret) if secret.trim_start().starts_with('[') => {
+                let bytes = byte_array(secret).ok_or("SOLANA_WALLET_KEY is not a JSON array of 64 bytes")?;
+                (bytes, "byte_array")
+            },
+            WalletKey::Inline(secret) => {
+                let bytes = bs58::decode(secret.trim())
+                    .into_vec()
+                    .map_err(|_| "SOLANA_WALLET_KEY is not valid base58".to_string())?;
+                (bytes, "base58")
+            },
+        };
+        if bytes.len() != 64 {
+            return Err(format!("Wallet key from {} is not a 64-byte secret key", source));
+        }
+
+        // Derive the keypair from the seed so a mismatched public half is caught
+        let keypair = keypair_from_seed(&bytes[..32])
+            .map_err(|_| format!("Wallet key from {} is not a valid ed25519 secret key", source))?;
+        if keypair.pubkey().to_bytes()[..] != bytes[32..] {
+            return Err(format!("Wallet key from {} has a public key that does not match its secret", source));
+        }
+
+        Ok(Self { keypair: Arc::new(keypair), source })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    /// How the key was supplied: `keypair_file`, `base58` or `byte_array`.
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    pub(super) fn keypair(&self) -> Arc<Keypair> {
+        self.keypair.clone()
+    }
+}
+
+impl fmt::Debug for WalletSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalletSigner")
+            .field("pubkey", &self.pubkey())
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+fn byte_array(text: &str) -> Option<Vec<u8>> {
+    serde_json::from_str::<Vec<u8>>(text.trim()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loaded(key: WalletKey) -> Result<(Pubkey, &'static str), String> {
+        WalletSigner::load(&key).map(|signer| (signer.pubkey(), signer.source()))
+    }
+
+    #[test]
+    fn loads_each_key_format() {
+        let keypair = Keypair::new();
+        let bytes = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+
+        let path = env::temp_dir().join(format!("wallet-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, &bytes).unwrap();
+        assert_eq!(loaded(WalletKey::File(path.clone())), Ok((keypair.pubkey(), "keypair_file")));
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded(WalletKey::Inline(keypair.to_base58_string())), Ok((keypair.pubkey(), "base58")));
+        assert_eq!(loaded(WalletKey::Inline(format!(" {}\n", bytes))), Ok((keypair.pubkey(), "byte_array")));
+
+        assert!(loaded(WalletKey::File(path)).unwrap_err().starts_with("Cannot read keypair file"));
+        assert_eq!(loaded(WalletKey::Inline("[1, 2]".to_string())), Err("Wallet key from byte_array is not a 64-byte secret key".to_string()));
+        assert_eq!(loaded(WalletKey::Inline("0OIl".to_string())), Err("SOLANA_WALLET_KEY is not valid base58".to_string()));
+    }
+
+    #[test]
+    fn rejects_a_mismatched_public_half() {
+        let mut bytes = Keypair::new().to_bytes();
+        bytes[32..].copy_from_slice(&Keypair::new().pubkey().to_bytes());
+
+        let error = loaded(WalletKey::Inline(bs58::encode(bytes).into_string())).unwrap_err();
+        assert_eq!(error, "Wallet key from base58 has a public key that does not match its secret");
+    }
+
+    #[test]
+    fn debug_never_prints_the_secret() {
+        let keypair = Keypair::new();
+        let secret = keypair.to_base58_string();
+        let key = WalletKey::Inline(secret.clone());
+        let signer = WalletSigner::load(&key).unwrap();
+
+        assert_eq!(format!("{:?}", key), "Inline(<redacted>)");
+        let debug = format!("{:?}", signer);
+        assert!(debug.contains(&keypair.pubkey().to_string()));
+        assert!(!debug.contains(&secret));
+        assert!(!debug.contains(&format!("{:?}", &keypair.to_bytes()[..32])));
+    }
+}
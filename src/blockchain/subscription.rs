@@ -0,0 +1,161 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_account_decoder::UiDataSliceConfig;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use base64::Engine;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+use super::errors::SendError;
+use super::{accounts, idl, SolanaClient};
+
+/// Instructions of `subscription_program` that act on an existing subscription.
+#[derive(Debug, Clone)]
+pub enum SubscriptionInstruction {
+    Cancel,
+    UpdatePlan { new_tier: u8 },
+    RetryPayment { amount_paid: u64, period_days: i64 },
+    ManualResubscribe { amount_paid: u64, period_days: i64 },
+}
+
+impl SubscriptionInstruction {
+    /// Instruction name in the IDL.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SubscriptionInstruction::Cancel => "cancel_subscription",
+            SubscriptionInstruction::UpdatePlan { .. } => "update_plan",
+            SubscriptionInstruction::RetryPayment { .. } => "retry_payment",
+            SubscriptionInstruction::ManualResubscribe { .. } => "manual_resubscribe",
+        }
+    }
+
+    fn args(&self) -> Value {
+        match self {
+            SubscriptionInstruction::Cancel => json!({}),
+            SubscriptionInstruction::UpdatePlan { new_tier } => json!({"new_tier": new_tier}),
+            SubscriptionInstruction::RetryPayment { amount_paid, period_days }
+            | SubscriptionInstruction::ManualResubscribe { amount_paid, period_days } => {
+                json!({"amount_paid": amount_paid, "period_days": period_days})
+            },
+        }
+    }
+}
+
+/// A confirmed `subscription_program` transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubmittedTransaction {
+    pub instruction: &'static str,
+    pub signature: String,
+    /// The `SubscriptionAccount` the instruction acted on.
+    pub subscription: String,
+    pub user: String,
+}
+
+impl SolanaClient {
+    /// Creates a subscription for the backend wallet.
+    ///
+    /// The program takes `subscription` as a signer rather than a PDA, so each
+    /// subscription lives at a freshly generated keypair address that is returned.
+    pub async fn subscribe(&self, plan_tier: u8, amount_paid: u64, period_days: i64) -> Result<SubmittedTransaction, SendError> {
+        let subscription = Keypair::new();
+        let program = self.program()?;
+        let ix = idl::subscription_program().build_instruction(
+            self.program_id,
+            "subscribe",
+            &[("user", self.wallet()), ("subscription", subscription.pubkey())],
+            &json!({"plan_tier": plan_tier, "amount_paid": amount_paid, "period_days": period_days}),
+        )?;
+
+        let signature = program.request()
+            .instruction(ix)
+            .signer(&subscription)
+            .send()
+            .await
+            .map_err(|e| SendError::from_client("subscribe", e))?;
+        accounts::invalidate(&self.wallet());
+
+        Ok(SubmittedTransaction {
+            instruction: "subscribe",
+            signature: signature.to_string(),
+            subscription: subscription.pubkey().to_string(),
+            user: self.wallet().to_string(),
+        })
+    }
+
+    /// Sends `instruction` for a subscription owned by the backend wallet.
+    pub async fn update_subscription(&self, subscription: Pubkey, instruction: SubscriptionInstruction) -> Result<SubmittedTransaction, SendError> {
+        let program = self.program()?;
+        let ix = idl::subscription_program().build_instruction(
+            self.program_id,
+            instruction.name(),
+            &[("user", self.wallet()), ("subscription", subscription)],
+            &instruction.args(),
+        )?;
+
+        let signature = program.request()
+            .instruction(ix)
+            .send()
+            .await
+            .map_err(|e| SendError::from_client(instruction.name(), e))?;
+        accounts::invalidate(&self.wallet());
+
+        Ok(SubmittedTransaction {
+            instruction: instruction.name(),
+            signature: signature.to_string(),
+            subscription: subscription.to_string(),
+            user: self.wallet().to_string(),
+        })
+    }
+
+    /// Number of `SubscriptionAccount`s owned by the program. Only the keys are fetched.
+    pub async fn count_subscriptions(&self) -> Result<usize, String> {
+        let discriminator = idl::subscription_program()
+            .account_discriminator("SubscriptionAccount")
+            .ok_or("IDL has no SubscriptionAccount")?;
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &discriminator))]),
+            account_config: RpcAccountInfoConfig {
+                data_slice: Some(UiDataSliceConfig { offset: 0, length: 0 }),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+
+        self.program()?
+            .async_rpc()
+            .get_program_accounts_with_config(&self.program_id, config)
+            .await
+            .map(|accounts| accounts.len())
+            .map_err(|e| format!("getProgramAccounts failed: {}", e))
+    }
+}
+
+/// Builds `instruction` for a subscription owned by `user` as an unsigned transaction
+/// with `user` as fee payer, base64-encoded for the user's wallet to sign and send.
+pub fn unsigned_transaction(
+    program_id: Pubkey,
+    user: Pubkey,
+    subscription: Pubkey,
+    instruction: &SubscriptionInstruction,
+    recent_blockhash: Hash,
+) -> Result<String, String> {
+    let ix = idl::subscription_program().build_instruction(
+        program_id,
+        instruction.name(),
+        &[("user", user), ("subscription", subscription)],
+        &instruction.args(),
+    )?;
+
+    let mut transaction = Transaction::new_with_payer(&[ix], Some(&user));
+    transaction.message.recent_blockhash = recent_blockhash;
+    encode_transaction(&transaction)
+}
+
+/// Base64 of the wire format, as wallets expect it.
+pub(super) fn encode_transaction(transaction: &Transaction) -> Result<String, String> {
+    let bytes = bincode::serialize(transaction).map_err(|e| e.to_string())?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
@@ -0,0 +1,317 @@
+use actix_web::web;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::blockchain::{actions, SolanaClient};
+use crate::events;
+use crate::storage::conditions::{ConditionOperator, ConditionSpec, ConditionTrigger, PriceCondition, RearmPolicy};
+use crate::storage::Database;
+use crate::symbols as registry;
+
+/// Longest `percent_change` window, and how much price history is kept per symbol.
+pub const MAX_WINDOW_SECONDS: i64 = 86_400;
+
+/// Normalizes and checks a condition before it is stored.
+pub fn validate(spec: &mut ConditionSpec) -> Result<(), String> {
+    spec.symbol = spec.symbol.trim().to_uppercase();
+    if !registry::is_supported(&spec.symbol) {
+        return Err(format!("Unsupported symbol {}. Supported: {}", spec.symbol, registry::symbols().join(", ")));
+    }
+    if !spec.value.is_finite() {
+        return Err("value must be a finite number".to_string());
+    }
+
+    match spec.operator {
+        ConditionOperator::PercentChange => {
+            if spec.value == 0.0 {
+                return Err("percent_change needs a non-zero value".to_string());
+            }
+            match spec.window_seconds {
+                Some(window) if (1..=MAX_WINDOW_SECONDS).contains(&window) => {},
+                _ => return Err(format!("percent_change needs window_seconds between 1 and {}", MAX_WINDOW_SECONDS)),
+            }
+        },
+        _ => {
+            if spec.value <= 0.0 {
+                return Err("value must be a positive price".to_string());
+            }
+            spec.window_seconds = None;
+        },
+    }
+
+    match spec.rearm {
+        RearmPolicy::Cooldown if spec.cooldown_seconds.is_none_or(|c| c <= 0) => {
+            Err("the cooldown re-arm policy needs a positive cooldown_seconds".to_string())
+        },
+        RearmPolicy::Cooldown => Ok(()),
+        _ => {
+            spec.cooldown_seconds = None;
+            Ok(())
+        },
+    }
+}
+
+/// Whether the condition's state holds at `price`. Crossings additionally need the
+/// previous price on the other side of the level to fire.
+fn holds(spec: &ConditionSpec, price: f64, reference: Option<f64>) -> bool {
+    match spec.operator {
+        ConditionOperator::Above | ConditionOperator::CrossesAbove => price > spec.value,
+        ConditionOperator::Below | ConditionOperator::CrossesBelow => price < spec.value,
+        ConditionOperator::PercentChange => match reference {
+            Some(start) if start > 0.0 => {
+                let change = (price - start) / start * 100.0;
+                if spec.value > 0.0 { change >= spec.value } else { change <= spec.value }
+            },
+            _ => false,
+        },
+    }
+}
+
+fn fires(spec: &ConditionSpec, price: f64, reference: Option<f64>) -> bool {
+    holds(spec, price, reference) && match spec.operator {
+        ConditionOperator::CrossesAbove => reference.is_some_and(|previous| previous <= spec.value),
+        ConditionOperator::CrossesBelow => reference.is_some_and(|previous| previous >= spec.value),
+        _ => true,
+    }
+}
+
+/// The oldest sample inside `window_seconds`.
+fn window_start(samples: &VecDeque<(i64, f64)>, now: i64, window_seconds: i64) -> Option<f64> {
+    samples.iter().find(|(at, _)| *at >= now - window_seconds).map(|(_, price)| *price)
+}
+
+/// Evaluates every condition on `symbol` against a new price.
+fn evaluate(
+    db: &Database,
+    symbol: &str,
+    price: f64,
+    previous: Option<f64>,
+    samples: &VecDeque<(i64, f64)>,
+    now: i64,
+) -> Result<Vec<(PriceCondition, ConditionTrigger)>, String> {
+    let mut triggered = Vec::new();
+
+    for condition in db.conditions(Some(symbol))? {
+        let spec = &condition.spec;
+        let reference = match spec.operator {
+            ConditionOperator::PercentChange => window_start(samples, now, spec.window_seconds.unwrap_or(MAX_WINDOW_SECONDS)),
+            ConditionOperator::CrossesAbove | ConditionOperator::CrossesBelow => previous,
+            ConditionOperator::Above | ConditionOperator::Below => None,
+        };
+
+        if !condition.armed {
+            let rearm = match spec.rearm {
+                RearmPolicy::Once => false,
+                RearmPolicy::Cooldown => condition.last_triggered_at
+                    .is_some_and(|at| now - at >= spec.cooldown_seconds.unwrap_or_default()),
+                RearmPolicy::Reset => !holds(spec, price, reference),
+            };
+            // Re-armed conditions fire from the next update on
+            if rearm {
+                db.set_condition_armed(condition.id, true)?;
+            }
+            continue;
+        }
+
+        if fires(spec, price, reference) {
+            let mut trigger = ConditionTrigger {
+                id: 0,
+                condition_id: condition.id,
+                symbol: symbol.to_string(),
+                price,
+                reference_price: reference,
+                triggered_at: now,
+            };
+            trigger.id = db.record_trigger(&trigger)?;
+            triggered.push((condition, trigger));
+        }
+    }
+
+    Ok(triggered)
+}
+
+/// Evaluates conditions on every price-cache update, i.e. every `prices:<SYMBOL>` event,
+/// and runs the on-chain actions of those that fire. Runs on the actix system arbiter
+/// since anchor-client's request builder is not `Send`.
+pub fn spawn(db: web::Data<Database>, solana: web::Data<Option<SolanaClient>>) {
+    let mut updates = events::subscribe();
+
+    actix_rt::spawn(async move {
+        let mut history: HashMap<String, VecDeque<(i64, f64)>> = HashMap::new();
+
+        loop {
+            let event = match updates.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    println!("⚠️ Condition engine lagged, skipped {} events", skipped);
+                    continue;
+                },
+                Err(RecvError::Closed) => break,
+            };
+            let Some(symbol) = event.channel.strip_prefix("prices:") else {
+                continue;
+            };
+            let Some(price) = event.data.get("price").and_then(Value::as_f64) else {
+                continue;
+            };
+            if event.data.get("stale").and_then(Value::as_bool) == Some(true) {
+                continue;
+            }
+
+            // At most one sample per second, enough for the longest window
+            let now = Utc::now().timestamp();
+            let samples = history.entry(symbol.to_string()).or_default();
+            let previous = samples.back().map(|(_, p)| *p);
+            match samples.back_mut() {
+                Some(last) if last.0 == now => last.1 = price,
+                _ => samples.push_back((now, price)),
+            }
+            while samples.front().is_some_and(|(at, _)| *at < now - MAX_WINDOW_SECONDS) {
+                samples.pop_front();
+            }
+
+            match evaluate(&db, symbol, price, previous, samples, now) {
+                Ok(triggered) => {
+                    for (condition, trigger) in triggered {
+                        println!("🎯 Condition {} fired: {} {} {} at ${:.2}",
+                            condition.id, symbol, condition.spec.operator.as_str(), condition.spec.value, price);
+                        events::publish("conditions", symbol, "condition_triggered", json!({
+                            "condition": condition,
+                            "trigger": trigger,
+                        }));
+
+                        // Sending can take seconds, so price updates keep flowing meanwhile
+                        let (db, solana) = (db.clone(), solana.clone());
+                        actix_rt::spawn(async move {
+                            actions::run_triggered(&trigger, &db, solana.as_ref().as_ref()).await;
+                        });
+                    }
+                },
+                Err(e) => println!("❌ Condition evaluation for {} failed: {}", symbol, e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::conditions::ConditionOperator::*;
+
+    fn spec(operator: ConditionOperator, value: f64, rearm: RearmPolicy) -> ConditionSpec {
+        ConditionSpec {
+            symbol: "BTC".to_string(),
+            operator,
+            value,
+            window_seconds: (operator == PercentChange).then_some(60),
+            rearm,
+            cooldown_seconds: (rearm == RearmPolicy::Cooldown).then_some(30),
+            label: None,
+        }
+    }
+
+    fn checked(mut spec: ConditionSpec) -> Result<ConditionSpec, String> {
+        validate(&mut spec).map(|_| spec)
+    }
+
+    #[test]
+    fn validates_and_normalizes_specs() {
+        let normalized = checked(ConditionSpec {
+            symbol: " btc ".to_string(),
+            window_seconds: Some(60),
+            cooldown_seconds: Some(30),
+            ..spec(Above, 100.0, RearmPolicy::Once)
+        }).unwrap();
+        assert_eq!((normalized.symbol.as_str(), normalized.window_seconds, normalized.cooldown_seconds), ("BTC", None, None));
+        let unsupported = ConditionSpec { symbol: "NOPE".to_string(), ..spec(Above, 100.0, RearmPolicy::Once) };
+        assert!(checked(unsupported).unwrap_err().starts_with("Unsupported symbol NOPE."));
+
+        assert!(checked(spec(Above, f64::NAN, RearmPolicy::Once)).is_err());
+        assert!(checked(spec(Below, 0.0, RearmPolicy::Once)).is_err());
+        assert!(checked(spec(PercentChange, -5.0, RearmPolicy::Once)).is_ok());
+        assert!(checked(spec(PercentChange, 0.0, RearmPolicy::Once)).is_err());
+        assert!(checked(ConditionSpec { window_seconds: None, ..spec(PercentChange, 5.0, RearmPolicy::Once) }).is_err());
+        assert!(checked(ConditionSpec { window_seconds: Some(MAX_WINDOW_SECONDS + 1), ..spec(PercentChange, 5.0, RearmPolicy::Once) }).is_err());
+
+        assert!(checked(spec(Above, 100.0, RearmPolicy::Cooldown)).is_ok());
+        assert!(checked(ConditionSpec { cooldown_seconds: None, ..spec(Above, 100.0, RearmPolicy::Cooldown) }).is_err());
+        assert!(checked(ConditionSpec { cooldown_seconds: Some(0), ..spec(Above, 100.0, RearmPolicy::Cooldown) }).is_err());
+    }
+
+    #[test]
+    fn levels_hold_and_crossings_need_the_previous_price() {
+        let above = spec(Above, 100.0, RearmPolicy::Once);
+        assert!(fires(&above, 101.0, None));
+        assert!(!fires(&above, 100.0, None));
+
+        let crosses_above = spec(CrossesAbove, 100.0, RearmPolicy::Once);
+        assert!(holds(&crosses_above, 101.0, None));
+        assert!(!fires(&crosses_above, 101.0, None));
+        assert!(fires(&crosses_above, 101.0, Some(100.0)));
+        assert!(!fires(&crosses_above, 101.0, Some(100.5)));
+
+        let crosses_below = spec(CrossesBelow, 100.0, RearmPolicy::Once);
+        assert!(!fires(&crosses_below, 99.0, None));
+        assert!(fires(&crosses_below, 99.0, Some(100.0)));
+        assert!(!fires(&crosses_below, 99.0, Some(99.5)));
+
+        let rise = spec(PercentChange, 5.0, RearmPolicy::Once);
+        assert!(fires(&rise, 105.0, Some(100.0)));
+        assert!(!fires(&rise, 104.0, Some(100.0)));
+        assert!(!fires(&rise, 105.0, None));
+        assert!(!fires(&rise, 105.0, Some(0.0)));
+        let drop = spec(PercentChange, -5.0, RearmPolicy::Once);
+        assert!(fires(&drop, 95.0, Some(100.0)));
+        assert!(!fires(&drop, 105.0, Some(100.0)));
+    }
+
+    #[test]
+    fn the_window_starts_at_its_oldest_sample() {
+        let samples = VecDeque::from([(100, 1.0), (130, 2.0), (160, 3.0)]);
+        assert_eq!(window_start(&samples, 160, 60), Some(1.0));
+        assert_eq!(window_start(&samples, 170, 60), Some(2.0));
+        assert_eq!(window_start(&samples, 250, 60), None);
+    }
+
+    /// Feeds `(now, price)` updates in order and returns the times that fired.
+    fn firings(db: &Database, prices: &[(i64, f64)]) -> Vec<i64> {
+        let mut previous = None;
+        let mut fired = Vec::new();
+        for &(now, price) in prices {
+            if !evaluate(db, "BTC", price, previous, &VecDeque::from([(now, price)]), now).unwrap().is_empty() {
+                fired.push(now);
+            }
+            previous = Some(price);
+        }
+        fired
+    }
+
+    #[test]
+    fn rearms_per_policy() {
+        let prices = [(0, 101.0), (10, 102.0), (20, 99.0), (30, 103.0), (40, 104.0), (50, 105.0)];
+
+        let db = Database::in_memory().unwrap();
+        let id = db.insert_condition(&spec(Above, 100.0, RearmPolicy::Once), 0).unwrap();
+        assert_eq!(firings(&db, &prices), [0]);
+        assert!(!db.condition(id).unwrap().unwrap().armed);
+
+        // Re-armed once the cooldown has passed, and fires from the update after
+        let db = Database::in_memory().unwrap();
+        db.insert_condition(&spec(Above, 100.0, RearmPolicy::Cooldown), 0).unwrap();
+        assert_eq!(firings(&db, &prices), [0, 40]);
+
+        // Re-armed once the state stops holding
+        let db = Database::in_memory().unwrap();
+        let id = db.insert_condition(&spec(Above, 100.0, RearmPolicy::Reset), 0).unwrap();
+        assert_eq!(firings(&db, &prices), [0, 30]);
+        let condition = db.condition(id).unwrap().unwrap();
+        assert_eq!((condition.trigger_count, condition.last_triggered_at), (2, Some(30)));
+        let triggers = db.condition_triggers(id, 10).unwrap();
+        assert_eq!(triggers.iter().map(|t| t.price).collect::<Vec<_>>(), [103.0, 101.0]);
+
+        // Only conditions on the updated symbol are evaluated
+        assert!(evaluate(&db, "ETH", 1_000.0, None, &VecDeque::new(), 60).unwrap().is_empty());
+    }
+}
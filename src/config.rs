@@ -1,5 +1,14 @@
 use serde::Deserialize;
 use std::env;
+use std::net::IpAddr;
+
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::blockchain::payments::PaymentMint;
+use crate::blockchain::signer::WalletKey;
+use crate::blockchain::{cluster_name, DEFAULT_RPC_URL};
+use crate::plans::{self, PlanTier};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -7,7 +16,47 @@ pub struct Config {
     pub port: u16,
     pub environment: String,
     pub binance_base_url: String,
-    pub trading_pairs: Vec<String>,
+    pub binance_ws_url: String,
+    /// Stream live prices for the registry's Binance pairs into the price cache.
+    pub price_stream_enabled: bool,
+    /// Keep klines for the registry's Binance pairs at every interval ingested into the candle cache.
+    pub candle_ingest_enabled: bool,
+    
+    // Price providers: coingecko, binance, kraken or mock. Several price providers
+    // are combined by median consensus; history providers are tried in order.
+    pub price_providers: Vec<String>,
+    pub history_providers: Vec<String>,
+    /// Quotes further than this fraction from the median are dropped as outliers.
+    pub max_price_deviation: f64,
+    pub coingecko_base_url: String,
+    pub kraken_base_url: String,
+    /// JSON file backing the mock provider; built-in prices when unset.
+    pub mock_prices_file: Option<String>,
+    /// JSON file backing the symbol registry; built-in symbols when unset.
+    pub symbols_file: Option<String>,
+    /// Required in `X-Admin-Key` for `/admin/*`; admin routes are off when unset.
+    pub admin_api_key: Option<String>,
+    /// Reverse proxies whose `X-Forwarded-For` is believed when rate limiting by address.
+    pub trusted_proxies: Vec<IpAddr>,
+    
+    // TradingView webhook
+    /// Named shared secrets (`source:secret`) accepted by `/tradingview-webhook`.
+    /// Without any, every webhook is refused unless `webhook_allow_unauthenticated` is set.
+    pub webhook_secrets: Vec<(String, String)>,
+    /// Accept unauthenticated webhooks when no secrets are configured, for local testing.
+    pub webhook_allow_unauthenticated: bool,
+    /// Reject webhooks without a valid HMAC-SHA256 body signature.
+    pub webhook_require_signature: bool,
+    /// Maximum age of a webhook `timestamp`, and how long nonces are remembered.
+    pub webhook_replay_window_seconds: i64,
+    /// Rejected webhooks are kept in the audit log this many days; 0 keeps them forever.
+    pub webhook_audit_retention_days: u32,
+    
+    // Storage
+    /// SQLite file holding TradingView alerts.
+    pub database_path: String,
+    /// Alerts older than this many days are deleted; 0 keeps them forever.
+    pub alert_retention_days: u32,
     
     // Signal parameters
     pub ema_short_period: usize,
@@ -18,21 +67,123 @@ pub struct Config {
     pub macd_fast: usize,
     pub macd_slow: usize,
     pub macd_signal: usize,
+    /// Generators merged into the combined signal, with their weights.
+    pub signal_weights: Vec<(String, f64)>,
+    /// Agreement ratio (0..1) above which a signal is promoted to StrongBuy/StrongSell.
+    pub strong_signal_threshold: f64,
+    /// TradingView buy/sell alerts count towards signals for this long, fading linearly.
+    pub alert_signal_ttl_seconds: i64,
+    /// Weight of a fresh TradingView alert relative to the indicator weights.
+    pub alert_signal_weight: f64,
+    /// Cap on the combined weight of all TradingView alerts for a symbol.
+    pub alert_signal_max_weight: f64,
     
     // Solana blockchain
     pub solana_rpc_url: Option<String>,
-    pub solana_wallet_key: Option<String>,
+    /// Backend wallet key, from `SOLANA_KEYPAIR_PATH` or `SOLANA_WALLET_KEY`.
+    pub solana_wallet: Option<WalletKey>,
     pub solana_program_id: Option<String>,
     pub update_interval_seconds: Option<u64>,
+    /// How long decoded `SubscriptionAccount`s are cached per wallet.
+    pub subscription_cache_seconds: u64,
+    /// What each on-chain `plan_tier` unlocks, sorted by tier.
+    pub plan_tiers: Vec<PlanTier>,
+    /// How often the lifecycle watcher scans subscriptions.
+    pub lifecycle_interval_seconds: u64,
+    /// Days before `end_time` to send an expiry reminder.
+    pub expiry_reminder_days: i64,
+    /// How often the transaction indexer polls for new program transactions.
+    pub indexer_interval_seconds: u64,
+    
+    // Token payments
+    /// Wallet receiving plan payments; the backend wallet when unset.
+    pub payment_treasury: Option<Pubkey>,
+    /// SPL mints plans can be paid in, from `PAYMENT_MINTS` (`SYMBOL:mint:decimals[:price_symbol]`).
+    /// USDC of the configured cluster by default.
+    pub payment_mints: Vec<PaymentMint>,
+    /// Only subscriptions with a verified token payment grant their plan. On unless
+    /// `REQUIRE_VERIFIED_PAYMENT=false`, since anyone can call `subscribe` with any `amount_paid`.
+    pub require_verified_payment: bool,
+    /// How often pending Solana Pay checkouts are looked up on-chain.
+    pub checkout_poll_seconds: u64,
+    /// Merchant name wallets show for Solana Pay checkouts.
+    pub pay_label: String,
+    /// Absolute URL of the checkout icon; `/pay/icon.svg` when unset.
+    pub pay_icon_url: Option<String>,
+    /// Requests per minute each client address may make to `/pay`.
+    pub pay_requests_per_minute: u32,
+    /// Unexpired pending checkouts one account may hold, one client address may open,
+    /// and all of them together.
+    pub max_pending_checkouts_per_account: u32,
+    pub max_pending_checkouts_per_client: u32,
+    pub max_pending_checkouts: u32,
+    
+    // Wallet sign-in
+    /// Domain named in the Sign-In With Solana message.
+    pub auth_domain: String,
+    pub session_ttl_seconds: i64,
+    /// Sign-in challenges each client address may request per minute.
+    pub challenge_requests_per_minute: u32,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self, String> {
-        let trading_pairs = env::var("TRADING_PAIRS")
-            .unwrap_or_else(|_| "BTCUSDT,ETHUSDT,SOLUSDT".to_string())
+        let signal_weights = env::var("SIGNAL_WEIGHTS")
+            .unwrap_or_else(|_| "ema:1.0,rsi:1.0,macd:1.0".to_string())
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(|entry| {
+                let (name, weight) = entry.split_once(':')
+                    .ok_or_else(|| format!("Invalid SIGNAL_WEIGHTS entry: {}", entry))?;
+                let weight = weight.trim().parse::<f64>()
+                    .map_err(|e| format!("Invalid weight for {}: {}", name.trim(), e))?;
+                Ok((name.trim().to_lowercase(), weight))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        
+        let webhook_secrets = env::var("TRADINGVIEW_WEBHOOK_SECRETS")
+            .unwrap_or_default()
             .split(',')
-            .map(|s| s.trim().to_string())
-            .collect();
+            .filter(|s| !s.trim().is_empty())
+            .map(|entry| {
+                let (source, secret) = entry.split_once(':')
+                    .ok_or("Invalid TRADINGVIEW_WEBHOOK_SECRETS entry: expected source:secret")?;
+                if secret.trim().is_empty() {
+                    return Err(format!("Empty webhook secret for {}", source.trim()));
+                }
+                Ok((source.trim().to_string(), secret.trim().to_string()))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        
+        let plan_tiers = match env::var("PLAN_TIERS_FILE") {
+            Ok(path) => {
+                let contents = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Cannot read PLAN_TIERS_FILE {}: {}", path, e))?;
+                plans::parse_catalogue(&contents)
+                    .map_err(|e| format!("Invalid PLAN_TIERS_FILE {}: {}", path, e))?
+            },
+            Err(_) => plans::default_catalogue(),
+        };
+        
+        let provider_list = |value: String| -> Vec<String> {
+            value.split(',')
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+        
+        let solana_rpc_url = env::var("SOLANA_RPC_URL").ok();
+        let payment_mints = match env::var("PAYMENT_MINTS") {
+            Ok(entries) => entries.split(',')
+                .filter(|s| !s.trim().is_empty())
+                .map(PaymentMint::parse)
+                .collect::<Result<Vec<_>, String>>()?,
+            Err(_) => vec![PaymentMint::default_usdc(cluster_name(solana_rpc_url.as_deref().unwrap_or(DEFAULT_RPC_URL)))],
+        };
+        
+        let price_providers = provider_list(env::var("PRICE_PROVIDER")
+            .unwrap_or_else(|_| "coingecko,binance,kraken".to_string()));
+        let offline = price_providers == ["mock"];
         
         Ok(Config {
             host: env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
@@ -43,7 +194,57 @@ impl Config {
             environment: env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string()),
             binance_base_url: env::var("BINANCE_BASE_URL")
                 .unwrap_or_else(|_| "https://api.binance.com".to_string()),
-            trading_pairs,
+            binance_ws_url: env::var("BINANCE_WS_URL")
+                .unwrap_or_else(|_| "wss://stream.binance.com:9443".to_string()),
+            price_stream_enabled: env::var("PRICE_STREAM_ENABLED")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(!offline),
+            candle_ingest_enabled: env::var("CANDLE_INGEST_ENABLED")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(!offline),
+            history_providers: provider_list(env::var("HISTORY_PROVIDER")
+                .unwrap_or_else(|_| if offline { "mock".to_string() } else { "binance,coingecko".to_string() })),
+            price_providers,
+            max_price_deviation: env::var("PRICE_MAX_DEVIATION")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid PRICE_MAX_DEVIATION: {}", e))?,
+            coingecko_base_url: env::var("COINGECKO_BASE_URL")
+                .unwrap_or_else(|_| "https://api.coingecko.com/api/v3".to_string()),
+            kraken_base_url: env::var("KRAKEN_BASE_URL")
+                .unwrap_or_else(|_| "https://api.kraken.com".to_string()),
+            mock_prices_file: env::var("MOCK_PRICES_FILE").ok(),
+            symbols_file: env::var("SYMBOLS_FILE").ok(),
+            admin_api_key: env::var("ADMIN_API_KEY").ok().filter(|k| !k.is_empty()),
+            trusted_proxies: env::var("TRUSTED_PROXIES")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.trim().parse().map_err(|e| format!("Invalid TRUSTED_PROXIES entry {}: {}", s.trim(), e)))
+                .collect::<Result<Vec<_>, String>>()?,
+            
+            webhook_secrets,
+            webhook_allow_unauthenticated: env::var("WEBHOOK_ALLOW_UNAUTHENTICATED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            webhook_require_signature: env::var("TRADINGVIEW_REQUIRE_SIGNATURE")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            webhook_replay_window_seconds: env::var("WEBHOOK_REPLAY_WINDOW_SECONDS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid WEBHOOK_REPLAY_WINDOW_SECONDS: {}", e))?,
+            webhook_audit_retention_days: env::var("WEBHOOK_AUDIT_RETENTION_DAYS")
+                .unwrap_or_else(|_| "7".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid WEBHOOK_AUDIT_RETENTION_DAYS: {}", e))?,
+            
+            database_path: env::var("DATABASE_PATH")
+                .unwrap_or_else(|_| "data/trading_signals.db".to_string()),
+            alert_retention_days: env::var("ALERT_RETENTION_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid ALERT_RETENTION_DAYS: {}", e))?,
             
             // Default signal parameters
             ema_short_period: 12,
@@ -54,14 +255,88 @@ impl Config {
             macd_fast: 12,
             macd_slow: 26,
             macd_signal: 9,
+            signal_weights,
+            strong_signal_threshold: env::var("STRONG_SIGNAL_THRESHOLD")
+                .unwrap_or_else(|_| "0.75".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid STRONG_SIGNAL_THRESHOLD: {}", e))?,
+            alert_signal_ttl_seconds: env::var("ALERT_SIGNAL_TTL_SECONDS")
+                .unwrap_or_else(|_| "14400".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid ALERT_SIGNAL_TTL_SECONDS: {}", e))?,
+            alert_signal_weight: env::var("ALERT_SIGNAL_WEIGHT")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid ALERT_SIGNAL_WEIGHT: {}", e))?,
+            alert_signal_max_weight: env::var("ALERT_SIGNAL_MAX_WEIGHT")
+                .unwrap_or_else(|_| "2.0".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid ALERT_SIGNAL_MAX_WEIGHT: {}", e))?,
             
             // Solana (optional)
-            solana_rpc_url: env::var("SOLANA_RPC_URL").ok(),
-            solana_wallet_key: env::var("SOLANA_WALLET_KEY").ok(),
+            solana_rpc_url,
+            solana_wallet: WalletKey::from_env(),
             solana_program_id: env::var("SOLANA_PROGRAM_ID").ok(),
             update_interval_seconds: env::var("UPDATE_INTERVAL_SECONDS")
                 .ok()
                 .and_then(|s| s.parse().ok()),
+            subscription_cache_seconds: env::var("SUBSCRIPTION_CACHE_SECONDS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid SUBSCRIPTION_CACHE_SECONDS: {}", e))?,
+            plan_tiers,
+            lifecycle_interval_seconds: env::var("LIFECYCLE_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid LIFECYCLE_INTERVAL_SECONDS: {}", e))?,
+            expiry_reminder_days: env::var("EXPIRY_REMINDER_DAYS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid EXPIRY_REMINDER_DAYS: {}", e))?,
+            indexer_interval_seconds: env::var("INDEXER_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid INDEXER_INTERVAL_SECONDS: {}", e))?,
+            payment_treasury: env::var("PAYMENT_TREASURY")
+                .ok()
+                .map(|s| Pubkey::from_str(s.trim()).map_err(|e| format!("Invalid PAYMENT_TREASURY: {}", e)))
+                .transpose()?,
+            payment_mints,
+            require_verified_payment: env::var("REQUIRE_VERIFIED_PAYMENT")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            checkout_poll_seconds: env::var("CHECKOUT_POLL_SECONDS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid CHECKOUT_POLL_SECONDS: {}", e))?,
+            pay_label: env::var("PAY_LABEL").unwrap_or_else(|_| "Trading Signals".to_string()),
+            pay_icon_url: env::var("PAY_ICON_URL").ok().filter(|u| !u.is_empty()),
+            pay_requests_per_minute: env::var("PAY_REQUESTS_PER_MINUTE")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid PAY_REQUESTS_PER_MINUTE: {}", e))?,
+            max_pending_checkouts_per_account: env::var("MAX_PENDING_CHECKOUTS_PER_ACCOUNT")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid MAX_PENDING_CHECKOUTS_PER_ACCOUNT: {}", e))?,
+            max_pending_checkouts_per_client: env::var("MAX_PENDING_CHECKOUTS_PER_CLIENT")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid MAX_PENDING_CHECKOUTS_PER_CLIENT: {}", e))?,
+            max_pending_checkouts: env::var("MAX_PENDING_CHECKOUTS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid MAX_PENDING_CHECKOUTS: {}", e))?,
+            auth_domain: env::var("AUTH_DOMAIN")
+                .unwrap_or_else(|_| "trading-signals-backend".to_string()),
+            session_ttl_seconds: env::var("SESSION_TTL_SECONDS")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid SESSION_TTL_SECONDS: {}", e))?,
+            challenge_requests_per_minute: env::var("CHALLENGE_REQUESTS_PER_MINUTE")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .map_err(|e| format!("Invalid CHALLENGE_REQUESTS_PER_MINUTE: {}", e))?,
         })
     }
 }
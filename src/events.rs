@@ -0,0 +1,110 @@
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::broadcast;
+
+type EventLog = Arc<Mutex<HashMap<String, VecDeque<Event>>>>;
+
+static BUS: OnceLock<broadcast::Sender<Event>> = OnceLock::new();
+static LOG: OnceLock<EventLog> = OnceLock::new();
+/// Ids are a plain sequence from 1. They restart with the process, together with the
+/// replay log, so a `Last-Event-ID` above `last_id()` comes from a previous run.
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Slow subscribers that fall further behind than this skip the oldest events.
+const BUS_CAPACITY: usize = 1024;
+/// Events kept per kind (`signals`, `alerts`) for `Last-Event-ID` replay.
+const LOG_CAPACITY: usize = 256;
+/// Kinds recorded in the replay log. Price ticks are too frequent to be worth replaying.
+const LOGGED_KINDS: [&str; 2] = ["signals", "alerts"];
+/// Kinds keyed by wallet rather than symbol. Their channels are only matched by exact name.
+const PRIVATE_KINDS: [&str; 1] = ["subscriptions"];
+
+/// A push notification on a `<kind>:<SYMBOL>` channel, e.g. `prices:BTC`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Event {
+    pub id: u64,
+    pub channel: String,
+    pub event: &'static str,
+    pub data: Value,
+    pub timestamp: i64,
+}
+
+fn bus() -> &'static broadcast::Sender<Event> {
+    BUS.get_or_init(|| broadcast::channel(BUS_CAPACITY).0)
+}
+
+/// The latest id handed out; anything above it was not issued by this process.
+pub fn last_id() -> u64 {
+    NEXT_ID.load(Ordering::Relaxed).saturating_sub(1)
+}
+
+fn log() -> &'static EventLog {
+    LOG.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
+}
+
+/// Publishes to every live subscriber and records signals/alerts for replay.
+pub fn publish(kind: &str, symbol: &str, event: &'static str, data: Value) {
+    let event = Event {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        // Wallet addresses are case sensitive
+        channel: if PRIVATE_KINDS.contains(&kind) {
+            format!("{}:{}", kind, symbol)
+        } else {
+            format!("{}:{}", kind, symbol.to_uppercase())
+        },
+        event,
+        data,
+        timestamp: Utc::now().timestamp(),
+    };
+
+    if LOGGED_KINDS.contains(&kind) {
+        let mut log = log().lock().unwrap();
+        let entries = log.entry(kind.to_string()).or_default();
+        entries.push_back(event.clone());
+        while entries.len() > LOG_CAPACITY {
+            entries.pop_front();
+        }
+    }
+
+    // Err only means nobody is listening
+    let _ = bus().send(event);
+}
+
+pub fn subscribe() -> broadcast::Receiver<Event> {
+    bus().subscribe()
+}
+
+/// Logged events of `kind` newer than `last_id`, oldest first.
+pub fn replay_since(kind: &str, last_id: u64) -> Vec<Event> {
+    log().lock().unwrap()
+        .get(kind)
+        .map(|entries| entries.iter().filter(|e| e.id > last_id).cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Whether `pattern` (`prices:BTC`, `signals:*` or `*`) covers `channel`. Private
+/// channels such as `subscriptions:<wallet>` are never covered by a wildcard.
+pub fn channel_matches(pattern: &str, channel: &str) -> bool {
+    if is_private(channel) {
+        return pattern == channel;
+    }
+    if pattern == "*" {
+        return true;
+    }
+
+    match (pattern.split_once(':'), channel.split_once(':')) {
+        (Some((kind, symbol)), Some((channel_kind, channel_symbol))) => {
+            kind.eq_ignore_ascii_case(channel_kind)
+                && (symbol == "*" || symbol.eq_ignore_ascii_case(channel_symbol))
+        },
+        _ => false,
+    }
+}
+
+pub fn is_private(channel: &str) -> bool {
+    channel.split_once(':').is_some_and(|(kind, _)| PRIVATE_KINDS.contains(&kind))
+}
@@ -1,6 +1,25 @@
-use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+use actix_cors::Cors;
+use actix_web::{get, middleware, web, App, HttpResponse, HttpServer, Responder};
+mod blockchain;
+mod conditions;
+mod config;
+mod events;
+mod market_data;
+mod plans;
+mod providers;
 mod routes;
-use routes::signals;
+mod signals;
+mod storage;
+mod symbols;
+#[allow(dead_code)]
+mod utils;
+
+use blockchain::accounts::SubscriptionReader;
+use blockchain::SolanaClient;
+use config::Config;
+use providers::Providers;
+use storage::Database;
+use routes::signals as signal_routes;
 
 #[get("/_health")]
 async fn health() -> impl Responder {
@@ -47,7 +66,7 @@ async fn index() -> impl Responder {
     <div class="container">
         <h1>🚀 Trading Signals Backend</h1>
         <p>✅ Server is running on Railway!</p>
-        <p>📊 <strong>Live Prices from CoinGecko:</strong> BTC, ETH, SOL, PAXG</p>
+        <p>📊 <strong>Live Prices:</strong> see <a href="/health">/health</a> for supported coins</p>
         
         <h3>🌐 Available Endpoints:</h3>
         <div class="endpoint">
@@ -64,7 +83,11 @@ async fn index() -> impl Responder {
         </div>
         <div class="endpoint">
             <span class="method get">GET</span> 
-            <a href="/signals">/signals</a> - Trading signals based on live prices
+            <a href="/signals">/signals</a> - EMA/RSI/MACD trading signals
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            <a href="/candles/BTC?interval=1h&limit=100">/candles/{symbol}</a> - Historical OHLCV candles from Binance
         </div>
         <div class="endpoint">
             <span class="method get">GET</span> 
@@ -72,7 +95,11 @@ async fn index() -> impl Responder {
         </div>
         <div class="endpoint">
             <span class="method get">GET</span> 
-            <a href="/explain-all-signals">/explain-all-signals</a> - AI explains all signals
+            <a href="/explain-all-signals">/explain-all-signals</a> - AI explains all signals (paid plans)
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            <a href="/plans">/plans</a> - Plan tiers: symbols, refresh rate, rate limit, AI access and alert channels
         </div>
         <div class="endpoint">
             <span class="method post">POST</span> 
@@ -80,12 +107,20 @@ async fn index() -> impl Responder {
         </div>
         <div class="endpoint">
             <span class="method get">GET</span> 
-            <a href="/tradingview-alerts">/tradingview-alerts</a> - Recent alerts
+            <a href="/tradingview-alerts">/tradingview-alerts</a> - Stored alerts (?from=&to=&alert_name=&limit=&offset=)
         </div>
         <div class="endpoint">
             <span class="method get">GET</span> 
             <a href="/alerts/BTC">/alerts/{symbol}</a> - Alerts for specific symbol
         </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /ws?subscribe=prices:BTC,signals:* - WebSocket push for prices, signals and alerts
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            <a href="/stream/signals">/stream/signals</a>, <a href="/stream/alerts">/stream/alerts</a> - Server-Sent Events feeds
+        </div>
         <div class="endpoint">
             <span class="method get">GET</span> 
             <a href="/cache-stats">/cache-stats</a> - Cache statistics
@@ -98,12 +133,88 @@ async fn index() -> impl Responder {
             <span class="method post">POST</span> 
             /clear-cache - Clear price cache
         </div>
+        <div class="endpoint">
+            <span class="method post">POST</span> 
+            /auth/challenge, /auth/verify - Sign in with a Solana wallet for a session token
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            <a href="/subscription-status">/subscription-status</a> - On-chain subscription program status
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            <a href="/blockchain-status">/blockchain-status</a> - Backend wallet public key and cluster
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            <a href="/solana-health">/solana-health</a> - RPC version, slot and latency, and whether the program is deployed
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            <a href="/wallet-info">/wallet-info</a> - Service wallet SOL balance and recent transactions (?limit=)
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /subscriptions/{wallet} - Subscription accounts and entitlement for a wallet
+        </div>
+        <div class="endpoint">
+            <span class="method post">POST</span> 
+            /subscribe, /subscriptions/{address}/cancel|plan|retry-payment|resubscribe - Subscription transactions (admin)
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /subscriptions/quote - Plan price in USDC or another payment mint (?plan_tier=&period_days=&currency=)
+        </div>
+        <div class="endpoint">
+            <span class="method post">POST</span> 
+            /subscriptions/payment-transaction, /subscriptions/{address}/verify-payment - Pay for a plan in SPL tokens and verify the payment
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /admin/subscriptions/payments - Token payments (?user=&status=&limit=, admin)
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /pay/subscribe/{tier} (POST) - Solana Pay transaction request for a plan (?period_days=&currency=&reference=)
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /pay/subscribe/{tier}/link, /pay/checkouts/{reference} - Solana Pay checkout link and payment status
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /admin/subscriptions/history - Indexed program transactions (?user=&instruction=&plan_tier=&from=&to=&success=&limit=&offset=, admin)
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /auto-renew (PUT, DELETE) - Renew lapsed subscriptions of the signed-in wallet with retry_payment
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /ws?subscribe=subscriptions:&lt;wallet&gt; - Expiry reminders, lapses and renewals for the signed-in wallet
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /conditions (POST), /conditions/{id} (PUT, DELETE), /conditions/{id}/rearm|triggers - Price conditions: &gt;, &lt;, crosses_above, crosses_below, percent_change (admin)
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /conditions/{id}/actions (POST), /actions/{id} (PUT, DELETE), /actions/{id}/simulate - On-chain actions run when a condition fires (admin)
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /action-log - Simulated and sent actions (?action_id=&condition_id=&status=&limit=, admin)
+        </div>
+        <div class="endpoint">
+            <span class="method get">GET</span> 
+            /ws?subscribe=conditions:BTC - Price conditions firing and their actions
+        </div>
         
         <h3>🔧 Testing:</h3>
         <p>Test with curl:</p>
         <pre><code>curl http://localhost:8080/explain-signal
 curl http://localhost:8080/explain-signal?symbol=SOL
-curl http://localhost:8080/explain-all-signals</code></pre>
+curl -H "Authorization: Bearer &lt;session token&gt;" http://localhost:8080/explain-all-signals</code></pre>
     </div>
 </body>
 </html>
@@ -112,33 +223,144 @@ curl http://localhost:8080/explain-all-signals</code></pre>
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let port = std::env::var("PORT")
-        .unwrap_or_else(|_| "8080".to_string())
-        .parse::<u16>()
-        .expect("PORT must be a number");
+    dotenv::dotenv().ok();
+    let mut config = Config::from_env().expect("Invalid configuration");
+    let port = config.port;
     
-    println!("🚀 Trading Signals Backend starting on port {}", port);
-    println!("📊 Fetching LIVE prices from CoinGecko API");
-    println!("✅ Supported coins: BTC, ETH, SOL, PAXG");
+    println!("🚀 Trading Signals Backend starting on port {} ({})", port, config.environment);
+    symbols::init(config.symbols_file.as_deref()).expect("Invalid symbol registry");
+    println!("✅ Supported coins: {}", symbols::symbols().join(", "));
     println!("🤖 AI Explanations available at /explain-signal");
     
-    HttpServer::new(|| {
+    if config.price_stream_enabled {
+        println!("📡 Streaming live prices from Binance for {}", symbols::binance_pairs().join(", "));
+        market_data::stream::spawn(&config);
+    }
+    if config.candle_ingest_enabled {
+        println!("🕯️ Ingesting 1m, 5m, 1h and 1d candles for {}", symbols::binance_pairs().join(", "));
+        market_data::ingest::spawn(&config);
+    }
+    
+    let providers = web::Data::new(Providers::from_config(&config).expect("Invalid price provider"));
+    println!("💱 Prices from {}, history from {}", providers.prices.name(), providers.history.name());
+    
+    let db = web::Data::new(Database::open(&config.database_path).expect("Cannot open database"));
+    signal_routes::spawn_retention(config.clone(), db.clone());
+    println!("💾 Alerts stored in {}", config.database_path);
+    if config.webhook_secrets.is_empty() {
+        if config.webhook_allow_unauthenticated {
+            println!("⚠️ TRADINGVIEW_WEBHOOK_SECRETS is not set, /tradingview-webhook accepts unauthenticated alerts");
+        } else {
+            println!("⚠️ TRADINGVIEW_WEBHOOK_SECRETS is not set, /tradingview-webhook refuses every alert");
+        }
+    }
+    
+    signal_routes::spawn_signal_monitor(config.clone(), providers.clone(), db.clone());
+    
+    let solana = web::Data::new(SolanaClient::from_config(&config).expect("Invalid Solana configuration"));
+    match solana.as_ref() {
+        Some(client) => println!("⛓️ Solana wallet {} ({}) on {} (program {})",
+            client.wallet(), client.signer().source(), client.cluster, client.program_id),
+        None => println!("⚠️ SOLANA_KEYPAIR_PATH or SOLANA_WALLET_KEY is not set, subscription transactions are disabled"),
+    }
+    config.payment_treasury = config.payment_treasury.or_else(|| solana.as_ref().as_ref().map(SolanaClient::wallet));
+    
+    let subscription_reader = web::Data::new(SubscriptionReader::from_config(&config).expect("Invalid Solana configuration"));
+    blockchain::lifecycle::spawn(config.clone(), db.clone(), subscription_reader.clone(), solana.clone());
+    blockchain::indexer::spawn(&config, db.clone(), subscription_reader.clone());
+    conditions::spawn(db.clone(), solana.clone());
+    blockchain::payments::spawn(&config, db.clone(), subscription_reader.clone());
+    
+    let host = config.host.clone();
+    let config = web::Data::new(config);
+    
+    HttpServer::new(move || {
         App::new()
+            .app_data(config.clone())
+            .app_data(providers.clone())
+            .app_data(db.clone())
+            .app_data(solana.clone())
+            .app_data(subscription_reader.clone())
             .service(health)
             .service(index)
-            .service(signals::health_check)
-            .service(signals::get_prices)
-            .service(signals::get_signals)
-            .service(signals::get_tradingview_alerts)
-            .service(signals::get_symbol_alerts)
-            .service(signals::get_cache_stats)
-            .route("/explain-signal", web::get().to(signals::explain_signal))
-            .route("/explain-all-signals", web::get().to(signals::explain_all_signals))
-            .route("/tradingview-webhook", web::post().to(signals::tradingview_webhook))
-            .route("/clear-alerts", web::post().to(signals::clear_alerts))
-            .route("/clear-cache", web::post().to(signals::clear_cache))
+            .service(routes::candles::get_candles)
+            .route("/tradingview-webhook", web::post().to(signal_routes::tradingview_webhook))
+            .route("/ws", web::get().to(routes::ws::ws_handler))
+            .route("/admin/symbols", web::get().to(routes::admin::list_symbols))
+            .route("/admin/symbols", web::post().to(routes::admin::upsert_symbol))
+            .route("/admin/symbols/{symbol}", web::delete().to(routes::admin::delete_symbol))
+            .route("/admin/webhook-audit", web::get().to(routes::admin::webhook_audit))
+            .route("/admin/subscriptions/lifecycle", web::get().to(routes::subscription::lifecycle_records))
+            .route("/admin/subscriptions/lifecycle/scan", web::post().to(routes::subscription::scan_lifecycle))
+            .route("/admin/subscriptions/history", web::get().to(routes::subscription::history))
+            .route("/admin/subscriptions/history/sync", web::post().to(routes::subscription::sync_history))
+            .route("/admin/subscriptions/payments", web::get().to(routes::subscription::list_payments))
+            .route("/conditions", web::get().to(routes::conditions::list_conditions))
+            .route("/conditions", web::post().to(routes::conditions::create_condition))
+            .route("/conditions/{id}", web::get().to(routes::conditions::get_condition))
+            .route("/conditions/{id}", web::put().to(routes::conditions::update_condition))
+            .route("/conditions/{id}", web::delete().to(routes::conditions::delete_condition))
+            .route("/conditions/{id}/rearm", web::post().to(routes::conditions::rearm_condition))
+            .route("/conditions/{id}/triggers", web::get().to(routes::conditions::condition_triggers))
+            .route("/conditions/{id}/actions", web::get().to(routes::conditions::list_actions))
+            .route("/conditions/{id}/actions", web::post().to(routes::conditions::create_action))
+            .route("/actions/{id}", web::get().to(routes::conditions::get_action))
+            .route("/actions/{id}", web::put().to(routes::conditions::update_action))
+            .route("/actions/{id}", web::delete().to(routes::conditions::delete_action))
+            .route("/actions/{id}/simulate", web::post().to(routes::conditions::simulate_action))
+            .route("/action-log", web::get().to(routes::conditions::action_log))
+            .route("/auth/challenge", web::post().to(routes::auth::challenge))
+            .route("/auth/verify", web::post().to(routes::auth::verify))
+            .route("/auth/session", web::get().to(routes::auth::session))
+            .route("/auth/logout", web::post().to(routes::auth::logout))
+            .route("/auto-renew", web::get().to(routes::subscription::get_auto_renew))
+            .route("/auto-renew", web::put().to(routes::subscription::set_auto_renew))
+            .route("/auto-renew", web::delete().to(routes::subscription::delete_auto_renew))
+            .route("/subscription-status", web::get().to(routes::subscription::status))
+            .route("/blockchain-status", web::get().to(routes::minimal_blockchain::blockchain_status))
+            .route("/solana-health", web::get().to(routes::wallet_simple::solana_health))
+            .route("/wallet-info", web::get().to(routes::wallet_simple::wallet_info))
+            .route("/subscriptions/quote", web::get().to(routes::subscription::payment_quote))
+            .route("/subscriptions/payment-transaction", web::post().to(routes::subscription::payment_transaction))
+            .route("/subscriptions/{address}/verify-payment", web::post().to(routes::subscription::verify_payment))
+            .route("/subscriptions/{wallet}", web::get().to(routes::subscription::get_subscriptions))
+            .service(
+                // Wallets fetch transaction requests from other origins
+                web::scope("/pay")
+                    .wrap(middleware::from_fn(routes::pay::limit))
+                    .wrap(Cors::default().allow_any_origin().allowed_methods(["GET", "POST"]).allow_any_header())
+                    .route("/icon.svg", web::get().to(routes::pay::icon))
+                    .route("/subscribe/{tier}", web::get().to(routes::pay::checkout_metadata))
+                    .route("/subscribe/{tier}", web::post().to(routes::pay::checkout_transaction))
+                    .route("/subscribe/{tier}/link", web::get().to(routes::pay::checkout_link))
+                    .route("/checkouts/{reference}", web::get().to(routes::pay::checkout_status))
+            )
+            .route("/subscribe", web::post().to(routes::subscription::subscribe))
+            .route("/subscriptions/{address}/cancel", web::post().to(routes::subscription::cancel))
+            .route("/subscriptions/{address}/plan", web::post().to(routes::subscription::update_plan))
+            .route("/subscriptions/{address}/retry-payment", web::post().to(routes::subscription::retry_payment))
+            .route("/subscriptions/{address}/resubscribe", web::post().to(routes::subscription::manual_resubscribe))
+            // Everything in routes::signals runs under the caller's plan. The empty-prefix
+            // scope matches every path, so it has to stay last.
+            .service(
+                web::scope("")
+                    .wrap(middleware::from_fn(routes::plans::enforce))
+                    .service(signal_routes::health_check)
+                    .service(signal_routes::get_prices)
+                    .service(signal_routes::get_signals)
+                    .service(signal_routes::get_tradingview_alerts)
+                    .service(signal_routes::get_symbol_alerts)
+                    .service(signal_routes::get_cache_stats)
+                    .service(routes::sse::stream_signals)
+                    .service(routes::sse::stream_alerts)
+                    .route("/plans", web::get().to(routes::plans::list_plans))
+                    .route("/explain-signal", web::get().to(signal_routes::explain_signal))
+                    .route("/explain-all-signals", web::get().to(signal_routes::explain_all_signals))
+                    .route("/clear-alerts", web::post().to(signal_routes::clear_alerts))
+                    .route("/clear-cache", web::post().to(signal_routes::clear_cache))
+            )
     })
-    .bind(("0.0.0.0", port))?
+    .bind((host, port))?
     .run()
     .await
 }
@@ -0,0 +1,137 @@
+use super::{Candle, Interval};
+use crate::utils::http_client::HttpClient;
+use serde_json::Value;
+
+/// Binance REST market data (`/api/v3/klines`).
+pub struct BinanceMarketData {
+    client: HttpClient,
+}
+
+impl BinanceMarketData {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: HttpClient::new(base_url),
+        }
+    }
+
+    pub async fn fetch_klines(&self, pair: &str, interval: Interval, limit: u16) -> Result<Vec<Candle>, String> {
+        let limit = limit.to_string();
+        let params = vec![
+            ("symbol", pair),
+            ("interval", interval.as_str()),
+            ("limit", limit.as_str()),
+        ];
+
+        let data = self.client.get("/api/v3/klines", Some(params))
+            .await
+            .map_err(|e| format!("Binance klines error for {}: {}", pair, e))?;
+
+        data.as_array()
+            .ok_or("Klines response is not an array")?
+            .iter()
+            .map(parse_kline)
+            .collect()
+    }
+}
+
+/// Parses one kline row:
+/// `[open_time, "open", "high", "low", "close", "volume", close_time, "quote_volume", trades, ...]`
+fn parse_kline(row: &Value) -> Result<Candle, String> {
+    let fields = row.as_array().ok_or("Kline row is not an array")?;
+    if fields.len() < 9 {
+        return Err(format!("Kline row has {} fields, expected at least 9", fields.len()));
+    }
+
+    let int = |i: usize| -> Result<i64, String> {
+        fields[i].as_i64().ok_or_else(|| format!("Kline field {} is not an integer", i))
+    };
+    // Binance sends prices and volumes as decimal strings
+    let num = |i: usize| -> Result<f64, String> {
+        match &fields[i] {
+            Value::String(s) => s.parse::<f64>().map_err(|e| format!("Kline field {}: {}", i, e)),
+            other => other.as_f64().ok_or_else(|| format!("Kline field {} is not a number", i)),
+        }
+    };
+
+    Ok(Candle {
+        open_time: int(0)?,
+        open: num(1)?,
+        high: num(2)?,
+        low: num(3)?,
+        close: num(4)?,
+        volume: num(5)?,
+        close_time: int(6)?,
+        quote_volume: num(7)?,
+        trades: int(8)? as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn kline(open_time: i64, close: &str) -> Value {
+        json!([open_time, "100.0", "110.5", "95.25", close, "12.5", open_time + 59_999, "1250.0", 42, "6.0", "600.0", "0"])
+    }
+
+    async fn klines(query: web::Query<HashMap<String, String>>) -> HttpResponse {
+        if query.get("symbol").map(String::as_str) != Some("BTCUSDT") {
+            return HttpResponse::BadRequest().json(json!({"code": -1121, "msg": "Invalid symbol."}));
+        }
+        assert_eq!(query.get("interval").map(String::as_str), Some("1m"));
+        let limit: i64 = query.get("limit").unwrap().parse().unwrap();
+        let rows: Vec<Value> = (0..limit).map(|i| kline(i * 60_000, &format!("{}.5", 100 + i))).collect();
+        HttpResponse::Ok().json(rows)
+    }
+
+    fn start_mock_binance() -> String {
+        let server = HttpServer::new(|| App::new().route("/api/v3/klines", web::get().to(klines)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let addr = server.addrs()[0];
+        actix_rt::spawn(server.run());
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn parses_kline_row() {
+        let candle = parse_kline(&kline(1_700_000_000_000, "105.75")).unwrap();
+        assert_eq!(candle.open_time, 1_700_000_000_000);
+        assert_eq!(candle.open, 100.0);
+        assert_eq!(candle.high, 110.5);
+        assert_eq!(candle.low, 95.25);
+        assert_eq!(candle.close, 105.75);
+        assert_eq!(candle.volume, 12.5);
+        assert_eq!(candle.close_time, 1_700_000_059_999);
+        assert_eq!(candle.trades, 42);
+    }
+
+    #[test]
+    fn rejects_short_row() {
+        assert!(parse_kline(&json!([1, "2", "3"])).is_err());
+    }
+
+    #[actix_rt::test]
+    async fn fetches_klines_from_mock_server() {
+        let market_data = BinanceMarketData::new(&start_mock_binance());
+
+        let candles = market_data.fetch_klines("BTCUSDT", Interval::OneMinute, 3).await.unwrap();
+
+        assert_eq!(candles.len(), 3);
+        assert_eq!(super::super::closes(&candles), vec![100.5, 101.5, 102.5]);
+        assert_eq!(candles[2].open_time, 120_000);
+    }
+
+    #[actix_rt::test]
+    async fn surfaces_binance_errors() {
+        let market_data = BinanceMarketData::new(&start_mock_binance());
+
+        let err = market_data.fetch_klines("DOGEUSDT", Interval::OneMinute, 3).await.unwrap_err();
+
+        assert!(err.contains("400"), "{}", err);
+    }
+}
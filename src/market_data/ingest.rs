@@ -0,0 +1,105 @@
+use super::binance::BinanceMarketData;
+use super::{store_candles, Interval};
+use crate::config::Config;
+use crate::symbols;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Candles kept per pair and interval, enough for the slowest indicator with room to spare.
+const INGEST_LIMIT: u16 = 500;
+/// How often the ingestion loop checks for series due a refresh.
+const TICK: Duration = Duration::from_secs(5);
+
+/// Starts the background task that pulls klines for every registered Binance pair at
+/// every interval into the candle cache, refreshing each series before it goes stale.
+/// Pairs are read from the symbol registry on every tick, so edits apply right away.
+pub fn spawn(config: &Config) {
+    let base_url = config.binance_base_url.clone();
+
+    tokio::spawn(async move {
+        let mut last_attempt = HashMap::new();
+        let mut ticker = tokio::time::interval(TICK);
+        loop {
+            ticker.tick().await;
+            let pairs = symbols::binance_pairs();
+            last_attempt.retain(|(pair, _), _| pairs.contains(pair));
+            ingest_due(&base_url, &pairs, &mut last_attempt).await;
+        }
+    });
+}
+
+/// Fetches each pair and interval not attempted within its cache TTL (less one tick,
+/// so the cached series never lapses between ticks). Returns how many series were stored.
+async fn ingest_due(base_url: &str, pairs: &[String], last_attempt: &mut HashMap<(String, Interval), Instant>) -> usize {
+    let market_data = BinanceMarketData::new(base_url);
+    let mut stored = 0;
+
+    for pair in pairs {
+        for interval in Interval::ALL {
+            let key = (pair.clone(), interval);
+            if last_attempt.get(&key).is_some_and(|at| at.elapsed() + TICK < interval.cache_ttl()) {
+                continue;
+            }
+            last_attempt.insert(key, Instant::now());
+
+            match market_data.fetch_klines(pair, interval, INGEST_LIMIT).await {
+                Ok(candles) => {
+                    store_candles(pair, interval, candles);
+                    stored += 1;
+                },
+                Err(e) => println!("⚠️ Candle ingestion failed for {} {}: {}", pair, interval.as_str(), e),
+            }
+        }
+    }
+
+    stored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::cached_candles;
+    use super::*;
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use serde_json::{json, Value};
+
+    async fn klines(query: web::Query<HashMap<String, String>>) -> HttpResponse {
+        let limit: i64 = query.get("limit").unwrap().parse().unwrap();
+        let rows: Vec<Value> = (0..limit)
+            .map(|i| json!([i * 60_000, "1.0", "2.0", "0.5", "1.5", "10", i * 60_000 + 59_999, "15", 3]))
+            .collect();
+        HttpResponse::Ok().json(rows)
+    }
+
+    #[actix_rt::test]
+    async fn ingests_every_interval_and_skips_fresh_series() {
+        let server = HttpServer::new(|| App::new().route("/api/v3/klines", web::get().to(klines)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let base_url = format!("http://{}", server.addrs()[0]);
+        actix_rt::spawn(server.run());
+        let pairs = vec!["INGESTUSDT".to_string()];
+        let mut last_attempt = HashMap::new();
+
+        assert_eq!(ingest_due(&base_url, &pairs, &mut last_attempt).await, 4);
+        for interval in Interval::ALL {
+            let candles = cached_candles("INGESTUSDT", interval, INGEST_LIMIT).unwrap();
+            assert_eq!(candles.len(), INGEST_LIMIT as usize);
+        }
+
+        // Every series was just fetched, so the next tick has nothing to do
+        assert_eq!(ingest_due(&base_url, &pairs, &mut last_attempt).await, 0);
+
+        // 80 seconds later only the 1m and 5m series are due again
+        for attempt in last_attempt.values_mut() {
+            *attempt -= Duration::from_secs(80);
+        }
+        assert_eq!(ingest_due(&base_url, &pairs, &mut last_attempt).await, 2);
+    }
+
+    #[test]
+    fn refreshes_slower_intervals_less_often() {
+        let ttls: Vec<u64> = Interval::ALL.iter().map(|i| i.cache_ttl().as_secs()).collect();
+        assert_eq!(ttls, [15, 75, 900, 900]);
+    }
+}
@@ -0,0 +1,141 @@
+pub mod binance;
+pub mod ingest;
+pub mod price_cache;
+pub mod stream;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use binance::BinanceMarketData;
+
+type CandleCache = Arc<Mutex<HashMap<(String, Interval), (Vec<Candle>, SystemTime)>>>;
+
+static CANDLE_CACHE: OnceLock<CandleCache> = OnceLock::new();
+
+/// Kline intervals we ingest from Binance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Interval {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl Interval {
+    pub const ALL: [Interval; 4] = [
+        Interval::OneMinute,
+        Interval::FiveMinutes,
+        Interval::OneHour,
+        Interval::OneDay,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Interval::OneMinute => "1m",
+            Interval::FiveMinutes => "5m",
+            Interval::OneHour => "1h",
+            Interval::OneDay => "1d",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        Interval::ALL.iter()
+            .find(|i| i.as_str() == value)
+            .copied()
+            .ok_or_else(|| format!("Unsupported interval: {}. Use 1m, 5m, 1h or 1d.", value))
+    }
+
+    fn duration(&self) -> Duration {
+        match self {
+            Interval::OneMinute => Duration::from_secs(60),
+            Interval::FiveMinutes => Duration::from_secs(5 * 60),
+            Interval::OneHour => Duration::from_secs(60 * 60),
+            Interval::OneDay => Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    /// How long a fetched series is served from cache, and so how often it is ingested:
+    /// a quarter of the interval, at most 15 minutes so the open bar's close keeps moving.
+    fn cache_ttl(&self) -> Duration {
+        (self.duration() / 4).min(Duration::from_secs(15 * 60))
+    }
+}
+
+/// One OHLCV bar. Times are Unix milliseconds, as Binance reports them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub open_time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub close_time: i64,
+    pub quote_volume: f64,
+    pub trades: u64,
+}
+
+/// Closing prices, oldest first, ready for the indicator generators.
+pub fn closes(candles: &[Candle]) -> Vec<f64> {
+    candles.iter().map(|c| c.close).collect()
+}
+
+/// Resolves `BTC` or `BTCUSDT` to one of the given trading pairs.
+pub fn pair_for_symbol(symbol: &str, pairs: &[String]) -> Option<String> {
+    let symbol_upper = symbol.to_uppercase();
+    pairs.iter()
+        .find(|pair| **pair == symbol_upper || **pair == format!("{}USDT", symbol_upper))
+        .cloned()
+}
+
+/// Our symbol for a Binance pair, e.g. `BTCUSDT` -> `BTC`.
+pub fn symbol_for_pair(pair: &str) -> String {
+    let pair_upper = pair.to_uppercase();
+    pair_upper.strip_suffix("USDT")
+        .map(str::to_string)
+        .unwrap_or(pair_upper)
+}
+
+fn candle_cache() -> &'static CandleCache {
+    CANDLE_CACHE.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
+}
+
+/// The last `limit` cached candles of `pair`, if the series is fresh and long enough.
+fn cached_candles(pair: &str, interval: Interval, limit: u16) -> Option<Vec<Candle>> {
+    let cache = candle_cache().lock().unwrap();
+    let (candles, fetched_at) = cache.get(&(pair.to_string(), interval))?;
+    let fresh = SystemTime::now().duration_since(*fetched_at)
+        .map(|age| age < interval.cache_ttl())
+        .unwrap_or(false);
+    (fresh && candles.len() >= limit as usize).then(|| candles[candles.len() - limit as usize..].to_vec())
+}
+
+fn store_candles(pair: &str, interval: Interval, candles: Vec<Candle>) {
+    candle_cache().lock().unwrap().insert((pair.to_string(), interval), (candles, SystemTime::now()));
+}
+
+/// Candles for a configured pair, served from cache while fresh.
+pub async fn get_candles(
+    base_url: &str,
+    pair: &str,
+    interval: Interval,
+    limit: u16,
+) -> Result<Vec<Candle>, String> {
+    if let Some(candles) = cached_candles(pair, interval, limit) {
+        return Ok(candles);
+    }
+
+    let candles = BinanceMarketData::new(base_url)
+        .fetch_klines(pair, interval, limit)
+        .await?;
+
+    store_candles(pair, interval, candles.clone());
+
+    Ok(candles)
+}
@@ -0,0 +1,94 @@
+use crate::events;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+type PriceStore = Arc<Mutex<HashMap<String, (PriceData, SystemTime)>>>;
+
+static PRICE_CACHE: OnceLock<PriceStore> = OnceLock::new();
+
+#[derive(Debug, Serialize, Clone, Deserialize)]
+pub struct PriceData {
+    pub symbol: String,
+    pub price: f64,
+    pub timestamp: i64,
+    pub change_24h: f64,
+    pub market_cap: Option<f64>,
+    pub volume_24h: Option<f64>,
+    /// Providers whose quotes produced this price.
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Set when every provider failed and this is the last known price.
+    #[serde(default)]
+    pub stale: bool,
+}
+
+fn store() -> &'static PriceStore {
+    PRICE_CACHE.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
+}
+
+/// Cached price for `symbol` if it was updated less than `max_age` ago.
+pub fn get_fresh(symbol: &str, max_age: Duration) -> Option<PriceData> {
+    let cache = store().lock().unwrap();
+    let (data, updated_at) = cache.get(&symbol.to_uppercase())?;
+    let age = SystemTime::now().duration_since(*updated_at).unwrap_or(Duration::from_secs(0));
+    (age < max_age).then(|| data.clone())
+}
+
+/// Last known price for `symbol` regardless of age, flagged as stale.
+pub fn get_stale(symbol: &str) -> Option<PriceData> {
+    let cache = store().lock().unwrap();
+    let (data, _) = cache.get(&symbol.to_uppercase())?;
+    Some(PriceData { stale: true, ..data.clone() })
+}
+
+pub fn insert(data: PriceData) {
+    store().lock().unwrap().insert(data.symbol.to_uppercase(), (data.clone(), SystemTime::now()));
+    events::publish("prices", &data.symbol, "price", json!(data));
+}
+
+/// Applies a streamed price tick. 24h stats are only replaced when the stream has them;
+/// otherwise the last REST values are kept.
+pub fn apply_tick(symbol: &str, price: f64, timestamp: i64, change_24h: Option<f64>, volume_24h: Option<f64>) {
+    let symbol_upper = symbol.to_uppercase();
+    let mut cache = store().lock().unwrap();
+
+    let mut data = cache.get(&symbol_upper)
+        .map(|(data, _)| data.clone())
+        .unwrap_or_else(|| PriceData {
+            symbol: symbol_upper.clone(),
+            price,
+            timestamp,
+            change_24h: 0.0,
+            market_cap: None,
+            volume_24h: None,
+            sources: Vec::new(),
+            stale: false,
+        });
+
+    data.price = price;
+    data.timestamp = timestamp;
+    data.sources = vec!["binance-stream".to_string()];
+    data.stale = false;
+    if let Some(change) = change_24h {
+        data.change_24h = change;
+    }
+    if volume_24h.is_some() {
+        data.volume_24h = volume_24h;
+    }
+
+    cache.insert(symbol_upper, (data.clone(), SystemTime::now()));
+    drop(cache);
+
+    events::publish("prices", &data.symbol, "price", json!(data));
+}
+
+pub fn clear() {
+    store().lock().unwrap().clear();
+}
+
+pub fn symbols() -> Vec<String> {
+    store().lock().unwrap().keys().cloned().collect()
+}
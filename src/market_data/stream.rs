@@ -0,0 +1,330 @@
+use super::{price_cache, symbol_for_pair, Candle, Interval};
+use crate::config::Config;
+use crate::symbols;
+use chrono::Utc;
+use futures_util::StreamExt;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+static HEALTH: OnceLock<Arc<Mutex<StreamHealth>>> = OnceLock::new();
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Binance pushes trades several times a second; silence this long means a dead socket.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Number of hourly candles kept per pair to derive rolling 24h change and volume.
+const WINDOW_HOURS: usize = 24;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StreamHealth {
+    pub enabled: bool,
+    pub connected: bool,
+    pub streams: Vec<String>,
+    pub connected_since: Option<i64>,
+    pub last_message_at: Option<i64>,
+    pub messages_received: u64,
+    pub reconnects: u64,
+    pub last_error: Option<String>,
+}
+
+fn health_store() -> &'static Arc<Mutex<StreamHealth>> {
+    HEALTH.get_or_init(|| Arc::new(Mutex::new(StreamHealth::default())))
+}
+
+fn update_health(f: impl FnOnce(&mut StreamHealth)) {
+    f(&mut health_store().lock().unwrap());
+}
+
+pub fn health() -> StreamHealth {
+    health_store().lock().unwrap().clone()
+}
+
+/// Starts the background task that streams `@trade` and `@kline_1h` for every
+/// registered Binance pair into the price cache, reconnecting with exponential
+/// backoff and resubscribing whenever the symbol registry changes.
+pub fn spawn(config: &Config) {
+    let ws_url = config.binance_ws_url.clone();
+    let rest_url = config.binance_base_url.clone();
+
+    update_health(|h| h.enabled = true);
+
+    tokio::spawn(async move {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut windows = HashMap::new();
+        let mut changes = symbols::subscribe();
+
+        loop {
+            changes.borrow_and_update();
+            let pairs = symbols::binance_pairs();
+            let streams = stream_names(&pairs);
+            update_health(|h| h.streams = streams.clone());
+
+            if streams.is_empty() {
+                // Nothing to stream until a symbol with a Binance pair is registered
+                if changes.changed().await.is_err() {
+                    return;
+                }
+                continue;
+            }
+
+            windows.retain(|pair, _| pairs.contains(pair));
+            seed_windows(&rest_url, &pairs, &mut windows).await;
+            let url = format!("{}/stream?streams={}", ws_url, streams.join("/"));
+            let started = Instant::now();
+
+            let reason = tokio::select! {
+                result = run_stream(&url, &mut windows) => match result {
+                    Ok(()) => "stream closed".to_string(),
+                    Err(e) => e,
+                },
+                _ = changes.changed() => {
+                    println!("🔄 Symbol registry changed, resubscribing the Binance stream");
+                    update_health(|h| {
+                        h.connected = false;
+                        h.connected_since = None;
+                    });
+                    continue;
+                },
+            };
+
+            // A session that stayed up for a while resets the backoff
+            if started.elapsed() > MAX_BACKOFF {
+                backoff = INITIAL_BACKOFF;
+            }
+
+            println!("⚠️ Binance stream disconnected: {}. Reconnecting in {}s", reason, backoff.as_secs());
+            update_health(|h| {
+                h.connected = false;
+                h.connected_since = None;
+                h.reconnects += 1;
+                h.last_error = Some(reason);
+            });
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    });
+}
+
+fn stream_names(pairs: &[String]) -> Vec<String> {
+    pairs.iter()
+        .flat_map(|pair| {
+            let pair = pair.to_lowercase();
+            [format!("{}@trade", pair), format!("{}@kline_1h", pair)]
+        })
+        .collect()
+}
+
+/// Loads the last 24 hourly candles for pairs without a window yet, so 24h stats
+/// are available from the first tick.
+async fn seed_windows(rest_url: &str, pairs: &[String], windows: &mut HashMap<String, VecDeque<Candle>>) {
+    let missing: Vec<&String> = pairs.iter().filter(|p| !windows.contains_key(*p)).collect();
+
+    for pair in missing {
+        match super::get_candles(rest_url, pair, Interval::OneHour, WINDOW_HOURS as u16).await {
+            Ok(candles) => {
+                windows.insert(pair.clone(), candles.into_iter().collect());
+            },
+            Err(e) => println!("⚠️ Could not seed 24h window for {}: {}", pair, e),
+        }
+    }
+}
+
+async fn run_stream(url: &str, windows: &mut HashMap<String, VecDeque<Candle>>) -> Result<(), String> {
+    let (mut ws, _) = connect_async(url)
+        .await
+        .map_err(|e| format!("Connect failed: {}", e))?;
+
+    println!("📡 Binance price stream connected");
+    update_health(|h| {
+        h.connected = true;
+        h.connected_since = Some(Utc::now().timestamp());
+    });
+
+    loop {
+        let message = tokio::time::timeout(IDLE_TIMEOUT, ws.next())
+            .await
+            .map_err(|_| format!("No data for {}s", IDLE_TIMEOUT.as_secs()))?;
+
+        match message {
+            None => return Ok(()),
+            Some(Err(e)) => return Err(format!("WebSocket error: {}", e)),
+            Some(Ok(Message::Text(text))) => {
+                update_health(|h| {
+                    h.messages_received += 1;
+                    h.last_message_at = Some(Utc::now().timestamp());
+                });
+                if let Err(e) = handle_message(&text, windows) {
+                    println!("⚠️ Bad stream message: {}", e);
+                }
+            },
+            Some(Ok(Message::Close(frame))) => {
+                return Err(format!("Closed by server: {:?}", frame));
+            },
+            // Pings are answered by tungstenite on the next read
+            Some(Ok(_)) => {},
+        }
+    }
+}
+
+fn handle_message(text: &str, windows: &mut HashMap<String, VecDeque<Candle>>) -> Result<(), String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| format!("JSON error: {}", e))?;
+    // Combined streams wrap the payload as {"stream": ..., "data": ...}
+    let data = value.get("data").unwrap_or(&value);
+
+    let pair = data.get("s").and_then(|v| v.as_str()).ok_or("Missing symbol")?;
+
+    let (price, timestamp_ms) = match data.get("e").and_then(|v| v.as_str()) {
+        Some("trade") => (
+            decimal(data, "p")?,
+            data.get("T").and_then(|v| v.as_i64()).ok_or("Missing trade time")?,
+        ),
+        Some("kline") => {
+            let candle = parse_stream_kline(data.get("k").ok_or("Missing kline")?)?;
+            let result = (candle.close, candle.close_time.min(Utc::now().timestamp_millis()));
+            if let Some(window) = windows.get_mut(pair) {
+                push_candle(window, candle);
+            }
+            result
+        },
+        _ => return Ok(()),
+    };
+
+    let (change_24h, volume_24h) = match windows.get(pair) {
+        Some(window) if !window.is_empty() => {
+            let reference = window.front().unwrap().open;
+            let change = if reference > 0.0 { (price - reference) / reference * 100.0 } else { 0.0 };
+            (Some(change), Some(window.iter().map(|c| c.quote_volume).sum()))
+        },
+        _ => (None, None),
+    };
+
+    price_cache::apply_tick(&symbol_for_pair(pair), price, timestamp_ms / 1000, change_24h, volume_24h);
+
+    Ok(())
+}
+
+/// Replaces the in-progress candle or appends a new one, keeping the last 24 hours.
+fn push_candle(window: &mut VecDeque<Candle>, candle: Candle) {
+    match window.back_mut() {
+        Some(last) if last.open_time == candle.open_time => *last = candle,
+        _ => {
+            window.push_back(candle);
+            while window.len() > WINDOW_HOURS {
+                window.pop_front();
+            }
+        },
+    }
+}
+
+fn decimal(data: &Value, key: &str) -> Result<f64, String> {
+    data.get(key)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("Missing field {}", key))?
+        .parse::<f64>()
+        .map_err(|e| format!("Field {}: {}", key, e))
+}
+
+fn parse_stream_kline(k: &Value) -> Result<Candle, String> {
+    let int = |key: &str| k.get(key).and_then(|v| v.as_i64()).ok_or_else(|| format!("Missing field {}", key));
+
+    Ok(Candle {
+        open_time: int("t")?,
+        open: decimal(k, "o")?,
+        high: decimal(k, "h")?,
+        low: decimal(k, "l")?,
+        close: decimal(k, "c")?,
+        volume: decimal(k, "v")?,
+        close_time: int("T")?,
+        quote_volume: decimal(k, "q")?,
+        trades: int("n")? as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn kline(open_time: i64, open: &str, close: &str) -> Value {
+        json!({
+            "t": open_time, "T": open_time + 3_599_999, "s": "STREAMUSDT", "i": "1h",
+            "o": open, "c": close, "h": "110.0", "l": "90.0", "v": "5", "n": 42, "x": false, "q": "500.5",
+        })
+    }
+
+    fn candle(open_time: i64, close: f64) -> Candle {
+        parse_stream_kline(&kline(open_time, "100.0", &close.to_string())).unwrap()
+    }
+
+    #[test]
+    fn subscribes_to_trades_and_hourly_klines() {
+        assert_eq!(
+            stream_names(&["BTCUSDT".to_string(), "JUPUSDT".to_string()]),
+            ["btcusdt@trade", "btcusdt@kline_1h", "jupusdt@trade", "jupusdt@kline_1h"]
+        );
+    }
+
+    #[test]
+    fn parses_stream_klines() {
+        let candle = parse_stream_kline(&kline(3_600_000, "100.5", "101.25")).unwrap();
+        assert_eq!(candle, Candle {
+            open_time: 3_600_000,
+            open: 100.5,
+            high: 110.0,
+            low: 90.0,
+            close: 101.25,
+            volume: 5.0,
+            close_time: 7_199_999,
+            quote_volume: 500.5,
+            trades: 42,
+        });
+
+        let mut missing = kline(0, "1", "1");
+        missing.as_object_mut().unwrap().remove("q");
+        assert_eq!(parse_stream_kline(&missing).unwrap_err(), "Missing field q");
+        assert!(parse_stream_kline(&kline(0, "one", "1")).unwrap_err().starts_with("Field o:"));
+    }
+
+    #[test]
+    fn pushes_replace_the_open_candle_and_keep_a_day() {
+        let hour = 3_600_000;
+        let mut window: VecDeque<Candle> = (0..WINDOW_HOURS as i64).map(|h| candle(h * hour, 100.0)).collect();
+
+        push_candle(&mut window, candle(23 * hour, 105.0));
+        assert_eq!(window.len(), WINDOW_HOURS);
+        assert_eq!(window.back().unwrap().close, 105.0);
+
+        push_candle(&mut window, candle(24 * hour, 106.0));
+        assert_eq!(window.len(), WINDOW_HOURS);
+        assert_eq!((window.front().unwrap().open_time, window.back().unwrap().open_time), (hour, 24 * hour));
+    }
+
+    #[test]
+    fn handles_combined_stream_messages() {
+        let mut windows = HashMap::from([("STREAMUSDT".to_string(), VecDeque::from([candle(0, 100.0)]))]);
+
+        let trade = json!({"stream": "streamusdt@trade", "data": {"e": "trade", "s": "STREAMUSDT", "p": "110.0", "T": 1_700_000_000_000_i64}});
+        handle_message(&trade.to_string(), &mut windows).unwrap();
+        let price = price_cache::get_stale("STREAM").unwrap();
+        assert_eq!((price.price, price.timestamp), (110.0, 1_700_000_000));
+        // Against the window's first open, 100
+        assert!((price.change_24h - 10.0).abs() < 1e-9);
+        assert_eq!(price.volume_24h, Some(500.5));
+
+        let update = json!({"data": {"e": "kline", "s": "STREAMUSDT", "k": kline(0, "100.0", "90.0")}});
+        handle_message(&update.to_string(), &mut windows).unwrap();
+        assert_eq!(windows["STREAMUSDT"].len(), 1);
+        assert_eq!(windows["STREAMUSDT"][0].close, 90.0);
+        assert_eq!(price_cache::get_stale("STREAM").unwrap().price, 90.0);
+
+        // Other events are ignored, broken ones reported
+        handle_message(&json!({"e": "depthUpdate", "s": "STREAMUSDT"}).to_string(), &mut windows).unwrap();
+        assert_eq!(handle_message("{}", &mut windows).unwrap_err(), "Missing symbol");
+        assert!(handle_message("not json", &mut windows).unwrap_err().starts_with("JSON error"));
+    }
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+/// What one on-chain `plan_tier` unlocks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanTier {
+    pub tier: u8,
+    pub name: String,
+    /// Symbols the plan may query. Empty means every registered symbol.
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    /// `/signals` entries older than this are recomputed; younger ones are served as is.
+    pub signal_refresh_seconds: u64,
+    pub requests_per_minute: u32,
+    /// Access to `/explain-signal` and `/explain-all-signals`.
+    pub ai_explanations: bool,
+    /// Channels a `/ws` connection may subscribe to at once.
+    pub alert_channels: usize,
+    /// Price of 30 days, quoted in the configured payment mints. Zero is not for sale.
+    #[serde(default)]
+    pub monthly_price_usd: f64,
+}
+
+impl PlanTier {
+    pub fn allows_symbol(&self, symbol: &str) -> bool {
+        self.symbols.is_empty() || self.symbols.iter().any(|s| s.eq_ignore_ascii_case(symbol))
+    }
+}
+
+/// Built-in catalogue: anonymous callers and wallets without a subscription get tier 0.
+pub fn default_catalogue() -> Vec<PlanTier> {
+    vec![
+        PlanTier {
+            tier: 0,
+            name: "free".to_string(),
+            symbols: vec!["BTC".to_string(), "ETH".to_string()],
+            signal_refresh_seconds: 300,
+            requests_per_minute: 30,
+            ai_explanations: false,
+            alert_channels: 2,
+            monthly_price_usd: 0.0,
+        },
+        PlanTier {
+            tier: 1,
+            name: "pro".to_string(),
+            symbols: Vec::new(),
+            signal_refresh_seconds: 60,
+            requests_per_minute: 120,
+            ai_explanations: true,
+            alert_channels: 10,
+            monthly_price_usd: 29.0,
+        },
+        PlanTier {
+            tier: 2,
+            name: "elite".to_string(),
+            symbols: Vec::new(),
+            signal_refresh_seconds: 15,
+            requests_per_minute: 600,
+            ai_explanations: true,
+            alert_channels: 50,
+            monthly_price_usd: 99.0,
+        },
+    ]
+}
+
+/// Parses a JSON array of `PlanTier`s, sorted by tier. Tier 0 is required since it
+/// applies to everyone without a subscription.
+pub fn parse_catalogue(json: &str) -> Result<Vec<PlanTier>, String> {
+    let mut tiers: Vec<PlanTier> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    tiers.sort_by_key(|t| t.tier);
+    if tiers.first().map(|t| t.tier) != Some(0) {
+        return Err("the catalogue must define tier 0".to_string());
+    }
+    Ok(tiers)
+}
+
+/// The highest catalogue entry at or below `tier`.
+pub fn for_tier(catalogue: &[PlanTier], tier: u8) -> &PlanTier {
+    catalogue.iter()
+        .rev()
+        .find(|t| t.tier <= tier)
+        .unwrap_or(&catalogue[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiers(catalogue: &[PlanTier]) -> Vec<u8> {
+        catalogue.iter().map(|t| t.tier).collect()
+    }
+
+    #[test]
+    fn parses_and_sorts_the_catalogue() {
+        let json = r#"[
+            {"tier": 3, "name": "whale", "signal_refresh_seconds": 5, "requests_per_minute": 1000, "ai_explanations": true, "alert_channels": 100, "monthly_price_usd": 299.0},
+            {"tier": 0, "name": "free", "symbols": ["BTC"], "signal_refresh_seconds": 300, "requests_per_minute": 10, "ai_explanations": false, "alert_channels": 1}
+        ]"#;
+        let catalogue = parse_catalogue(json).unwrap();
+
+        assert_eq!(tiers(&catalogue), [0, 3]);
+        assert_eq!(catalogue[0].monthly_price_usd, 0.0);
+        assert!(catalogue[0].allows_symbol("btc") && !catalogue[0].allows_symbol("ETH"));
+        assert!(catalogue[1].allows_symbol("ANY"));
+
+        let without_free = r#"[{"tier": 1, "name": "pro", "signal_refresh_seconds": 60, "requests_per_minute": 120, "ai_explanations": true, "alert_channels": 10}]"#;
+        assert_eq!(parse_catalogue(without_free).unwrap_err(), "the catalogue must define tier 0");
+        assert!(parse_catalogue("[]").is_err());
+        assert!(parse_catalogue(r#"[{"tier": 0}]"#).unwrap_err().contains("missing field"));
+    }
+
+    #[test]
+    fn falls_back_to_the_highest_tier_below() {
+        let catalogue: Vec<PlanTier> = default_catalogue().into_iter().filter(|t| t.tier != 1).collect();
+
+        assert_eq!(for_tier(&catalogue, 0).name, "free");
+        assert_eq!(for_tier(&catalogue, 1).name, "free");
+        assert_eq!(for_tier(&catalogue, 2).name, "elite");
+        assert_eq!(for_tier(&catalogue, 200).name, "elite");
+    }
+}
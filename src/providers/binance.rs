@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::Value;
+
+use super::PriceProvider;
+use crate::market_data::price_cache::PriceData;
+use crate::market_data::{self, Interval};
+use crate::symbols;
+use crate::utils::http_client::HttpClient;
+
+/// Binance REST `/api/v3/ticker/24hr` for prices and hourly klines for history.
+pub struct BinanceProvider {
+    client: HttpClient,
+    base_url: String,
+}
+
+impl BinanceProvider {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: HttpClient::new(base_url),
+            base_url: base_url.to_string(),
+        }
+    }
+}
+
+fn usdt_pair(symbol: &str) -> String {
+    symbols::get(symbol)
+        .and_then(|info| info.binance_pair)
+        .unwrap_or_else(|| format!("{}USDT", symbol.to_uppercase()))
+}
+
+fn decimal_field(data: &Value, key: &str) -> Option<f64> {
+    data.get(key).and_then(|v| v.as_str()).and_then(|s| s.parse().ok())
+}
+
+#[async_trait]
+impl PriceProvider for BinanceProvider {
+    fn name(&self) -> &'static str {
+        "binance"
+    }
+
+    async fn fetch_price(&self, symbol: &str) -> Result<PriceData, String> {
+        let pair = usdt_pair(symbol);
+        let data = self.client.get("/api/v3/ticker/24hr", Some(vec![("symbol", pair.as_str())]))
+            .await
+            .map_err(|e| format!("Binance error for {}: {}", pair, e))?;
+
+        let price = decimal_field(&data, "lastPrice").ok_or("No lastPrice in response")?;
+
+        Ok(PriceData {
+            symbol: symbol.to_uppercase(),
+            price,
+            timestamp: Utc::now().timestamp(),
+            change_24h: decimal_field(&data, "priceChangePercent").unwrap_or(0.0),
+            market_cap: None,
+            volume_24h: decimal_field(&data, "quoteVolume"),
+            sources: vec![self.name().to_string()],
+            stale: false,
+        })
+    }
+
+    async fn fetch_history(&self, symbol: &str) -> Result<Vec<f64>, String> {
+        let candles = market_data::get_candles(&self.base_url, &usdt_pair(symbol), Interval::OneHour, 200).await?;
+        Ok(market_data::closes(&candles))
+    }
+}
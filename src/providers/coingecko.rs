@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use super::PriceProvider;
+use crate::market_data::price_cache::PriceData;
+use crate::symbols;
+use crate::utils::http_client::HttpClient;
+
+/// CoinGecko `/simple/price` and `/coins/{id}/market_chart`.
+pub struct CoinGeckoProvider {
+    client: HttpClient,
+}
+
+impl CoinGeckoProvider {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: HttpClient::new(base_url),
+        }
+    }
+}
+
+fn get_coingecko_id(symbol: &str) -> Option<String> {
+    symbols::get(symbol).and_then(|info| info.coingecko_id)
+}
+
+#[async_trait]
+impl PriceProvider for CoinGeckoProvider {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    async fn fetch_price(&self, symbol: &str) -> Result<PriceData, String> {
+        let symbol_upper = symbol.to_uppercase();
+        let coin_id = get_coingecko_id(&symbol_upper)
+            .ok_or_else(|| format!("Unknown symbol: {}", symbol))?;
+
+        let params = vec![
+            ("ids", coin_id.as_str()),
+            ("vs_currencies", "usd"),
+            ("include_24hr_change", "true"),
+            ("include_market_cap", "true"),
+            ("include_24hr_vol", "true"),
+        ];
+        let data = self.client.get("/simple/price", Some(params))
+            .await
+            .map_err(|e| format!("CoinGecko error: {}", e))?;
+
+        let coin_data = data.get(&coin_id)
+            .ok_or_else(|| format!("No data for {}", symbol))?;
+
+        let price = coin_data.get("usd")
+            .and_then(|v| v.as_f64())
+            .ok_or("No price in response")?;
+
+        Ok(PriceData {
+            symbol: symbol_upper,
+            price,
+            timestamp: Utc::now().timestamp(),
+            change_24h: coin_data.get("usd_24h_change").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            market_cap: coin_data.get("usd_market_cap").and_then(|v| v.as_f64()),
+            volume_24h: coin_data.get("usd_24h_vol").and_then(|v| v.as_f64()),
+            sources: vec![self.name().to_string()],
+            stale: false,
+        })
+    }
+
+    async fn fetch_history(&self, symbol: &str) -> Result<Vec<f64>, String> {
+        let coin_id = get_coingecko_id(symbol)
+            .ok_or_else(|| format!("Unknown symbol: {}", symbol))?;
+
+        // 2-90 day ranges come back at hourly granularity
+        let endpoint = format!("/coins/{}/market_chart", coin_id);
+        let data = self.client.get(&endpoint, Some(vec![("vs_currency", "usd"), ("days", "7")]))
+            .await
+            .map_err(|e| format!("CoinGecko error: {}", e))?;
+
+        let history = data.get("prices")
+            .and_then(|v| v.as_array())
+            .ok_or("No price history in response")?
+            .iter()
+            .filter_map(|point| point.get(1).and_then(|p| p.as_f64()))
+            .collect();
+
+        Ok(history)
+    }
+}
@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use std::sync::Arc;
+
+use super::PriceProvider;
+use crate::market_data::price_cache::PriceData;
+
+/// Queries several providers at once and reconciles their quotes.
+///
+/// Prices are the median of all successful quotes after dropping those further
+/// than `max_deviation` from the first-pass median. History is taken from the
+/// first provider, in configured order, that returns any.
+pub struct ConsensusProvider {
+    providers: Vec<Arc<dyn PriceProvider>>,
+    max_deviation: f64,
+}
+
+impl ConsensusProvider {
+    pub fn new(providers: Vec<Arc<dyn PriceProvider>>, max_deviation: f64) -> Self {
+        Self { providers, max_deviation }
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[async_trait]
+impl PriceProvider for ConsensusProvider {
+    fn name(&self) -> &'static str {
+        "consensus"
+    }
+
+    async fn fetch_price(&self, symbol: &str) -> Result<PriceData, String> {
+        let results = join_all(self.providers.iter().map(|p| p.fetch_price(symbol))).await;
+
+        let mut quotes = Vec::new();
+        let mut errors = Vec::new();
+        for (provider, result) in self.providers.iter().zip(results) {
+            match result {
+                Ok(quote) => quotes.push(quote),
+                Err(e) => {
+                    println!("⚠️ {} failed for {}: {}", provider.name(), symbol, e);
+                    errors.push(format!("{}: {}", provider.name(), e));
+                },
+            }
+        }
+
+        if quotes.is_empty() {
+            return Err(format!("All price providers failed ({})", errors.join("; ")));
+        }
+
+        let first_pass = median(&mut quotes.iter().map(|q| q.price).collect::<Vec<_>>());
+        let (kept, rejected): (Vec<PriceData>, Vec<PriceData>) = quotes.into_iter()
+            .partition(|q| ((q.price - first_pass) / first_pass).abs() <= self.max_deviation);
+
+        for outlier in &rejected {
+            println!("⚠️ Rejected {} quote for {}: ${:.2} vs median ${:.2}",
+                outlier.sources.join(","), symbol, outlier.price, first_pass);
+        }
+
+        // With only two quotes that disagree neither is an outlier of the other;
+        // the median still splits the difference
+        let kept = if kept.is_empty() { rejected } else { kept };
+
+        Ok(PriceData {
+            symbol: symbol.to_uppercase(),
+            price: median(&mut kept.iter().map(|q| q.price).collect::<Vec<_>>()),
+            timestamp: kept.iter().map(|q| q.timestamp).max().unwrap_or_default(),
+            change_24h: median(&mut kept.iter().map(|q| q.change_24h).collect::<Vec<_>>()),
+            market_cap: kept.iter().find_map(|q| q.market_cap),
+            volume_24h: kept.iter().find_map(|q| q.volume_24h),
+            sources: kept.iter().flat_map(|q| q.sources.clone()).collect(),
+            stale: false,
+        })
+    }
+
+    async fn fetch_history(&self, symbol: &str) -> Result<Vec<f64>, String> {
+        let mut errors = Vec::new();
+
+        for provider in &self.providers {
+            match provider.fetch_history(symbol).await {
+                Ok(history) if !history.is_empty() => return Ok(history),
+                Ok(_) => errors.push(format!("{}: empty history", provider.name())),
+                Err(e) => errors.push(format!("{}: {}", provider.name(), e)),
+            }
+        }
+
+        Err(format!("No history provider succeeded ({})", errors.join("; ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::mock::{MockAsset, MockProvider};
+    use std::collections::HashMap;
+
+    fn quoting(price: f64, change_24h: f64) -> Arc<dyn PriceProvider> {
+        Arc::new(MockProvider::new(HashMap::from([("BTC".to_string(), MockAsset {
+            price,
+            change_24h,
+            market_cap: None,
+            volume_24h: None,
+            history: vec![price],
+        })])))
+    }
+
+    #[test]
+    fn takes_the_middle_of_sorted_values() {
+        assert_eq!(median(&mut [3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&mut [4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert_eq!(median(&mut [-1.0]), -1.0);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_quotes_far_from_the_median() {
+        let consensus = ConsensusProvider::new(vec![
+            quoting(100.0, 1.0),
+            quoting(101.0, 2.0),
+            quoting(150.0, 9.0),
+            quoting(99.0, 3.0),
+        ], 0.05);
+
+        let price = consensus.fetch_price("btc").await.unwrap();
+        assert_eq!(price.symbol, "BTC");
+        assert_eq!(price.price, 100.0);
+        assert_eq!(price.change_24h, 2.0);
+        assert_eq!(price.sources.len(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn keeps_both_of_two_disagreeing_quotes() {
+        let consensus = ConsensusProvider::new(vec![quoting(100.0, 0.0), quoting(200.0, 0.0)], 0.05);
+
+        let price = consensus.fetch_price("BTC").await.unwrap();
+        assert_eq!(price.price, 150.0);
+        assert_eq!(price.sources.len(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn skips_failed_providers() {
+        let consensus = ConsensusProvider::new(vec![quoting(100.0, 0.0), Arc::new(MockProvider::new(HashMap::new()))], 0.05);
+        assert_eq!(consensus.fetch_price("BTC").await.unwrap().price, 100.0);
+        assert_eq!(consensus.fetch_history("BTC").await.unwrap(), vec![100.0]);
+
+        let err = consensus.fetch_price("ETH").await.unwrap_err();
+        assert!(err.starts_with("All price providers failed (mock: Unknown symbol: ETH"), "{}", err);
+    }
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::Value;
+
+use super::PriceProvider;
+use crate::market_data::price_cache::PriceData;
+use crate::symbols;
+use crate::utils::http_client::HttpClient;
+
+/// Hourly points returned from the OHLC endpoint (it serves up to 720).
+const HISTORY_LEN: usize = 200;
+
+/// Kraken public REST `/0/public/Ticker` and `/0/public/OHLC`.
+pub struct KrakenProvider {
+    client: HttpClient,
+}
+
+impl KrakenProvider {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: HttpClient::new(base_url),
+        }
+    }
+
+    /// Calls a public endpoint and returns the single pair entry of `result`.
+    /// Kraken reports failures in an `error` array with a 200 status.
+    async fn pair_result(&self, endpoint: &str, params: Vec<(&str, &str)>) -> Result<Value, String> {
+        let data = self.client.get(endpoint, Some(params))
+            .await
+            .map_err(|e| format!("Kraken error: {}", e))?;
+
+        if let Some(errors) = data.get("error").and_then(|v| v.as_array()) {
+            if !errors.is_empty() {
+                return Err(format!("Kraken error: {}", Value::Array(errors.clone())));
+            }
+        }
+
+        // Result is keyed by Kraken's own pair name (e.g. XXBTZUSD), plus "last" for OHLC
+        data.get("result")
+            .and_then(|v| v.as_object())
+            .and_then(|result| result.iter().find(|(key, _)| key.as_str() != "last"))
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| "Empty Kraken result".to_string())
+    }
+}
+
+fn kraken_pair(symbol: &str) -> String {
+    symbols::get(symbol)
+        .and_then(|info| info.kraken_pair)
+        .unwrap_or_else(|| format!("{}USD", symbol.to_uppercase()))
+}
+
+fn decimal(value: Option<&Value>) -> Option<f64> {
+    value.and_then(|v| v.as_str()).and_then(|s| s.parse().ok())
+}
+
+#[async_trait]
+impl PriceProvider for KrakenProvider {
+    fn name(&self) -> &'static str {
+        "kraken"
+    }
+
+    async fn fetch_price(&self, symbol: &str) -> Result<PriceData, String> {
+        let pair = kraken_pair(symbol);
+        let ticker = self.pair_result("/0/public/Ticker", vec![("pair", pair.as_str())]).await?;
+
+        let price = decimal(ticker.get("c").and_then(|c| c.get(0)))
+            .ok_or("No last trade price in response")?;
+        // Kraken has no rolling 24h change; use the move since today's open
+        let change_24h = decimal(ticker.get("o"))
+            .filter(|open| *open > 0.0)
+            .map(|open| (price - open) / open * 100.0)
+            .unwrap_or(0.0);
+        let volume_24h = decimal(ticker.get("v").and_then(|v| v.get(1)))
+            .zip(decimal(ticker.get("p").and_then(|p| p.get(1))))
+            .map(|(volume, vwap)| volume * vwap);
+
+        Ok(PriceData {
+            symbol: symbol.to_uppercase(),
+            price,
+            timestamp: Utc::now().timestamp(),
+            change_24h,
+            market_cap: None,
+            volume_24h,
+            sources: vec![self.name().to_string()],
+            stale: false,
+        })
+    }
+
+    async fn fetch_history(&self, symbol: &str) -> Result<Vec<f64>, String> {
+        let pair = kraken_pair(symbol);
+        let rows = self.pair_result("/0/public/OHLC", vec![("pair", pair.as_str()), ("interval", "60")]).await?;
+
+        // [time, open, high, low, close, vwap, volume, count]
+        let closes: Vec<f64> = rows.as_array()
+            .ok_or("OHLC result is not an array")?
+            .iter()
+            .filter_map(|row| decimal(row.get(4)))
+            .collect();
+
+        Ok(closes[closes.len().saturating_sub(HISTORY_LEN)..].to_vec())
+    }
+}
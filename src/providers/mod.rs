@@ -0,0 +1,74 @@
+pub mod binance;
+pub mod coingecko;
+pub mod consensus;
+pub mod kraken;
+pub mod mock;
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::market_data::price_cache::PriceData;
+
+/// A source of spot prices and hourly price history.
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn fetch_price(&self, symbol: &str) -> Result<PriceData, String>;
+
+    /// Hourly closing prices, oldest first.
+    async fn fetch_history(&self, symbol: &str) -> Result<Vec<f64>, String>;
+}
+
+/// The providers the routes read from, selected by `Config::price_providers`
+/// and `Config::history_providers`.
+pub struct Providers {
+    pub prices: Arc<dyn PriceProvider>,
+    pub history: Arc<dyn PriceProvider>,
+}
+
+impl Providers {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        Ok(Self {
+            prices: build_all(&config.price_providers, config)?,
+            history: build_all(&config.history_providers, config)?,
+        })
+    }
+
+    /// The built-in mock prices and history, so routes run offline in tests.
+    #[cfg(test)]
+    pub fn fixture() -> Self {
+        let mock: Arc<dyn PriceProvider> = Arc::new(mock::MockProvider::with_defaults());
+        Self {
+            prices: mock.clone(),
+            history: mock,
+        }
+    }
+}
+
+/// A single provider, or a consensus over several.
+pub fn build_all(names: &[String], config: &Config) -> Result<Arc<dyn PriceProvider>, String> {
+    let mut providers = names.iter()
+        .map(|name| build(name, config))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    match providers.len() {
+        0 => Err("No price provider configured".to_string()),
+        1 => Ok(providers.remove(0)),
+        _ => Ok(Arc::new(consensus::ConsensusProvider::new(providers, config.max_price_deviation))),
+    }
+}
+
+pub fn build(name: &str, config: &Config) -> Result<Arc<dyn PriceProvider>, String> {
+    match name {
+        "coingecko" => Ok(Arc::new(coingecko::CoinGeckoProvider::new(&config.coingecko_base_url))),
+        "binance" => Ok(Arc::new(binance::BinanceProvider::new(&config.binance_base_url))),
+        "kraken" => Ok(Arc::new(kraken::KrakenProvider::new(&config.kraken_base_url))),
+        "mock" => match &config.mock_prices_file {
+            Some(path) => Ok(Arc::new(mock::MockProvider::from_file(path)?)),
+            None => Ok(Arc::new(mock::MockProvider::with_defaults())),
+        },
+        other => Err(format!("Unknown price provider: {}. Use coingecko, binance, kraken or mock.", other)),
+    }
+}
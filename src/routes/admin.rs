@@ -0,0 +1,192 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::webhook_auth::constant_time_eq;
+use crate::config::Config;
+use crate::storage::Database;
+use crate::symbols::{self, SymbolInfo};
+
+/// Checks the `X-Admin-Key` header against `Config::admin_api_key`, returning the
+/// error response when the caller is not an admin. Admin routes are disabled
+/// entirely when no key is configured.
+pub fn reject_non_admin(req: &HttpRequest, config: &Config) -> Option<HttpResponse> {
+    let Some(expected) = config.admin_api_key.as_deref() else {
+        return Some(HttpResponse::ServiceUnavailable().json(json!({
+            "error": "Admin API disabled",
+            "message": "Set ADMIN_API_KEY to enable admin endpoints",
+        })));
+    };
+
+    let provided = req.headers().get("X-Admin-Key").and_then(|v| v.to_str().ok());
+    if !provided.is_some_and(|key| constant_time_eq(key, expected)) {
+        return Some(HttpResponse::Unauthorized().json(json!({
+            "error": "Unauthorized",
+            "message": "Missing or invalid X-Admin-Key header",
+        })));
+    }
+
+    None
+}
+
+// ========== SYMBOL REGISTRY ==========
+pub async fn list_symbols(req: HttpRequest, config: web::Data<Config>) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    let symbols = symbols::all();
+    HttpResponse::Ok().json(json!({
+        "symbols": symbols,
+        "count": symbols.len(),
+        "timestamp": Utc::now().timestamp()
+    }))
+}
+
+pub async fn upsert_symbol(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    info: web::Json<SymbolInfo>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match symbols::upsert(info.into_inner()) {
+        Ok(stored) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "symbol": stored,
+            "timestamp": Utc::now().timestamp()
+        })),
+        Err(e) => HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": e,
+        })),
+    }
+}
+
+pub async fn delete_symbol(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    symbol: web::Path<String>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    let symbol = symbol.into_inner().to_uppercase();
+    match symbols::remove(&symbol) {
+        Ok(true) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "message": format!("Removed {}", symbol),
+            "timestamp": Utc::now().timestamp()
+        })),
+        Ok(false) => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": format!("Unknown symbol: {}", symbol),
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "status": "error",
+            "message": e,
+        })),
+    }
+}
+
+// ========== WEBHOOK AUDIT LOG ==========
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    pub limit: Option<u32>,
+}
+
+pub async fn webhook_audit(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    query: web::Query<AuditQuery>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match db.recent_audit_entries(query.limit.unwrap_or(100).clamp(1, 1000)) {
+        Ok(entries) => HttpResponse::Ok().json(json!({
+            "entries": entries,
+            "count": entries.len(),
+            "timestamp": Utc::now().timestamp()
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "status": "error",
+            "message": e,
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+
+    fn config(admin_api_key: Option<&str>) -> Config {
+        let mut config = Config::from_env().unwrap();
+        config.admin_api_key = admin_api_key.map(str::to_string);
+        config
+    }
+
+    #[actix_rt::test]
+    async fn symbol_routes_require_the_admin_key() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config(Some("admin-secret"))))
+                .route("/admin/symbols", web::get().to(list_symbols))
+                .route("/admin/symbols", web::post().to(upsert_symbol))
+                .route("/admin/symbols/{symbol}", web::delete().to(delete_symbol)),
+        ).await;
+        let admin = |req: test::TestRequest| req.insert_header(("X-Admin-Key", "admin-secret")).to_request();
+
+        let anonymous = test::call_service(&app, test::TestRequest::get().uri("/admin/symbols").to_request()).await;
+        assert_eq!(anonymous.status(), 401);
+        let wrong = test::TestRequest::get().uri("/admin/symbols").insert_header(("X-Admin-Key", "admin-secre")).to_request();
+        assert_eq!(test::call_service(&app, wrong).await.status(), 401);
+
+        let added: serde_json::Value = test::call_and_read_body_json(&app, admin(test::TestRequest::post()
+            .uri("/admin/symbols")
+            .set_json(json!({ "symbol": "admintest", "coingecko_id": "admin-test", "binance_pair": "admintestusdt" })))).await;
+        assert_eq!(added["symbol"]["symbol"], "ADMINTEST");
+        assert_eq!(added["symbol"]["binance_pair"], "ADMINTESTUSDT");
+
+        let invalid = test::call_service(&app, admin(test::TestRequest::post()
+            .uri("/admin/symbols")
+            .set_json(json!({ "symbol": "no/slash" })))).await;
+        assert_eq!(invalid.status(), 400);
+
+        let listed: serde_json::Value = test::call_and_read_body_json(&app, admin(test::TestRequest::get().uri("/admin/symbols"))).await;
+        assert!(listed["symbols"].as_array().unwrap().iter().any(|s| s["symbol"] == "ADMINTEST"));
+
+        let removed = test::call_service(&app, admin(test::TestRequest::delete().uri("/admin/symbols/admintest"))).await;
+        assert_eq!(removed.status(), 200);
+        let missing = test::call_service(&app, admin(test::TestRequest::delete().uri("/admin/symbols/admintest"))).await;
+        assert_eq!(missing.status(), 404);
+    }
+
+    #[actix_rt::test]
+    async fn admin_routes_are_disabled_without_a_key() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config(None)))
+                .app_data(web::Data::new(Database::in_memory().unwrap()))
+                .route("/admin/webhook-audit", web::get().to(webhook_audit)),
+        ).await;
+
+        let req = test::TestRequest::get().uri("/admin/webhook-audit").insert_header(("X-Admin-Key", "anything")).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 503);
+    }
+
+    #[actix_rt::test]
+    async fn compares_keys_of_any_length() {
+        assert!(constant_time_eq("admin-secret", "admin-secret"));
+        assert!(!constant_time_eq("admin-secret", "admin-secreT"));
+        assert!(!constant_time_eq("admin", "admin-secret"));
+        assert!(!constant_time_eq("", "admin-secret"));
+    }
+}
@@ -10,17 +10,16 @@ pub struct SignalExplanation {
     pub vibe: String,
     pub simple_advice: String,
     pub risk_level: String,
+    /// Indicators and TradingView alerts that contributed to the signal.
+    #[serde(default)]
+    pub sources: Vec<String>,
 }
 
-pub struct AIExplainer {
-    api_key: String,
-}
+pub struct AIExplainer;
 
 impl AIExplainer {
     pub fn new() -> Self {
-        Self {
-            api_key: std::env::var("OPENAI_API_KEY").unwrap_or_default(),
-        }
+        Self
     }
 
     pub async fn explain_signal(
@@ -78,6 +77,7 @@ impl AIExplainer {
             vibe: vibe.to_string(),
             simple_advice: simple_advice.to_string(),
             risk_level: risk_level.to_string(),
+            sources: Vec::new(),
         }
     }
 }
@@ -0,0 +1,369 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::{TimeZone, Utc};
+use rand::Rng;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::plans::{check_rate_limit, client_ip};
+use crate::config::Config;
+use crate::storage::sessions::Session;
+use crate::storage::Database;
+
+/// Outstanding sign-in challenges.
+static CHALLENGES: OnceLock<Arc<Mutex<ChallengeStore>>> = OnceLock::new();
+
+/// How long a client has to sign a challenge.
+const CHALLENGE_TTL_SECONDS: i64 = 300;
+/// Outstanding challenges kept at most; the oldest are dropped beyond this.
+const MAX_CHALLENGES: usize = 10_000;
+
+#[derive(Debug, Clone)]
+struct Challenge {
+    wallet: Pubkey,
+    message: String,
+    expires_at: i64,
+}
+
+/// Challenges by nonce, plus their nonces in expiry order. Every challenge lives for
+/// `CHALLENGE_TTL_SECONDS`, so insertion order is expiry order and the oldest is always
+/// at the front of `by_expiry`.
+#[derive(Default)]
+struct ChallengeStore {
+    pending: HashMap<String, Challenge>,
+    /// May still hold nonces already taken; they are skipped when they reach the front.
+    by_expiry: VecDeque<(i64, String)>,
+}
+
+impl ChallengeStore {
+    /// Drops expired challenges, and the oldest ones beyond `MAX_CHALLENGES`, then adds `challenge`.
+    fn insert(&mut self, nonce: String, challenge: Challenge, now: i64) {
+        while let Some((expires_at, _)) = self.by_expiry.front() {
+            if *expires_at > now && self.pending.len() < MAX_CHALLENGES {
+                break;
+            }
+            if let Some((_, oldest)) = self.by_expiry.pop_front() {
+                self.pending.remove(&oldest);
+            }
+        }
+        self.by_expiry.push_back((challenge.expires_at, nonce.clone()));
+        self.pending.insert(nonce, challenge);
+    }
+
+    /// Removes and returns the challenge for `nonce`, so each is used at most once.
+    fn take(&mut self, nonce: &str) -> Option<Challenge> {
+        self.pending.remove(nonce)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChallengeRequest {
+    pub wallet: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyRequest {
+    pub wallet: String,
+    pub nonce: String,
+    /// Base58 ed25519 signature of the challenge message.
+    pub signature: String,
+}
+
+fn challenges() -> &'static Arc<Mutex<ChallengeStore>> {
+    CHALLENGES.get_or_init(|| Arc::new(Mutex::new(ChallengeStore::default())))
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut buffer = vec![0u8; bytes];
+    rand::thread_rng().fill(buffer.as_mut_slice());
+    hex::encode(buffer)
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn iso8601(timestamp: i64) -> String {
+    Utc.timestamp_opt(timestamp, 0).single().unwrap_or_default().to_rfc3339()
+}
+
+/// The message the wallet signs, in the Sign-In With Solana text format.
+fn sign_in_message(domain: &str, wallet: &Pubkey, nonce: &str, issued_at: i64, expires_at: i64) -> String {
+    format!(
+        "{} wants you to sign in with your Solana account:\n{}\n\nSign in to Trading Signals Backend\n\nNonce: {}\nIssued At: {}\nExpiration Time: {}",
+        domain, wallet, nonce, iso8601(issued_at), iso8601(expires_at)
+    )
+}
+
+fn bearer_token(req: &HttpRequest) -> Option<&str> {
+    req.headers().get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+/// The wallet behind the request's `Authorization: Bearer` session token, if any.
+pub fn session_wallet(req: &HttpRequest, db: &Database) -> Option<Pubkey> {
+    let token = bearer_token(req)?;
+    match db.find_session(&hash_token(token), Utc::now().timestamp()) {
+        Ok(session) => session.and_then(|s| Pubkey::from_str(&s.wallet).ok()),
+        Err(e) => {
+            println!("❌ Session lookup failed: {}", e);
+            None
+        },
+    }
+}
+
+// ========== SIGN-IN WITH SOLANA ==========
+pub async fn challenge(req: HttpRequest, config: web::Data<Config>, body: web::Json<ChallengeRequest>) -> impl Responder {
+    let key = match client_ip(&req, &config.trusted_proxies) {
+        Some(ip) => format!("challenge:{}", ip),
+        None => "challenge:unknown".to_string(),
+    };
+    if let Err(retry_after) = check_rate_limit(&key, config.challenge_requests_per_minute) {
+        return HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", retry_after.to_string()))
+            .json(json!({ "message": format!("Too many requests, try again in {} seconds", retry_after) }));
+    }
+
+    let Ok(wallet) = Pubkey::from_str(body.wallet.trim()) else {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid wallet address",
+            "wallet": body.wallet,
+        }));
+    };
+
+    let now = Utc::now().timestamp();
+    let nonce = random_hex(16);
+    let expires_at = now + CHALLENGE_TTL_SECONDS;
+    let message = sign_in_message(&config.auth_domain, &wallet, &nonce, now, expires_at);
+
+    challenges().lock().unwrap().insert(nonce.clone(), Challenge { wallet, message: message.clone(), expires_at }, now);
+
+    HttpResponse::Ok().json(json!({
+        "wallet": wallet.to_string(),
+        "nonce": nonce,
+        "message": message,
+        "expires_at": expires_at
+    }))
+}
+
+pub async fn verify(
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    body: web::Json<VerifyRequest>,
+) -> impl Responder {
+    let unauthorized = |message: &str| HttpResponse::Unauthorized().json(json!({
+        "error": "Sign-in failed",
+        "message": message,
+    }));
+
+    // Each nonce is single use, whether or not the signature checks out
+    let Some(challenge) = challenges().lock().unwrap().take(&body.nonce) else {
+        return unauthorized("Unknown or already used nonce");
+    };
+    let now = Utc::now().timestamp();
+    if challenge.expires_at <= now {
+        return unauthorized("Challenge expired");
+    }
+    if Pubkey::from_str(body.wallet.trim()).ok() != Some(challenge.wallet) {
+        return unauthorized("Wallet does not match the challenge");
+    }
+
+    let signature = bs58::decode(body.signature.trim())
+        .into_vec()
+        .ok()
+        .and_then(|bytes| Signature::try_from(bytes.as_slice()).ok());
+    let Some(signature) = signature else {
+        return unauthorized("Signature must be a base58-encoded ed25519 signature");
+    };
+    if !signature.verify(challenge.wallet.as_ref(), challenge.message.as_bytes()) {
+        return unauthorized("Invalid signature");
+    }
+
+    let token = random_hex(32);
+    let session = Session {
+        wallet: challenge.wallet.to_string(),
+        created_at: now,
+        expires_at: now + config.session_ttl_seconds,
+    };
+    if let Err(e) = db.prune_sessions(now).and_then(|_| db.insert_session(&hash_token(&token), &session)) {
+        println!("❌ Failed to store session: {}", e);
+        return HttpResponse::InternalServerError().json(json!({
+            "error": "Failed to create session",
+        }));
+    }
+
+    println!("🔑 Wallet {} signed in", session.wallet);
+    HttpResponse::Ok().json(json!({
+        "token": token,
+        "token_type": "Bearer",
+        "wallet": session.wallet,
+        "expires_at": session.expires_at
+    }))
+}
+
+pub async fn session(req: HttpRequest, db: web::Data<Database>) -> impl Responder {
+    let session = bearer_token(&req)
+        .map(|token| db.find_session(&hash_token(token), Utc::now().timestamp()));
+
+    match session {
+        Some(Ok(Some(session))) => HttpResponse::Ok().json(session),
+        Some(Err(e)) => HttpResponse::InternalServerError().json(json!({
+            "error": "Session lookup failed",
+            "message": e,
+        })),
+        _ => HttpResponse::Unauthorized().json(json!({
+            "error": "Not signed in",
+            "message": "Missing, invalid or expired bearer token",
+        })),
+    }
+}
+
+pub async fn logout(req: HttpRequest, db: web::Data<Database>) -> impl Responder {
+    let removed = bearer_token(&req)
+        .map(|token| db.delete_session(&hash_token(token)).unwrap_or(false))
+        .unwrap_or(false);
+
+    HttpResponse::Ok().json(json!({
+        "status": "success",
+        "signed_out": removed,
+        "timestamp": Utc::now().timestamp()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+    use serde_json::Value;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn challenge_for(wallet: &Pubkey, expires_at: i64) -> Challenge {
+        Challenge { wallet: *wallet, message: "sign me".to_string(), expires_at }
+    }
+
+    #[actix_rt::test]
+    async fn signs_in_with_a_signed_challenge() {
+        let mut config = Config::from_env().unwrap();
+        config.challenge_requests_per_minute = 3;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(Database::in_memory().unwrap()))
+                .route("/auth/challenge", web::post().to(challenge))
+                .route("/auth/verify", web::post().to(verify))
+                .route("/auth/session", web::get().to(session))
+                .route("/auth/logout", web::post().to(logout)),
+        ).await;
+        let keypair = Keypair::new();
+        let other = Keypair::new();
+        let request_challenge = || test::TestRequest::post()
+            .uri("/auth/challenge")
+            .peer_addr("192.0.2.16:5000".parse().unwrap())
+            .set_json(json!({ "wallet": keypair.pubkey().to_string() }))
+            .to_request();
+        let verify_with = |nonce: &Value, signer: &Keypair, message: &Value| test::TestRequest::post()
+            .uri("/auth/verify")
+            .set_json(json!({
+                "wallet": keypair.pubkey().to_string(),
+                "nonce": nonce,
+                "signature": signer.sign_message(message.as_str().unwrap().as_bytes()).to_string(),
+            }))
+            .to_request();
+
+        // A signature by another wallet fails and uses up the nonce
+        let issued: Value = test::call_and_read_body_json(&app, request_challenge()).await;
+        assert!(issued["message"].as_str().unwrap().contains(&keypair.pubkey().to_string()));
+        let failed: Value = test::call_and_read_body_json(&app, verify_with(&issued["nonce"], &other, &issued["message"])).await;
+        assert_eq!(failed["message"], "Invalid signature");
+        let retried: Value = test::call_and_read_body_json(&app, verify_with(&issued["nonce"], &keypair, &issued["message"])).await;
+        assert_eq!(retried["message"], "Unknown or already used nonce");
+
+        let issued: Value = test::call_and_read_body_json(&app, request_challenge()).await;
+        let signed_in: Value = test::call_and_read_body_json(&app, verify_with(&issued["nonce"], &keypair, &issued["message"])).await;
+        assert_eq!(signed_in["wallet"], keypair.pubkey().to_string());
+        let bearer = format!("Bearer {}", signed_in["token"].as_str().unwrap());
+
+        let current: Value = test::call_and_read_body_json(&app, test::TestRequest::get()
+            .uri("/auth/session")
+            .insert_header(("Authorization", bearer.as_str()))
+            .to_request()).await;
+        assert_eq!(current["wallet"], keypair.pubkey().to_string());
+        let logged_out: Value = test::call_and_read_body_json(&app, test::TestRequest::post()
+            .uri("/auth/logout")
+            .insert_header(("Authorization", bearer.as_str()))
+            .to_request()).await;
+        assert_eq!(logged_out["signed_out"], true);
+        let gone = test::call_service(&app, test::TestRequest::get()
+            .uri("/auth/session")
+            .insert_header(("Authorization", bearer.as_str()))
+            .to_request()).await;
+        assert_eq!(gone.status(), 401);
+
+        // The fourth challenge within the minute is refused
+        assert_eq!(test::call_service(&app, request_challenge()).await.status(), 200);
+        let limited = test::call_service(&app, request_challenge()).await;
+        assert_eq!(limited.status(), 429);
+        assert!(limited.headers().contains_key("Retry-After"));
+    }
+
+    #[actix_rt::test]
+    async fn refuses_expired_or_mismatched_challenges() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Config::from_env().unwrap()))
+                .app_data(web::Data::new(Database::in_memory().unwrap()))
+                .route("/auth/verify", web::post().to(verify)),
+        ).await;
+        let keypair = Keypair::new();
+        let now = Utc::now().timestamp();
+        // Straight into the map, so a challenge issued by another test cannot evict the expired one first
+        {
+            let mut store = challenges().lock().unwrap();
+            store.pending.insert("expired-nonce".to_string(), challenge_for(&keypair.pubkey(), now - 1));
+            store.pending.insert("mismatch-nonce".to_string(), challenge_for(&Keypair::new().pubkey(), now + 300));
+        }
+
+        let attempt = |nonce: &str| test::TestRequest::post()
+            .uri("/auth/verify")
+            .set_json(json!({
+                "wallet": keypair.pubkey().to_string(),
+                "nonce": nonce,
+                "signature": keypair.sign_message(b"sign me").to_string(),
+            }))
+            .to_request();
+
+        let expired: Value = test::call_and_read_body_json(&app, attempt("expired-nonce")).await;
+        assert_eq!(expired["message"], "Challenge expired");
+        let mismatch: Value = test::call_and_read_body_json(&app, attempt("mismatch-nonce")).await;
+        assert_eq!(mismatch["message"], "Wallet does not match the challenge");
+    }
+
+    #[actix_rt::test]
+    async fn evicts_expired_and_then_the_oldest_challenges() {
+        let wallet = Pubkey::new_unique();
+        let mut store = ChallengeStore::default();
+
+        store.insert("old".to_string(), challenge_for(&wallet, 100), 0);
+        store.insert("taken".to_string(), challenge_for(&wallet, 150), 0);
+        assert!(store.take("taken").is_some());
+        store.insert("new".to_string(), challenge_for(&wallet, 200), 120);
+        assert_eq!(store.pending.len(), 1);
+        assert_eq!(store.by_expiry.front().map(|(_, nonce)| nonce.as_str()), Some("taken"));
+
+        for i in 0..MAX_CHALLENGES {
+            store.insert(format!("n{}", i), challenge_for(&wallet, 300), 120);
+        }
+        assert_eq!(store.pending.len(), MAX_CHALLENGES);
+        assert!(store.take("new").is_none());
+        assert!(store.take("n0").is_some());
+        assert!(store.take("taken").is_none());
+    }
+}
@@ -0,0 +1,66 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::Config;
+use crate::market_data::{self, Interval};
+use crate::symbols;
+
+#[derive(Deserialize)]
+pub struct CandleQuery {
+    pub interval: Option<String>,
+    pub limit: Option<u16>,
+}
+
+// ========== HISTORICAL CANDLES ==========
+#[get("/candles/{symbol}")]
+pub async fn get_candles(
+    symbol: web::Path<String>,
+    query: web::Query<CandleQuery>,
+    config: web::Data<Config>,
+) -> impl Responder {
+    let symbol_upper = symbol.into_inner().to_uppercase();
+
+    let interval = match Interval::parse(query.interval.as_deref().unwrap_or("1h")) {
+        Ok(interval) => interval,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": "Invalid interval",
+                "message": e,
+            }));
+        }
+    };
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+
+    let pairs = symbols::binance_pairs();
+    let pair = symbols::get(&symbol_upper)
+        .and_then(|info| info.binance_pair)
+        .or_else(|| market_data::pair_for_symbol(&symbol_upper, &pairs));
+    let pair = match pair {
+        Some(pair) => pair,
+        None => {
+            return HttpResponse::NotFound().json(json!({
+                "error": "Unsupported symbol",
+                "message": format!("No Binance pair for {}. Registered pairs: {}", symbol_upper, pairs.join(", ")),
+                "symbol": symbol_upper
+            }));
+        }
+    };
+
+    match market_data::get_candles(&config.binance_base_url, &pair, interval, limit).await {
+        Ok(candles) => HttpResponse::Ok().json(json!({
+            "symbol": symbol_upper,
+            "pair": pair,
+            "interval": interval,
+            "candles": candles,
+            "count": candles.len(),
+            "timestamp": Utc::now().timestamp()
+        })),
+        Err(e) => HttpResponse::ServiceUnavailable().json(json!({
+            "error": "Failed to fetch candles",
+            "message": e,
+            "symbol": symbol_upper
+        })),
+    }
+}
@@ -0,0 +1,424 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::admin::reject_non_admin;
+use crate::blockchain::{actions, SolanaClient};
+use crate::conditions;
+use crate::config::Config;
+use crate::storage::actions::{ActionRunQuery, ActionSpec, ConditionAction, RunMode};
+use crate::storage::conditions::{ConditionSpec, PriceCondition};
+use crate::storage::Database;
+
+fn storage_error(e: String) -> HttpResponse {
+    HttpResponse::InternalServerError().json(json!({
+        "status": "error",
+        "message": e,
+    }))
+}
+
+fn invalid_request(e: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(json!({
+        "status": "error",
+        "message": e,
+    }))
+}
+
+fn condition_not_found(id: i64) -> HttpResponse {
+    HttpResponse::NotFound().json(json!({
+        "status": "error",
+        "message": format!("Unknown condition: {}", id),
+    }))
+}
+
+/// Action `id` together with the condition it belongs to.
+fn action_with_condition(db: &Database, id: i64) -> Result<Option<(ConditionAction, PriceCondition)>, String> {
+    match db.action(id)? {
+        Some(action) => Ok(db.condition(action.condition_id)?.map(|condition| (action, condition))),
+        None => Ok(None),
+    }
+}
+
+fn action_not_found(id: i64) -> HttpResponse {
+    HttpResponse::NotFound().json(json!({
+        "status": "error",
+        "message": format!("Unknown action: {}", id),
+    }))
+}
+
+// ========== PRICE CONDITIONS ==========
+// Conditions are evaluated on every price update; firing disarms them until their
+// re-arm policy (once, cooldown, reset) allows another trigger.
+#[derive(Deserialize)]
+pub struct ConditionsQuery {
+    pub symbol: Option<String>,
+}
+
+pub async fn list_conditions(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    query: web::Query<ConditionsQuery>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    let symbol = query.symbol.as_deref().map(str::to_uppercase);
+    match db.conditions(symbol.as_deref()) {
+        Ok(conditions) => HttpResponse::Ok().json(json!({
+            "conditions": conditions,
+            "count": conditions.len(),
+            "timestamp": Utc::now().timestamp()
+        })),
+        Err(e) => storage_error(e),
+    }
+}
+
+pub async fn create_condition(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    body: web::Json<ConditionSpec>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    let mut spec = body.into_inner();
+    if let Err(e) = conditions::validate(&mut spec) {
+        return invalid_request(e);
+    }
+
+    let created = db.insert_condition(&spec, Utc::now().timestamp())
+        .and_then(|id| db.condition(id));
+    match created {
+        Ok(Some(condition)) => {
+            println!("🎯 Condition {} added: {} {} {}", condition.id, spec.symbol, spec.operator.as_str(), spec.value);
+            HttpResponse::Created().json(json!({
+                "status": "success",
+                "condition": condition,
+            }))
+        },
+        Ok(None) => storage_error("Condition was not stored".to_string()),
+        Err(e) => storage_error(e),
+    }
+}
+
+pub async fn get_condition(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    id: web::Path<i64>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match db.condition(*id) {
+        Ok(Some(condition)) => HttpResponse::Ok().json(condition),
+        Ok(None) => condition_not_found(*id),
+        Err(e) => storage_error(e),
+    }
+}
+
+/// Replaces a condition and re-arms it.
+pub async fn update_condition(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    id: web::Path<i64>,
+    body: web::Json<ConditionSpec>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    let mut spec = body.into_inner();
+    if let Err(e) = conditions::validate(&mut spec) {
+        return invalid_request(e);
+    }
+
+    match db.update_condition(*id, &spec).and_then(|_| db.condition(*id)) {
+        Ok(Some(condition)) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "condition": condition,
+        })),
+        Ok(None) => condition_not_found(*id),
+        Err(e) => storage_error(e),
+    }
+}
+
+pub async fn delete_condition(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    id: web::Path<i64>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match db.delete_condition(*id) {
+        Ok(true) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "message": format!("Removed condition {}", id),
+            "timestamp": Utc::now().timestamp()
+        })),
+        Ok(false) => condition_not_found(*id),
+        Err(e) => storage_error(e),
+    }
+}
+
+pub async fn rearm_condition(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    id: web::Path<i64>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match db.set_condition_armed(*id, true).and_then(|_| db.condition(*id)) {
+        Ok(Some(condition)) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "condition": condition,
+        })),
+        Ok(None) => condition_not_found(*id),
+        Err(e) => storage_error(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TriggersQuery {
+    pub limit: Option<u32>,
+}
+
+pub async fn condition_triggers(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    id: web::Path<i64>,
+    query: web::Query<TriggersQuery>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match db.condition(*id) {
+        Ok(Some(_)) => {},
+        Ok(None) => return condition_not_found(*id),
+        Err(e) => return storage_error(e),
+    }
+
+    match db.condition_triggers(*id, query.limit.unwrap_or(100).clamp(1, 1000)) {
+        Ok(triggers) => HttpResponse::Ok().json(json!({
+            "condition_id": *id,
+            "triggers": triggers,
+            "count": triggers.len(),
+            "timestamp": Utc::now().timestamp()
+        })),
+        Err(e) => storage_error(e),
+    }
+}
+
+// ========== CONDITION ACTIONS ==========
+// A firing condition runs its actions: a `subscription_program` instruction with templated
+// accounts and args, sent once per idempotency key or only simulated for dry runs.
+pub async fn list_actions(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    condition_id: web::Path<i64>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match db.condition(*condition_id) {
+        Ok(Some(_)) => {},
+        Ok(None) => return condition_not_found(*condition_id),
+        Err(e) => return storage_error(e),
+    }
+
+    match db.actions(Some(*condition_id)) {
+        Ok(actions) => HttpResponse::Ok().json(json!({
+            "condition_id": *condition_id,
+            "actions": actions,
+            "count": actions.len(),
+            "timestamp": Utc::now().timestamp()
+        })),
+        Err(e) => storage_error(e),
+    }
+}
+
+pub async fn create_action(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    solana: web::Data<Option<SolanaClient>>,
+    condition_id: web::Path<i64>,
+    body: web::Json<ActionSpec>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    let condition = match db.condition(*condition_id) {
+        Ok(Some(condition)) => condition,
+        Ok(None) => return condition_not_found(*condition_id),
+        Err(e) => return storage_error(e),
+    };
+    let spec = body.into_inner();
+    if let Err(e) = actions::validate(&spec, &condition, solana.as_ref().as_ref()) {
+        return invalid_request(e);
+    }
+
+    let created = db.insert_action(condition.id, &spec, Utc::now().timestamp())
+        .and_then(|id| db.action(id));
+    match created {
+        Ok(Some(action)) => {
+            println!("⛓️ Action {} added to condition {}: {}{}", action.id, condition.id, spec.instruction,
+                if spec.dry_run { " (dry run)" } else { "" });
+            HttpResponse::Created().json(json!({
+                "status": "success",
+                "action": action,
+            }))
+        },
+        Ok(None) => storage_error("Action was not stored".to_string()),
+        Err(e) => storage_error(e),
+    }
+}
+
+pub async fn get_action(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    id: web::Path<i64>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match db.action(*id) {
+        Ok(Some(action)) => HttpResponse::Ok().json(action),
+        Ok(None) => action_not_found(*id),
+        Err(e) => storage_error(e),
+    }
+}
+
+pub async fn update_action(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    solana: web::Data<Option<SolanaClient>>,
+    id: web::Path<i64>,
+    body: web::Json<ActionSpec>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    let condition = match action_with_condition(&db, *id) {
+        Ok(Some((_, condition))) => condition,
+        Ok(None) => return action_not_found(*id),
+        Err(e) => return storage_error(e),
+    };
+    let spec = body.into_inner();
+    if let Err(e) = actions::validate(&spec, &condition, solana.as_ref().as_ref()) {
+        return invalid_request(e);
+    }
+
+    match db.update_action(*id, &spec).and_then(|_| db.action(*id)) {
+        Ok(Some(action)) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "action": action,
+        })),
+        Ok(None) => action_not_found(*id),
+        Err(e) => storage_error(e),
+    }
+}
+
+pub async fn delete_action(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    id: web::Path<i64>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match db.delete_action(*id) {
+        Ok(true) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "message": format!("Removed action {}", id),
+            "timestamp": Utc::now().timestamp()
+        })),
+        Ok(false) => action_not_found(*id),
+        Err(e) => storage_error(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SimulateQuery {
+    /// Trigger price; defaults to the condition's level.
+    pub price: Option<f64>,
+}
+
+/// Dry-runs an action through `simulateTransaction` as if its condition fired now.
+pub async fn simulate_action(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    solana: web::Data<Option<SolanaClient>>,
+    id: web::Path<i64>,
+    query: web::Query<SimulateQuery>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+    if solana.is_none() {
+        return HttpResponse::ServiceUnavailable().json(json!({
+            "error": "Solana not configured",
+            "message": "Set SOLANA_KEYPAIR_PATH or SOLANA_WALLET_KEY to enable condition actions",
+        }));
+    }
+
+    let (action, condition) = match action_with_condition(&db, *id) {
+        Ok(Some(found)) => found,
+        Ok(None) => return action_not_found(*id),
+        Err(e) => return storage_error(e),
+    };
+
+    let trigger = actions::sample_trigger(&condition, query.price);
+    match actions::execute(&action, &trigger, RunMode::Simulate, &db, solana.as_ref().as_ref()).await {
+        Ok(run) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "run": run,
+        })),
+        Err(e) => storage_error(e),
+    }
+}
+
+pub async fn action_log(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    query: web::Query<ActionRunQuery>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match db.action_runs(&query) {
+        Ok(runs) => HttpResponse::Ok().json(json!({
+            "runs": runs,
+            "count": runs.len(),
+            "timestamp": Utc::now().timestamp()
+        })),
+        Err(e) => storage_error(e),
+    }
+}
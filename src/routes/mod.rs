@@ -1,2 +1,15 @@
 pub mod signals;
+pub mod admin;
 pub mod ai_explanation;
+pub mod auth;
+pub mod candles;
+pub mod conditions;
+pub mod minimal_blockchain;
+pub mod pay;
+pub mod plans;
+pub mod sse;
+pub mod subscription;
+pub mod tradingview;
+pub mod wallet_simple;
+pub mod webhook_auth;
+pub mod ws;
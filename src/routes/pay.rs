@@ -0,0 +1,404 @@
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::str::FromStr;
+
+use super::plans::{check_rate_limit, client_ip};
+use super::subscription::{build_payment, plan_quote, PaymentTerms};
+use crate::blockchain::accounts::SubscriptionReader;
+use crate::blockchain::payments::{checkout_expired, find_mint, PaymentMint, PaymentQuote, CHECKOUT_TTL_SECONDS};
+use crate::config::Config;
+use crate::providers::Providers;
+use crate::storage::payments::{PaymentStatus, SubscriptionPayment};
+use crate::storage::Database;
+
+const ICON_SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64"><rect width="64" height="64" rx="14" fill="#111827"/><path d="M12 44l12-14 9 8 19-22" stroke="#22c55e" stroke-width="6" fill="none" stroke-linecap="round" stroke-linejoin="round"/></svg>"##;
+
+/// Query string of a checkout link: `/pay/subscribe/{tier}?period_days=&currency=&reference=`.
+#[derive(Debug, Deserialize)]
+pub struct CheckoutQuery {
+    pub period_days: Option<i64>,
+    pub currency: Option<String>,
+    /// Solana Pay reference key; generated when unset.
+    pub reference: Option<String>,
+}
+
+impl CheckoutQuery {
+    fn terms(&self, plan_tier: u8) -> PaymentTerms {
+        PaymentTerms {
+            plan_tier,
+            period_days: self.period_days,
+            currency: self.currency.clone(),
+        }
+    }
+}
+
+/// Body a wallet posts to a transaction request URL.
+#[derive(Debug, Deserialize)]
+pub struct TransactionRequest {
+    pub account: String,
+}
+
+/// Solana Pay error responses carry a `message` the wallet shows to the user.
+fn pay_error(mut response: actix_web::HttpResponseBuilder, message: String) -> HttpResponse {
+    response.json(json!({ "message": message }))
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn base_url(req: &HttpRequest) -> String {
+    let info = req.connection_info();
+    format!("{}://{}", info.scheme(), info.host())
+}
+
+/// Why `checkout` cannot be rebuilt for `account`, if it cannot.
+fn checkout_closed(checkout: &SubscriptionPayment, account: &Pubkey, now: i64) -> Option<&'static str> {
+    if checkout.user != account.to_string() {
+        return Some("This checkout link belongs to another wallet");
+    }
+    match checkout.status {
+        PaymentStatus::Verified => Some("This checkout was already paid"),
+        PaymentStatus::Expired => Some("This checkout link has expired"),
+        PaymentStatus::Failed => Some("This checkout link was already used"),
+        PaymentStatus::Pending if checkout_expired(checkout, now) => Some("This checkout link has expired"),
+        PaymentStatus::Pending => None,
+    }
+}
+
+/// Middleware for `/pay`, which wallets call without a session: rate limits each client address.
+pub async fn limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let Some(config) = req.app_data::<web::Data<Config>>().cloned() else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+
+    let key = match client_ip(req.request(), &config.trusted_proxies) {
+        Some(ip) => format!("pay:{}", ip),
+        None => "pay:unknown".to_string(),
+    };
+    if let Err(retry_after) = check_rate_limit(&key, config.pay_requests_per_minute) {
+        let response = HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", retry_after.to_string()))
+            .json(json!({ "message": format!("Too many requests, try again in {} seconds", retry_after) }));
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+    Ok(next.call(req).await?.map_into_left_body())
+}
+
+// ========== SOLANA PAY ==========
+// Transaction requests per the Solana Pay spec: wallets GET the label and icon, then
+// POST their `account` and receive the payment transaction to sign.
+pub async fn checkout_metadata(req: HttpRequest, config: web::Data<Config>, tier: web::Path<u8>) -> impl Responder {
+    let tier = tier.into_inner();
+    if !config.plan_tiers.iter().any(|t| t.tier == tier && t.monthly_price_usd > 0.0) {
+        return pay_error(HttpResponse::NotFound(), format!("No plan with tier {} is for sale", tier));
+    }
+
+    HttpResponse::Ok().json(json!({
+        "label": config.pay_label,
+        "icon": config.pay_icon_url.clone().unwrap_or_else(|| format!("{}/pay/icon.svg", base_url(&req))),
+    }))
+}
+
+/// The terms a pending checkout was opened with, so a rebuilt transaction asks for the
+/// same plan and amount as any earlier build the wallet may still send.
+fn recorded_quote(config: &Config, checkout: &SubscriptionPayment) -> Option<(PaymentQuote, PaymentMint, Pubkey)> {
+    let mint = find_mint(&config.payment_mints, &checkout.mint)?;
+    let plan = config.plan_tiers.iter().find(|t| t.tier == checkout.plan_tier)?;
+    let treasury = Pubkey::from_str(&checkout.treasury).ok()?;
+    let ui_amount = checkout.amount as f64 / 10f64.powi(mint.decimals as i32);
+
+    let quote = PaymentQuote {
+        plan_tier: checkout.plan_tier,
+        plan: plan.name.clone(),
+        period_days: checkout.period_days,
+        price_usd: checkout.price_usd,
+        currency: checkout.currency.clone(),
+        mint: checkout.mint.clone(),
+        decimals: mint.decimals,
+        amount: checkout.amount,
+        ui_amount,
+        token_price_usd: checkout.price_usd / ui_amount,
+        treasury: checkout.treasury.clone(),
+        quoted_at: checkout.created_at,
+    };
+    Some((quote, mint.clone(), treasury))
+}
+
+/// Builds the payment transaction for the posting wallet. The checkout is recorded
+/// under its reference key and marked paid by the checkout poller once it lands.
+/// Posting again for a pending checkout rebuilds its transaction on the recorded terms,
+/// e.g. after a dropped response. New checkouts are capped per account, per client
+/// address and overall.
+#[allow(clippy::too_many_arguments)]
+pub async fn checkout_transaction(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    providers: web::Data<Providers>,
+    reader: web::Data<SubscriptionReader>,
+    tier: web::Path<u8>,
+    query: web::Query<CheckoutQuery>,
+    body: web::Json<TransactionRequest>,
+) -> impl Responder {
+    let Ok(account) = Pubkey::from_str(&body.account) else {
+        return pay_error(HttpResponse::BadRequest(), format!("Invalid account: {}", body.account));
+    };
+    let reference = match query.reference.as_deref().map(Pubkey::from_str) {
+        Some(Ok(reference)) => reference,
+        Some(Err(_)) => return pay_error(HttpResponse::BadRequest(), "Invalid reference key".to_string()),
+        None => Keypair::new().pubkey(),
+    };
+    let client = client_ip(&req, &config.trusted_proxies);
+    let now = Utc::now().timestamp();
+
+    let quoted = match db.payment_by_reference(&reference.to_string()) {
+        Ok(Some(checkout)) => {
+            if let Some(reason) = checkout_closed(&checkout, &account, now) {
+                return pay_error(HttpResponse::Conflict(), reason.to_string());
+            }
+            match recorded_quote(&config, &checkout) {
+                Some(quoted) => quoted,
+                None => return pay_error(HttpResponse::Conflict(), "This checkout can no longer be paid, open a new one".to_string()),
+            }
+        },
+        Ok(None) => {
+            let client_key = client.map(|ip| ip.to_string());
+            match db.pending_checkout_counts(&account.to_string(), client_key.as_deref(), now - CHECKOUT_TTL_SECONDS) {
+                Ok((mine, _, _)) if mine >= config.max_pending_checkouts_per_account => return pay_error(
+                    HttpResponse::TooManyRequests(),
+                    format!("This wallet already has {} open checkouts, pay or let them expire first", mine),
+                ),
+                Ok((_, from_client, _)) if from_client >= config.max_pending_checkouts_per_client => return pay_error(
+                    HttpResponse::TooManyRequests(),
+                    "Too many open checkouts from this address, pay or let them expire first".to_string(),
+                ),
+                Ok((_, _, all)) if all >= config.max_pending_checkouts => return pay_error(
+                    HttpResponse::ServiceUnavailable(),
+                    "Too many open checkouts, try again in a few minutes".to_string(),
+                ),
+                Ok(_) => {},
+                Err(e) => return pay_error(HttpResponse::InternalServerError(), e),
+            }
+            match plan_quote(&config, &providers, &query.terms(tier.into_inner())).await {
+                Ok(quoted) => quoted,
+                Err(response) => return response,
+            }
+        },
+        Err(e) => return pay_error(HttpResponse::InternalServerError(), e),
+    };
+
+    match build_payment(&db, &reader, account, quoted, Some(reference), client).await {
+        Ok((built, quote)) => HttpResponse::Ok().json(json!({
+            "transaction": built.transaction,
+            "message": format!("{} plan, {} days for {} {}", quote.plan, quote.period_days, quote.ui_amount, quote.currency),
+        })),
+        Err(response) => response,
+    }
+}
+
+/// A ready-made `solana:` transaction request link with a fresh reference key to poll.
+pub async fn checkout_link(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    tier: web::Path<u8>,
+    query: web::Query<CheckoutQuery>,
+) -> impl Responder {
+    let tier = tier.into_inner();
+    if !config.plan_tiers.iter().any(|t| t.tier == tier && t.monthly_price_usd > 0.0) {
+        return pay_error(HttpResponse::NotFound(), format!("No plan with tier {} is for sale", tier));
+    }
+
+    let reference = Keypair::new().pubkey().to_string();
+    let mut params = vec![format!("reference={}", reference)];
+    if let Some(days) = query.period_days {
+        params.push(format!("period_days={}", days));
+    }
+    if let Some(currency) = &query.currency {
+        params.push(format!("currency={}", percent_encode(currency)));
+    }
+    let link = format!("{}/pay/subscribe/{}?{}", base_url(&req), tier, params.join("&"));
+
+    HttpResponse::Ok().json(json!({
+        "url": format!("solana:{}", percent_encode(&link)),
+        "link": link,
+        "reference": reference,
+        "status": format!("/pay/checkouts/{}", reference),
+    }))
+}
+
+/// Where a checkout stands; `paid` once the poller verified its transaction.
+pub async fn checkout_status(db: web::Data<Database>, reference: web::Path<String>) -> impl Responder {
+    let reference = reference.into_inner();
+    match db.payment_by_reference(&reference) {
+        Ok(Some(payment)) => HttpResponse::Ok().json(json!({
+            "reference": reference,
+            "status": payment.status,
+            "paid": payment.status == PaymentStatus::Verified,
+            "payment": payment,
+        })),
+        // The wallet has not posted for this reference yet
+        Ok(None) => HttpResponse::Ok().json(json!({
+            "reference": reference,
+            "status": "awaiting_wallet",
+            "paid": false,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": "Failed to load checkout",
+            "message": e,
+        })),
+    }
+}
+
+pub async fn icon() -> impl Responder {
+    HttpResponse::Ok().content_type("image/svg+xml").body(ICON_SVG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::payments;
+    use actix_web::{test, App, HttpServer};
+    use serde_json::Value;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// A validator whose only program transaction is `landed`, once set.
+    async fn rpc(body: web::Json<Value>, landed: web::Data<Mutex<Value>>) -> HttpResponse {
+        let landed = landed.lock().unwrap().clone();
+        let result = match body["method"].as_str() {
+            Some("getLatestBlockhash") => json!({
+                "context": { "slot": 1 },
+                "value": { "blockhash": solana_sdk::hash::Hash::new_unique().to_string(), "lastValidBlockHeight": 100 },
+            }),
+            Some("getSignaturesForAddress") if landed.is_null() => json!([]),
+            Some("getSignaturesForAddress") => json!([{
+                "signature": solana_sdk::signature::Signature::new_unique().to_string(),
+                "slot": 1, "err": null, "memo": null, "blockTime": null,
+            }]),
+            Some("getTransaction") => landed,
+            Some("getVersion") => json!({ "solana-core": "1.18.26", "feature-set": 0 }),
+            _ => Value::Null,
+        };
+        HttpResponse::Ok().json(json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }))
+    }
+
+    fn reader(landed: web::Data<Mutex<Value>>) -> SubscriptionReader {
+        let server = HttpServer::new(move || App::new().app_data(landed.clone()).route("/", web::post().to(rpc)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let url = format!("http://{}", server.addrs()[0]);
+        actix_rt::spawn(server.run());
+        SubscriptionReader::new(url, Pubkey::new_unique(), Duration::from_secs(0))
+    }
+
+    #[actix_rt::test]
+    async fn a_checkout_is_paid_by_whichever_build_the_wallet_sent() {
+        let landed = web::Data::new(Mutex::new(Value::Null));
+        let reader = web::Data::new(reader(landed.clone()));
+        let db = web::Data::new(Database::in_memory().unwrap());
+        let mut config = Config::from_env().unwrap();
+        let treasury = Pubkey::new_unique();
+        config.payment_treasury = Some(treasury);
+        config.max_pending_checkouts_per_client = 1;
+        let mint = config.payment_mints[0].mint.to_string();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(Providers::fixture()))
+                .app_data(db.clone())
+                .app_data(reader.clone())
+                .route("/pay/subscribe/{tier}", web::get().to(checkout_metadata))
+                .route("/pay/subscribe/{tier}", web::post().to(checkout_transaction))
+                .route("/pay/subscribe/{tier}/link", web::get().to(checkout_link))
+                .route("/pay/checkouts/{reference}", web::get().to(checkout_status)),
+        ).await;
+        let post = |reference: &str, account: Pubkey, peer: &str| test::TestRequest::post()
+            .uri(&format!("/pay/subscribe/1?reference={}", reference))
+            .peer_addr(format!("{}:50000", peer).parse().unwrap())
+            .set_json(json!({ "account": account.to_string() }))
+            .to_request();
+
+        let metadata: Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/pay/subscribe/1").to_request()).await;
+        assert!(metadata["label"].is_string());
+        let link: Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/pay/subscribe/1/link").to_request()).await;
+        let reference = link["reference"].as_str().unwrap().to_string();
+
+        // The wallet signs the first build, but its response is dropped and it posts again
+        let user = Pubkey::new_unique();
+        let first: Value = test::call_and_read_body_json(&app, post(&reference, user, "198.51.100.1")).await;
+        let sent = db.payment_by_reference(&reference).unwrap().unwrap();
+        let second: Value = test::call_and_read_body_json(&app, post(&reference, user, "198.51.100.1")).await;
+        let rebuilt = db.payment_by_reference(&reference).unwrap().unwrap();
+        assert_ne!(first["transaction"], second["transaction"]);
+        assert_ne!(sent.subscription, rebuilt.subscription);
+        assert_eq!((rebuilt.plan_tier, rebuilt.amount), (sent.plan_tier, sent.amount));
+
+        // Another checkout from the same address is refused, from elsewhere it is not
+        let other = Keypair::new().pubkey().to_string();
+        let response = test::call_service(&app, post(&other, Pubkey::new_unique(), "198.51.100.1")).await;
+        assert_eq!(response.status(), actix_web::http::StatusCode::TOO_MANY_REQUESTS);
+        let response = test::call_service(&app, post(&other, Pubkey::new_unique(), "198.51.100.2")).await;
+        assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+
+        let status_uri = format!("/pay/checkouts/{}", reference);
+        let status: Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri(&status_uri).to_request()).await;
+        assert_eq!((status["status"].as_str(), status["paid"].as_bool()), (Some("pending"), Some(false)));
+
+        // The first build lands, and the poller finds it through the reference
+        *landed.lock().unwrap() = json!({
+            "slot": 1,
+            "blockTime": null,
+            "transaction": [first["transaction"], "base64"],
+            "meta": {
+                "err": null,
+                "status": { "Ok": null },
+                "fee": 5000,
+                "preBalances": [],
+                "postBalances": [],
+                "preTokenBalances": [],
+                "postTokenBalances": [{
+                    "accountIndex": 2,
+                    "mint": mint,
+                    "owner": treasury.to_string(),
+                    "uiTokenAmount": { "amount": sent.amount.to_string(), "decimals": 6, "uiAmount": null, "uiAmountString": "" },
+                }],
+            },
+        });
+        let verified = payments::verify(&db, &reader, &rebuilt).await.unwrap();
+        assert_eq!((verified.status, verified.subscription.as_str()), (PaymentStatus::Verified, sent.subscription.as_str()));
+
+        let status: Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri(&status_uri).to_request()).await;
+        assert_eq!(status["paid"].as_bool(), Some(true));
+        assert_eq!(status["payment"]["subscription"].as_str(), Some(sent.subscription.as_str()));
+        let response = test::call_service(&app, post(&reference, user, "198.51.100.1")).await;
+        assert_eq!(response.status(), actix_web::http::StatusCode::CONFLICT);
+    }
+
+    #[actix_rt::test]
+    async fn percent_encodes_all_but_unreserved_characters() {
+        assert_eq!(percent_encode("AZaz09-._~"), "AZaz09-._~");
+        assert_eq!(
+            percent_encode("https://api.example.com/pay/subscribe/1?reference=abc&currency=USDC"),
+            "https%3A%2F%2Fapi.example.com%2Fpay%2Fsubscribe%2F1%3Freference%3Dabc%26currency%3DUSDC"
+        );
+        assert_eq!(percent_encode("a b+é"), "a%20b%2B%C3%A9");
+    }
+}
@@ -0,0 +1,322 @@
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpMessage, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::auth::session_wallet;
+use crate::blockchain::accounts::{Entitlement, SubscriptionAccount, SubscriptionReader};
+use crate::config::Config;
+use crate::plans::{self, PlanTier};
+use crate::storage::Database;
+
+/// Caller key -> (minute, requests in that minute).
+type RateWindows = Arc<Mutex<HashMap<String, (i64, u32)>>>;
+
+static RATE_WINDOWS: OnceLock<RateWindows> = OnceLock::new();
+
+/// The plan a request runs under, stored in the request extensions by `enforce`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CallerPlan {
+    pub wallet: Option<String>,
+    pub plan: PlanTier,
+}
+
+/// The accounts that grant their plan: those whose token payment was verified, or all
+/// of them with `REQUIRE_VERIFIED_PAYMENT=false`. Each is capped at what was paid for, the
+/// payment's tier for its period from verification, so renewals and plan changes sent
+/// on-chain without a verified payment grant nothing more.
+pub fn entitled_accounts(db: &Database, config: &Config, wallet: &Pubkey, accounts: Vec<SubscriptionAccount>) -> Vec<SubscriptionAccount> {
+    if !config.require_verified_payment {
+        return accounts;
+    }
+    match db.verified_payments(&wallet.to_string()) {
+        Ok(paid) => accounts.into_iter()
+            .filter_map(|account| {
+                let payment = paid.get(&account.address)?;
+                Some(SubscriptionAccount {
+                    plan_tier: account.plan_tier.min(payment.plan_tier),
+                    end_time: account.end_time.min(payment.updated_at + payment.period_days * 86_400),
+                    ..account
+                })
+            })
+            .collect(),
+        Err(e) => {
+            println!("⚠️ Could not load payments for {}, granting no plan: {}", wallet, e);
+            Vec::new()
+        },
+    }
+}
+
+/// Resolves the caller's plan from their session wallet's on-chain subscription.
+/// Anonymous callers, wallets without a current (paid) subscription and failed RPC
+/// lookups all fall back to tier 0.
+pub async fn caller_plan(req: &HttpRequest, db: &Database, reader: &SubscriptionReader, config: &Config) -> CallerPlan {
+    let wallet = session_wallet(req, db);
+    let tier = match wallet {
+        Some(wallet) => match reader.subscriptions_for(&wallet).await {
+            Ok(accounts) => {
+                let accounts = entitled_accounts(db, config, &wallet, accounts);
+                Entitlement::from_accounts(&accounts, Utc::now().timestamp()).plan_tier.unwrap_or(0)
+            },
+            Err(e) => {
+                println!("⚠️ Could not load subscriptions for {}, using tier 0: {}", wallet, e);
+                0
+            },
+        },
+        None => 0,
+    };
+
+    CallerPlan {
+        wallet: wallet.as_ref().map(Pubkey::to_string),
+        plan: plans::for_tier(&config.plan_tiers, tier).clone(),
+    }
+}
+
+/// Counts a request against the caller's per-minute budget. Returns the seconds
+/// until the window resets when the budget is spent.
+pub(super) fn check_rate_limit(key: &str, limit: u32) -> Result<(), i64> {
+    let now = Utc::now().timestamp();
+    let minute = now / 60;
+    let mut windows = RATE_WINDOWS.get_or_init(|| Arc::new(Mutex::new(HashMap::new()))).lock().unwrap();
+
+    if !windows.contains_key(key) {
+        windows.retain(|_, (m, _)| *m == minute);
+    }
+    let window = windows.entry(key.to_string()).or_insert((minute, 0));
+    if window.0 != minute {
+        *window = (minute, 0);
+    }
+    if window.1 >= limit {
+        return Err(60 - now % 60);
+    }
+    window.1 += 1;
+    Ok(())
+}
+
+/// The caller's address for anonymous rate limits. `X-Forwarded-For` is only believed on
+/// connections from `TRUSTED_PROXIES`, and then only up to the first hop they did not add:
+/// anything to the left of it was written by the client.
+pub fn client_ip(req: &HttpRequest, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    let peer = req.peer_addr()?.ip();
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer);
+    }
+
+    let hops: Vec<&str> = req.headers().get_all("X-Forwarded-For")
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .collect();
+    for hop in hops.into_iter().rev() {
+        match hop.trim().parse::<IpAddr>() {
+            Ok(ip) if trusted_proxies.contains(&ip) => continue,
+            Ok(ip) => return Some(ip),
+            Err(_) => break,
+        }
+    }
+    Some(peer)
+}
+
+/// The symbol a request is about, from `/alerts/{symbol}` or `?symbol=`.
+fn requested_symbol(req: &ServiceRequest) -> Option<String> {
+    if let Some(symbol) = req.path().strip_prefix("/alerts/") {
+        return Some(symbol.trim_matches('/').to_uppercase());
+    }
+    web::Query::<HashMap<String, String>>::from_query(req.query_string())
+        .ok()
+        .and_then(|query| query.get("symbol").map(|s| s.to_uppercase()))
+}
+
+// ========== PLAN ENFORCEMENT ==========
+/// Middleware for the signal routes: resolves the caller's plan, applies its rate
+/// limit, AI explanation access and symbol allowance, and hands the plan to
+/// handlers as `web::ReqData<CallerPlan>`.
+pub async fn enforce(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let (Some(config), Some(db), Some(reader)) = (
+        req.app_data::<web::Data<Config>>().cloned(),
+        req.app_data::<web::Data<Database>>().cloned(),
+        req.app_data::<web::Data<SubscriptionReader>>().cloned(),
+    ) else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+
+    let caller = caller_plan(req.request(), &db, &reader, &config).await;
+    let plan = &caller.plan;
+
+    let key = match &caller.wallet {
+        Some(wallet) => wallet.clone(),
+        None => match client_ip(req.request(), &config.trusted_proxies) {
+            Some(ip) => format!("ip:{}", ip),
+            None => "ip:unknown".to_string(),
+        },
+    };
+    if let Err(retry_after) = check_rate_limit(&key, plan.requests_per_minute) {
+        let response = HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", retry_after.to_string()))
+            .json(json!({
+                "error": "Rate limit exceeded",
+                "message": format!("The {} plan allows {} requests per minute", plan.name, plan.requests_per_minute),
+                "plan": plan.name,
+                "retry_after": retry_after,
+            }));
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    if req.path().starts_with("/explain") && !plan.ai_explanations {
+        let response = HttpResponse::PaymentRequired().json(json!({
+            "error": "Subscription required",
+            "message": format!("AI explanations are not included in the {} plan", plan.name),
+            "plan": plan.name,
+            "sign_in": "Sign in with a subscribed wallet via /auth/challenge and /auth/verify, then send the session as a Bearer token",
+        }));
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    if let Some(symbol) = requested_symbol(&req) {
+        if !plan.allows_symbol(&symbol) {
+            let response = HttpResponse::Forbidden().json(json!({
+                "error": "Symbol not included in plan",
+                "message": format!("The {} plan covers {}", plan.name, plan.symbols.join(", ")),
+                "symbol": symbol,
+                "plan": plan.name,
+            }));
+            return Ok(req.into_response(response).map_into_right_body());
+        }
+    }
+
+    req.extensions_mut().insert(caller);
+    Ok(next.call(req).await?.map_into_left_body())
+}
+
+pub async fn list_plans(config: web::Data<Config>, caller: web::ReqData<CallerPlan>) -> impl Responder {
+    HttpResponse::Ok().json(json!({
+        "plans": config.plan_tiers,
+        "current": caller.into_inner(),
+        "timestamp": Utc::now().timestamp()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::Providers;
+    use crate::routes::signals;
+    use actix_web::{middleware, test, App};
+
+    /// Entitlement comes only from a session proven by a wallet signature. Naming a
+    /// subscriber's public address, or an unknown token, leaves the caller on tier 0.
+    #[actix_rt::test]
+    async fn unsigned_wallet_claims_get_the_free_plan() {
+        let config = Config::from_env().unwrap();
+        let reader = SubscriptionReader::from_config(&config).unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(Providers::fixture()))
+                .app_data(web::Data::new(Database::in_memory().unwrap()))
+                .app_data(web::Data::new(reader))
+                .wrap(middleware::from_fn(enforce))
+                .route("/explain-all-signals", web::get().to(signals::explain_all_signals)),
+        ).await;
+        let subscriber = Pubkey::new_unique().to_string();
+
+        let requests = [
+            test::TestRequest::get().uri("/explain-all-signals").insert_header(("X-Wallet-Address", subscriber.as_str())),
+            test::TestRequest::get().uri(&format!("/explain-all-signals?wallet={}", subscriber)),
+            test::TestRequest::get().uri("/explain-all-signals").insert_header(("Authorization", "Bearer not-a-session")),
+        ];
+        for request in requests {
+            let response = test::call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), actix_web::http::StatusCode::PAYMENT_REQUIRED);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn forwarded_addresses_are_only_believed_from_trusted_proxies() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let request = |peer: &str| test::TestRequest::default()
+            .peer_addr(format!("{}:443", peer).parse().unwrap())
+            .insert_header(("X-Forwarded-For", "1.2.3.4, 203.0.113.7"))
+            .to_http_request();
+
+        // A direct client cannot pick its own key
+        assert_eq!(client_ip(&request("198.51.100.9"), &[proxy]), Some("198.51.100.9".parse().unwrap()));
+        // Behind the proxy, the hop it appended wins over whatever the client prepended
+        assert_eq!(client_ip(&request("10.0.0.1"), &[proxy]), Some("203.0.113.7".parse().unwrap()));
+        assert_eq!(client_ip(&request("10.0.0.1"), &[]), Some(proxy));
+    }
+
+    #[actix_rt::test]
+    async fn limits_each_key_per_minute() {
+        // Stay clear of a minute boundary, which would reset the window mid-test
+        if Utc::now().timestamp() % 60 > 57 {
+            tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+        }
+
+        for _ in 0..3 {
+            assert_eq!(check_rate_limit("test-limit:a", 3), Ok(()));
+        }
+        let retry_after = check_rate_limit("test-limit:a", 3).unwrap_err();
+        assert!((1..=60).contains(&retry_after));
+        assert_eq!(check_rate_limit("test-limit:b", 3), Ok(()));
+        assert!(check_rate_limit("test-limit:c", 0).is_err());
+    }
+
+    #[actix_rt::test]
+    async fn entitlement_is_capped_at_the_verified_payment() {
+        use crate::storage::payments::{PaymentStatus, SubscriptionPayment};
+
+        let db = Database::in_memory().unwrap();
+        let mut config = Config::from_env().unwrap();
+        config.require_verified_payment = true;
+        let (wallet, verified_at) = (Pubkey::new_unique(), 1_700_000_000);
+        let account = |plan_tier, end_time| SubscriptionAccount {
+            address: Pubkey::new_unique().to_string(),
+            user: wallet.to_string(),
+            plan_tier,
+            start_time: verified_at,
+            end_time,
+            is_active: true,
+        };
+
+        // Upgraded and renewed on-chain after paying for 30 days of tier 1
+        let paid = account(3, verified_at + 90 * 86_400);
+        db.insert_payment(&SubscriptionPayment {
+            subscription: paid.address.clone(),
+            user: wallet.to_string(),
+            plan_tier: 1,
+            period_days: 30,
+            currency: "USDC".to_string(),
+            mint: "mint".to_string(),
+            treasury: "treasury".to_string(),
+            amount: 29_000_000,
+            price_usd: 29.0,
+            status: PaymentStatus::Pending,
+            signature: None,
+            error: None,
+            created_at: verified_at - 60,
+            updated_at: verified_at - 60,
+            reference: None,
+        }).unwrap();
+        db.set_payment_status(&paid.address, PaymentStatus::Verified, Some("signature"), None, verified_at).unwrap();
+        let unpaid = account(2, verified_at + 30 * 86_400);
+
+        let entitled = entitled_accounts(&db, &config, &wallet, vec![paid.clone(), unpaid.clone()]);
+        assert_eq!(entitled.len(), 1);
+        assert_eq!((entitled[0].plan_tier, entitled[0].end_time), (1, verified_at + 30 * 86_400));
+        let entitlement = Entitlement::from_accounts(&entitled, verified_at + 31 * 86_400);
+        assert_eq!((entitlement.active, entitlement.plan_tier), (false, None));
+
+        config.require_verified_payment = false;
+        assert_eq!(entitled_accounts(&db, &config, &wallet, vec![paid, unpaid]).len(), 2);
+    }
+}
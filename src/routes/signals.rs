@@ -1,5 +1,5 @@
-use actix_web::{get, HttpResponse, Responder, web};
-use serde::{Deserialize, Serialize};
+use actix_web::{get, HttpRequest, HttpResponse, Responder, web};
+use serde::Deserialize;
 use serde_json::json;
 use chrono::Utc;
 use std::sync::{Arc, Mutex};
@@ -8,173 +8,123 @@ use std::time::{SystemTime, Duration};
 
 // Import AI module
 use super::ai_explanation::{AIExplainer, SignalExplanation};
+use super::plans::CallerPlan;
+use super::tradingview::{self, TradingViewWebhook};
+use super::webhook_auth::{self, BodyCredentials, WebhookAuthQuery};
+use crate::config::Config;
+use crate::events;
+use crate::market_data::price_cache::{self, PriceData};
+use crate::market_data::stream;
+use crate::providers::Providers;
+use crate::symbols as registry;
+use crate::signals::aggregator::{AggregatedSignal, IndicatorResult};
+use crate::signals::alerts as signal_alerts;
+use crate::signals::engine;
+use crate::storage::Database;
+use crate::storage::alerts::{AlertQuery, TradingViewAlert};
 
-// Import AI module
+type Cache<T> = std::sync::OnceLock<Arc<Mutex<HashMap<String, (T, SystemTime)>>>>;
 
-// Store to keep alerts in memory
-static ALERTS: std::sync::OnceLock<Arc<Mutex<Vec<TradingViewAlert>>>> = std::sync::OnceLock::new();
-static PRICE_CACHE: std::sync::OnceLock<Arc<Mutex<HashMap<String, (PriceData, SystemTime)>>>> = std::sync::OnceLock::new();
-
-#[derive(Debug, Serialize, Clone, Deserialize)]
-pub struct PriceData {
-    pub symbol: String,
-    pub price: f64,
-    pub timestamp: i64,
-    pub change_24h: f64,
-    pub market_cap: Option<f64>,
-    pub volume_24h: Option<f64>,
-}
+static HISTORY_CACHE: Cache<Vec<f64>> = std::sync::OnceLock::new();
+/// Latest `/signals` entry per symbol, served until it is older than the caller's plan refresh interval.
+static SIGNAL_SNAPSHOTS: Cache<serde_json::Value> = std::sync::OnceLock::new();
+static LAST_SIGNALS: std::sync::OnceLock<Arc<Mutex<HashMap<String, String>>>> = std::sync::OnceLock::new();
 
-#[derive(Debug, Serialize, Clone, Deserialize)]
-pub struct TradingViewAlert {
-    pub symbol: String,
-    pub price: f64,
-    pub alert_name: String,
-    pub timestamp: i64,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct TradingViewWebhook {
-    pub symbol: String,
-    pub price: f64,
-    pub alert_name: Option<String>,
-}
+/// How often alerts and webhook audit entries past retention are deleted.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(3600);
 
 // ========== HEALTH CHECK ==========
 #[get("/health")]
 pub async fn health_check() -> impl Responder {
+    let price_stream = stream::health();
+    let status = if price_stream.enabled && !price_stream.connected { "degraded" } else { "healthy" };
+    
     HttpResponse::Ok().json(json!({
-        "status": "healthy",
+        "status": status,
+        "price_stream": price_stream,
         "service": "trading-signals-backend",
         "timestamp": Utc::now().timestamp(),
         "version": "1.0.0",
-        "supported_coins": ["BTC", "ETH", "SOL", "PAXG"],
+        "supported_coins": registry::symbols(),
         "endpoints": [
             "/health",
             "/prices", 
             "/signals",
+            "/candles/{symbol}",
             "/explain-signal",
             "/explain-all-signals",
             "/tradingview-webhook",
             "/tradingview-alerts",
-            "/alerts/{symbol}"
+            "/alerts/{symbol}",
+            "/ws",
+            "/stream/signals",
+            "/stream/alerts"
         ]
     }))
 }
 
 // ========== REAL PRICE FETCHING ==========
-fn get_coingecko_id(symbol: &str) -> Option<&'static str> {
-    match symbol.to_uppercase().as_str() {
-        "BTC" => Some("bitcoin"),
-        "ETH" => Some("ethereum"),
-        "SOL" => Some("solana"),
-        "PAXG" => Some("pax-gold"),
-        _ => None,
-    }
-}
-
-async fn fetch_live_price(symbol: &str) -> Result<PriceData, String> {
+async fn fetch_live_price(providers: &Providers, symbol: &str) -> Result<PriceData, String> {
     let symbol_upper = symbol.to_uppercase();
     
-    // Check cache
-    if let Some(cache) = PRICE_CACHE.get() {
-        let cache_lock = cache.lock().unwrap();
-        if let Some((data, timestamp)) = cache_lock.get(&symbol_upper) {
-            if SystemTime::now().duration_since(*timestamp)
-                .unwrap_or(Duration::from_secs(0))
-                .as_secs() < 30 {
-                return Ok(data.clone());
-            }
-        }
+    // Check cache (kept fresh by the Binance stream for streamed pairs)
+    if let Some(data) = price_cache::get_fresh(&symbol_upper, Duration::from_secs(30)) {
+        return Ok(data);
     }
     
-    let coin_id = get_coingecko_id(&symbol_upper)
-        .ok_or_else(|| format!("Unknown symbol: {}", symbol))?;
-    
-    let url = format!("https://api.coingecko.com/api/v3/simple/price?ids={}&vs_currencies=usd&include_24hr_change=true&include_market_cap=true&include_24hr_vol=true", 
-        coin_id);
-    
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
-        .map_err(|e| format!("Client error: {}", e))?;
-    
-    let response = client.get(&url)
-        .header("User-Agent", "TradingSignalsBot/1.0")
-        .send()
-        .await
-        .map_err(|e| format!("Network error: {}", e))?;
-    
-    if !response.status().is_success() {
-        return Err(format!("API error: {}", response.status()));
-    }
-    
-    let data: serde_json::Value = response.json()
-        .await
-        .map_err(|e| format!("JSON error: {}", e))?;
-    
-    let coin_data = data.get(coin_id)
-        .ok_or_else(|| format!("No data for {}", symbol))?;
-    
-    let price = coin_data.get("usd")
-        .and_then(|v| v.as_f64())
-        .ok_or("No price in response")?;
-    
-    let change_24h = coin_data.get("usd_24h_change")
-        .and_then(|v| v.as_f64())
-        .unwrap_or(0.0);
-    
-    let market_cap = coin_data.get("usd_market_cap")
-        .and_then(|v| v.as_f64());
-    
-    let volume_24h = coin_data.get("usd_24h_vol")
-        .and_then(|v| v.as_f64());
-    
-    let price_data = PriceData {
-        symbol: symbol_upper.clone(),
-        price,
-        timestamp: Utc::now().timestamp(),
-        change_24h,
-        market_cap,
-        volume_24h,
+    let price_data = match providers.prices.fetch_price(&symbol_upper).await {
+        Ok(price_data) => price_data,
+        // Serve the last known price, flagged stale, rather than failing outright
+        Err(e) => return price_cache::get_stale(&symbol_upper).ok_or(e),
     };
     
     // Update cache
-    if let Some(cache) = PRICE_CACHE.get() {
-        let mut cache_lock = cache.lock().unwrap();
-        cache_lock.insert(symbol_upper, (price_data.clone(), SystemTime::now()));
-    } else {
-        let mut new_cache = HashMap::new();
-        new_cache.insert(symbol_upper, (price_data.clone(), SystemTime::now()));
-        let _ = PRICE_CACHE.set(Arc::new(Mutex::new(new_cache)));
-    }
+    price_cache::insert(price_data.clone());
     
     Ok(price_data)
 }
 
+/// Hourly closing prices from the history provider, oldest first. Cached for 5 minutes.
+async fn fetch_price_history(providers: &Providers, symbol: &str) -> Result<Vec<f64>, String> {
+    let symbol_upper = symbol.to_uppercase();
+
+    let cache = HISTORY_CACHE.get_or_init(|| Arc::new(Mutex::new(HashMap::new())));
+    if let Some((history, timestamp)) = cache.lock().unwrap().get(&symbol_upper) {
+        if SystemTime::now().duration_since(*timestamp)
+            .unwrap_or(Duration::from_secs(0))
+            .as_secs() < 300 {
+            return Ok(history.clone());
+        }
+    }
+
+    let history = providers.history.fetch_history(&symbol_upper).await?;
+
+    cache.lock().unwrap().insert(symbol_upper, (history.clone(), SystemTime::now()));
+
+    Ok(history)
+}
+
 #[get("/prices")]
-pub async fn get_prices() -> impl Responder {
-    println!("🚀 Fetching live prices from CoinGecko...");
+pub async fn get_prices(providers: web::Data<Providers>, caller: web::ReqData<CallerPlan>) -> impl Responder {
+    println!("🚀 Fetching live prices from {}...", providers.prices.name());
     
-    let symbols = vec!["BTC", "ETH", "SOL", "PAXG"];
+    let symbols = plan_symbols(&caller);
     let mut prices = Vec::new();
+    let mut unavailable = Vec::new();
     
     for symbol in symbols {
-        match fetch_live_price(symbol).await {
+        match fetch_live_price(&providers, &symbol).await {
             Ok(price_data) => {
-                println!("✅ {}: ${:.2} ({:.2}%)", symbol, price_data.price, price_data.change_24h);
+                println!("✅ {}: ${:.2} ({:.2}%) via {}{}", symbol, price_data.price, price_data.change_24h,
+                    price_data.sources.join(","), if price_data.stale { " [stale]" } else { "" });
                 prices.push(price_data);
             },
             Err(e) => {
                 println!("❌ Failed {}: {}", symbol, e);
-                prices.push(PriceData {
-                    symbol: symbol.to_string(),
-                    price: 0.0,
-                    timestamp: Utc::now().timestamp(),
-                    change_24h: 0.0,
-                    market_cap: None,
-                    volume_24h: None,
-                });
+                unavailable.push(json!({
+                    "symbol": symbol,
+                    "error": e,
+                }));
             }
         }
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -183,53 +133,201 @@ pub async fn get_prices() -> impl Responder {
     HttpResponse::Ok().json(json!({
         "prices": prices,
         "count": prices.len(),
+        "unavailable": unavailable,
         "timestamp": Utc::now().timestamp(),
-        "source": "CoinGecko API"
+        "source": providers.prices.name()
     }))
 }
 
 // ========== SIGNAL GENERATION ==========
+struct SymbolSignal {
+    signal: String,
+    confidence: f64,
+    method: &'static str,
+    analysis: Option<AggregatedSignal>,
+}
+
+impl SymbolSignal {
+    /// Names of the indicators and alerts behind the signal.
+    fn sources(&self) -> Vec<String> {
+        match &self.analysis {
+            Some(analysis) => analysis.components.iter().map(|c| c.name.clone()).collect(),
+            None => vec![self.method.to_string()],
+        }
+    }
+}
+
+/// Buy/sell TradingView alerts for `symbol` still inside the signal TTL, as aggregator components.
+fn alert_components(symbol: &str, config: &Config, db: &Database) -> Vec<IndicatorResult> {
+    let now = Utc::now().timestamp();
+    let query = AlertQuery {
+        symbol: Some(symbol.to_string()),
+        from: Some(now - config.alert_signal_ttl_seconds),
+        limit: Some(AlertQuery::MAX_LIMIT),
+        ..AlertQuery::default()
+    };
+    
+    match db.query_alerts(&query) {
+        Ok((alerts, _)) => signal_alerts::components(
+            &alerts,
+            now,
+            config.alert_signal_ttl_seconds,
+            config.alert_signal_weight,
+            config.alert_signal_max_weight,
+        ),
+        Err(e) => {
+            println!("⚠️ Could not load alerts for {}: {}", symbol, e);
+            Vec::new()
+        },
+    }
+}
+
+/// Runs the EMA/RSI/MACD engine over the symbol's price history and blends in recent
+/// TradingView alerts, falling back to the 24h change heuristic when neither is available.
+async fn compute_signal(price_data: &PriceData, config: &Config, providers: &Providers, db: &Database) -> SymbolSignal {
+    let history = fetch_price_history(providers, &price_data.symbol).await
+        .map_err(|e| println!("⚠️ No price history for {}: {}", price_data.symbol, e))
+        .ok();
+    
+    // Generators are not Send, so the aggregator is only built once nothing else is awaited
+    let aggregator = engine::build_aggregator(&price_data.symbol, config);
+    let alerts = alert_components(&price_data.symbol, config, db);
+    let indicators = history.and_then(|mut history| {
+        history.push(price_data.price);
+        aggregator.aggregate(&price_data.symbol, &history)
+            .map_err(|e| println!("⚠️ {}: {}", price_data.symbol, e))
+            .ok()
+    });
+    
+    let (components, method) = match indicators {
+        Some(analysis) if alerts.is_empty() => (analysis.components, "indicators"),
+        Some(analysis) => (analysis.components.into_iter().chain(alerts).collect(), "indicators+alerts"),
+        None if !alerts.is_empty() => (alerts, "alerts"),
+        None => {
+            let (signal, confidence) = generate_signal(price_data);
+            return SymbolSignal {
+                signal,
+                confidence,
+                method: "change_24h",
+                analysis: None,
+            };
+        },
+    };
+    
+    let analysis = aggregator.merge(&price_data.symbol, price_data.price, components);
+    SymbolSignal {
+        signal: analysis.signal.signal_type.as_str().to_string(),
+        confidence: (analysis.signal.confidence / 100.0).min(1.0),
+        method,
+        analysis: Some(analysis),
+    }
+}
+
+/// One `/signals` entry. Publishes on `signals:<SYMBOL>` when the signal type changes.
+async fn build_signal_entry(symbol: &str, config: &Config, providers: &Providers, db: &Database) -> serde_json::Value {
+    match fetch_live_price(providers, symbol).await {
+        Ok(price_data) => {
+            let computed = compute_signal(&price_data, config, providers, db).await;
+            
+            let entry = json!({
+                "symbol": symbol,
+                "price": price_data.price,
+                "change_24h": price_data.change_24h,
+                "signal": computed.signal,
+                "confidence": (computed.confidence * 100.0).round() / 100.0,
+                "action": get_action_from_signal(&computed.signal),
+                "method": computed.method,
+                "agreement": computed.analysis.as_ref().map(|a| (a.agreement * 100.0).round() / 100.0),
+                "sources": computed.sources(),
+                "indicators": computed.analysis.map(|a| a.components).unwrap_or_default(),
+                "timestamp": Utc::now().timestamp(),
+            });
+            
+            let previous = LAST_SIGNALS.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
+                .lock()
+                .unwrap()
+                .insert(symbol.to_string(), computed.signal.clone());
+            if previous.as_ref() != Some(&computed.signal) {
+                events::publish("signals", symbol, "signal_changed", entry.clone());
+            }
+            SIGNAL_SNAPSHOTS.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
+                .lock()
+                .unwrap()
+                .insert(symbol.to_string(), (entry.clone(), SystemTime::now()));
+            
+            entry
+        },
+        Err(e) => json!({
+            "symbol": symbol,
+            "error": e,
+            "signal": "error",
+            "confidence": 0.0,
+            "timestamp": Utc::now().timestamp(),
+        }),
+    }
+}
+
+/// The last `/signals` entry for `symbol` if it is younger than `max_age`.
+fn signal_snapshot(symbol: &str, max_age: Duration) -> Option<serde_json::Value> {
+    let snapshots = SIGNAL_SNAPSHOTS.get()?.lock().unwrap();
+    let (entry, computed_at) = snapshots.get(symbol)?;
+    let age = SystemTime::now().duration_since(*computed_at).unwrap_or_default();
+    (age < max_age).then(|| entry.clone())
+}
+
+/// Registered symbols the caller's plan covers.
+fn plan_symbols(caller: &CallerPlan) -> Vec<String> {
+    registry::symbols()
+        .into_iter()
+        .filter(|symbol| caller.plan.allows_symbol(symbol))
+        .collect()
+}
+
+/// Entries are recomputed only once they are older than the plan's `signal_refresh_seconds`.
 #[get("/signals")]
-pub async fn get_signals() -> impl Responder {
+pub async fn get_signals(
+    config: web::Data<Config>,
+    providers: web::Data<Providers>,
+    db: web::Data<Database>,
+    caller: web::ReqData<CallerPlan>,
+) -> impl Responder {
     println!("📈 Generating trading signals...");
     
-    let symbols = vec!["BTC", "ETH", "SOL", "PAXG"];
+    let max_age = Duration::from_secs(caller.plan.signal_refresh_seconds);
     let mut signals = Vec::new();
     
-    for symbol in symbols {
-        match fetch_live_price(symbol).await {
-            Ok(price_data) => {
-                let (signal, confidence) = generate_signal(&price_data);
-                
-                signals.push(json!({
-                    "symbol": symbol,
-                    "price": price_data.price,
-                    "change_24h": price_data.change_24h,
-                    "signal": signal,
-                    "confidence": (confidence * 100.0).round() / 100.0,
-                    "action": get_action_from_signal(&signal),
-                    "timestamp": Utc::now().timestamp(),
-                }));
-            },
-            Err(e) => {
-                signals.push(json!({
-                    "symbol": symbol,
-                    "error": e,
-                    "signal": "error",
-                    "confidence": 0.0,
-                    "timestamp": Utc::now().timestamp(),
-                }));
-            }
-        }
+    for symbol in plan_symbols(&caller) {
+        let entry = match signal_snapshot(&symbol, max_age) {
+            Some(entry) => entry,
+            None => build_signal_entry(&symbol, &config, &providers, &db).await,
+        };
+        signals.push(entry);
     }
     
     HttpResponse::Ok().json(json!({
         "signals": signals,
         "count": signals.len(),
+        "plan": caller.plan.name,
+        "refresh_seconds": caller.plan.signal_refresh_seconds,
         "timestamp": Utc::now().timestamp(),
     }))
 }
 
+/// Recomputes signals in the background so subscribers hear about signal
+/// changes without anyone polling `/signals`.
+pub fn spawn_signal_monitor(config: Config, providers: web::Data<Providers>, db: web::Data<Database>) {
+    let interval = Duration::from_secs(config.update_interval_seconds.unwrap_or(60));
+    
+    tokio::spawn(async move {
+        loop {
+            for symbol in registry::symbols() {
+                build_signal_entry(&symbol, &config, &providers, &db).await;
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
 fn generate_signal(price_data: &PriceData) -> (String, f64) {
     match price_data.change_24h {
         c if c > 10.0 => ("strong_sell".to_string(), 0.85),
@@ -253,46 +351,99 @@ fn get_action_from_signal(signal: &str) -> &'static str {
 }
 
 // ========== TRADINGVIEW WEBHOOK ==========
-pub async fn tradingview_webhook(data: web::Json<TradingViewWebhook>) -> impl Responder {
+pub async fn tradingview_webhook(
+    req: HttpRequest,
+    body: web::Bytes,
+    query: web::Query<WebhookAuthQuery>,
+    config: web::Data<Config>,
+    providers: web::Data<Providers>,
+    db: web::Data<Database>,
+) -> impl Responder {
     println!("📈 TradingView webhook received!");
     
-    let symbol = clean_symbol(&data.symbol);
-    let valid_symbols = ["BTC", "ETH", "SOL", "PAXG"];
+    // Authenticate before validating so unauthenticated callers learn nothing about the schema
+    let payload = tradingview::parse_body(&body);
+    let credentials = BodyCredentials::from_json(&payload);
+    let source = match webhook_auth::authenticate(&req, &query, &body, &credentials, &config) {
+        Ok(source) => source,
+        Err(rejection) => {
+            webhook_auth::audit_rejection(&db, &req, &rejection, &config);
+            return HttpResponse::Unauthorized().json(json!({
+                "status": "error",
+                "message": rejection.reason,
+            }));
+        },
+    };
+    
+    let data = match TradingViewWebhook::from_value(&payload) {
+        Ok(data) => data,
+        Err(e) => return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "field": e.field,
+            "message": format!("Invalid webhook payload: {}", e),
+        })),
+    };
+    
+    // `{{exchange}}` and `{{ticker}}` arrive separately; try them as one TradingView ticker first
+    let symbol = data.exchange.as_ref()
+        .filter(|_| !data.symbol.contains(':'))
+        .and_then(|exchange| registry::find_by_tradingview_ticker(&format!("{}:{}", exchange, data.symbol)))
+        .or_else(|| registry::find_by_tradingview_ticker(&data.symbol))
+        .unwrap_or_else(|| clean_symbol(&data.symbol));
     
-    if !valid_symbols.contains(&symbol.as_str()) {
+    if !registry::is_supported(&symbol) {
         return HttpResponse::BadRequest().json(json!({
             "status": "error",
-            "message": format!("Unsupported symbol: {}. Only BTC, ETH, SOL, PAXG.", symbol),
+            "field": "symbol",
+            "message": format!("Unsupported symbol: {}. Only {}.", symbol, registry::symbols().join(", ")),
         }));
     }
     
     let alert = TradingViewAlert {
         symbol: symbol.clone(),
         price: data.price,
-        alert_name: data.alert_name.clone().unwrap_or_else(|| "Unknown".to_string()),
+        alert_name: data.alert_name.unwrap_or_else(|| "Unknown".to_string()),
         timestamp: Utc::now().timestamp(),
+        action: data.action,
+        position_size: data.position_size,
+        interval: data.interval,
+        exchange: data.exchange,
+        bar_time: data.bar_time,
+        extra: data.extra,
     };
     
-    let alerts_store = ALERTS.get_or_init(|| Arc::new(Mutex::new(Vec::new())));
-    let mut alerts = alerts_store.lock().unwrap();
-    alerts.push(alert.clone());
+    if let Err(e) = db.insert_alert(&alert) {
+        println!("❌ Failed to store alert: {}", e);
+        return HttpResponse::InternalServerError().json(json!({
+            "status": "error",
+            "message": "Failed to store alert",
+        }));
+    }
+    if let Err(e) = apply_retention(&db, &config) {
+        println!("⚠️ Alert retention cleanup failed: {}", e);
+    }
+    
+    events::publish("alerts", &alert.symbol, "alert", json!(alert));
     
-    // FIXED: Store length in variable before using it
-    let alerts_len = alerts.len();
-    if alerts_len > 50 {
-        alerts.drain(0..alerts_len - 50);
+    // Buy/sell alerts feed into the symbol's signal; recompute so subscribers see the change now
+    if alert.action.is_some() {
+        let (config, providers, db) = (config.clone(), providers.clone(), db.clone());
+        tokio::spawn(async move {
+            build_signal_entry(&symbol, &config, &providers, &db).await;
+        });
     }
     
     HttpResponse::Ok().json(json!({
         "status": "success",
         "alert": alert,
+        "source": source,
         "timestamp": Utc::now().timestamp()
     }))
 }
 
 fn clean_symbol(raw_symbol: &str) -> String {
     let cleaned = if raw_symbol.contains(":") {
-        raw_symbol.split(':').last().unwrap_or(raw_symbol)
+        raw_symbol.split(':').next_back().unwrap_or(raw_symbol)
             .replace("USDT", "")
             .replace("USD", "")
     } else {
@@ -306,60 +457,106 @@ fn clean_symbol(raw_symbol: &str) -> String {
 }
 
 // ========== ALERTS ENDPOINTS ==========
-#[get("/tradingview-alerts")]
-pub async fn get_tradingview_alerts() -> impl Responder {
-    let alerts = ALERTS.get()
-        .map(|store| store.lock().unwrap().clone())
-        .unwrap_or_else(Vec::new);
+/// Deletes alerts and webhook audit entries older than their configured retention
+/// windows, returning how many alerts were removed. A zero retention keeps everything.
+pub fn apply_retention(db: &Database, config: &Config) -> Result<usize, String> {
+    let now = Utc::now().timestamp();
+    if config.webhook_audit_retention_days > 0 {
+        db.prune_audit_entries(now - config.webhook_audit_retention_days as i64 * 86_400)?;
+    }
+    if config.alert_retention_days == 0 {
+        return Ok(0);
+    }
+    db.prune_alerts(now - config.alert_retention_days as i64 * 86_400)
+}
+
+/// Applies retention at startup and then every `RETENTION_INTERVAL`.
+pub fn spawn_retention(config: Config, db: web::Data<Database>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(RETENTION_INTERVAL);
+        loop {
+            ticker.tick().await;
+            match apply_retention(&db, &config) {
+                Ok(removed) if removed > 0 => println!("🧹 Removed {} alerts past retention", removed),
+                Ok(_) => {},
+                Err(e) => println!("⚠️ Retention cleanup failed: {}", e),
+            }
+        }
+    });
+}
+
+fn alerts_response(db: &Database, query: &AlertQuery) -> HttpResponse {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return HttpResponse::BadRequest().json(json!({
+                "error": "Invalid time range",
+                "message": "`from` must not be after `to`",
+            }));
+        }
+    }
     
-    HttpResponse::Ok().json(json!({
-        "alerts": alerts,
-        "count": alerts.len(),
-        "timestamp": Utc::now().timestamp()
-    }))
+    match db.query_alerts(query) {
+        Ok((alerts, total)) => HttpResponse::Ok().json(json!({
+            "symbol": query.symbol,
+            "alerts": alerts,
+            "count": alerts.len(),
+            "total": total,
+            "limit": query.limit(),
+            "offset": query.offset(),
+            "timestamp": Utc::now().timestamp()
+        })),
+        Err(e) => {
+            println!("❌ Failed to load alerts: {}", e);
+            HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to load alerts",
+            }))
+        },
+    }
+}
+
+/// Supports `?from=&to=` (Unix seconds), `alert_name=`, `symbol=`, `limit=` and `offset=`.
+#[get("/tradingview-alerts")]
+pub async fn get_tradingview_alerts(
+    query: web::Query<AlertQuery>,
+    db: web::Data<Database>,
+    caller: web::ReqData<CallerPlan>,
+) -> impl Responder {
+    let mut query = query.into_inner();
+    query.symbols = caller.plan.symbols.clone();
+    
+    alerts_response(&db, &query)
 }
 
 #[get("/alerts/{symbol}")]
-pub async fn get_symbol_alerts(symbol: web::Path<String>) -> impl Responder {
-    let symbol_str = symbol.into_inner().to_uppercase();
-    
-    let alerts = ALERTS.get()
-        .map(|store| {
-            let store_lock = store.lock().unwrap();
-            store_lock.iter()
-                .filter(|a| a.symbol == symbol_str)
-                .cloned()
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_else(Vec::new);
-    
-    HttpResponse::Ok().json(json!({
-        "symbol": symbol_str,
-        "alerts": alerts,
-        "count": alerts.len(),
-        "timestamp": Utc::now().timestamp()
-    }))
+pub async fn get_symbol_alerts(
+    symbol: web::Path<String>,
+    query: web::Query<AlertQuery>,
+    db: web::Data<Database>,
+) -> impl Responder {
+    let mut query = query.into_inner();
+    query.symbol = Some(symbol.into_inner().to_uppercase());
+    
+    alerts_response(&db, &query)
 }
 
 // ========== UTILITY ENDPOINTS ==========
-pub async fn clear_alerts() -> impl Responder {
-    if let Some(alerts_store) = ALERTS.get() {
-        let mut alerts = alerts_store.lock().unwrap();
-        alerts.clear();
+pub async fn clear_alerts(db: web::Data<Database>) -> impl Responder {
+    match db.clear_alerts() {
+        Ok(removed) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "message": "All alerts cleared",
+            "removed": removed,
+            "timestamp": Utc::now().timestamp()
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "status": "error",
+            "message": e,
+        })),
     }
-    
-    HttpResponse::Ok().json(json!({
-        "status": "success",
-        "message": "All alerts cleared",
-        "timestamp": Utc::now().timestamp()
-    }))
 }
 
 pub async fn clear_cache() -> impl Responder {
-    if let Some(cache) = PRICE_CACHE.get() {
-        let mut cache_lock = cache.lock().unwrap();
-        cache_lock.clear();
-    }
+    price_cache::clear();
     
     HttpResponse::Ok().json(json!({
         "status": "success",
@@ -370,31 +567,27 @@ pub async fn clear_cache() -> impl Responder {
 
 // ========== CACHE STATS ==========
 #[get("/cache-stats")]
-pub async fn get_cache_stats() -> impl Responder {
-    let cache_info = if let Some(cache) = PRICE_CACHE.get() {
-        let cache_lock = cache.lock().unwrap();
-        json!({
-            "entries": cache_lock.len(),
-            "symbols": cache_lock.keys().cloned().collect::<Vec<_>>()
-        })
-    } else {
-        json!({"entries": 0, "symbols": []})
-    };
-    
-    let alerts_info = if let Some(alerts_store) = ALERTS.get() {
-        let alerts = alerts_store.lock().unwrap();
-        json!({
-            "total_alerts": alerts.len()
-        })
-    } else {
-        json!({"total_alerts": 0})
-    };
+pub async fn get_cache_stats(
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+) -> impl Responder {
+    let cached_symbols = price_cache::symbols();
+    let cache_info = json!({
+        "entries": cached_symbols.len(),
+        "symbols": cached_symbols
+    });
+    
+    let alerts_info = json!({
+        "total_alerts": db.count_alerts().unwrap_or(0),
+        "database": config.database_path,
+        "retention_days": config.alert_retention_days
+    });
     
     HttpResponse::Ok().json(json!({
         "price_cache": cache_info,
         "alerts_store": alerts_info,
         "timestamp": Utc::now().timestamp(),
-        "supported_coins": ["BTC", "ETH", "SOL", "PAXG"]
+        "supported_coins": registry::symbols()
     }))
 }
 
@@ -405,7 +598,12 @@ pub struct ExplainQuery {
 }
 
 // Regular async function (NOT #[get] macro)
-pub async fn explain_signal(query: web::Query<ExplainQuery>) -> impl Responder {
+pub async fn explain_signal(
+    query: web::Query<ExplainQuery>,
+    config: web::Data<Config>,
+    providers: web::Data<Providers>,
+    db: web::Data<Database>,
+) -> impl Responder {
     let explainer = AIExplainer::new();
     
     // Get symbol from query or default to BTC
@@ -413,28 +611,28 @@ pub async fn explain_signal(query: web::Query<ExplainQuery>) -> impl Responder {
     let symbol_upper = requested_symbol.to_uppercase();
     
     // Validate symbol
-    let valid_symbols = ["BTC", "ETH", "SOL", "PAXG"];
-    if !valid_symbols.contains(&symbol_upper.as_str()) {
+    if !registry::is_supported(&symbol_upper) {
         return HttpResponse::BadRequest().json(json!({
             "error": "Unsupported symbol",
-            "message": format!("Only {} are supported", valid_symbols.join(", ")),
+            "message": format!("Only {} are supported", registry::symbols().join(", ")),
             "symbol": symbol_upper
         }));
     }
     
     // Get live price data
-    match fetch_live_price(&symbol_upper).await {
+    match fetch_live_price(&providers, &symbol_upper).await {
         Ok(price_data) => {
             // Generate signal from price data
-            let (signal, _) = generate_signal(&price_data);
+            let computed = compute_signal(&price_data, &config, &providers, &db).await;
             
             // Create AI explanation
-            let explanation = explainer.explain_signal(
+            let mut explanation = explainer.explain_signal(
                 &symbol_upper,
-                &signal,
+                &computed.signal,
                 price_data.price,
                 price_data.change_24h,
             ).await;
+            explanation.sources = computed.sources();
             
             HttpResponse::Ok().json(explanation)
         },
@@ -449,29 +647,35 @@ pub async fn explain_signal(query: web::Query<ExplainQuery>) -> impl Responder {
 }
 
 // Regular async function (NOT #[get] macro)
-pub async fn explain_all_signals() -> impl Responder {
+pub async fn explain_all_signals(
+    config: web::Data<Config>,
+    providers: web::Data<Providers>,
+    db: web::Data<Database>,
+    caller: web::ReqData<CallerPlan>,
+) -> impl Responder {
     let explainer = AIExplainer::new();
-    let symbols = vec!["BTC", "ETH", "SOL", "PAXG"];
+    let symbols = plan_symbols(&caller);
     let mut explanations = Vec::new();
     
     for symbol in symbols {
-        match fetch_live_price(symbol).await {
+        match fetch_live_price(&providers, &symbol).await {
             Ok(price_data) => {
-                let (signal, _) = generate_signal(&price_data);
+                let computed = compute_signal(&price_data, &config, &providers, &db).await;
                 
-                let explanation = explainer.explain_signal(
-                    symbol,
-                    &signal,
+                let mut explanation = explainer.explain_signal(
+                    &symbol,
+                    &computed.signal,
                     price_data.price,
                     price_data.change_24h,
                 ).await;
+                explanation.sources = computed.sources();
                 
                 explanations.push(explanation);
             },
             Err(e) => {
                 // Add error explanation
                 explanations.push(SignalExplanation {
-                    symbol: symbol.to_string(),
+                    symbol: symbol.clone(),
                     current_signal: "error".to_string(),
                     explanation: format!("Failed to fetch data: {}", e),
                     confidence: 0.0,
@@ -479,6 +683,7 @@ pub async fn explain_all_signals() -> impl Responder {
                     vibe: "Error vibes".to_string(),
                     simple_advice: "Data unavailable".to_string(),
                     risk_level: "Unknown".to_string(),
+                    sources: Vec::new(),
                 });
             }
         }
@@ -492,3 +697,75 @@ pub async fn explain_all_signals() -> impl Responder {
         "timestamp": Utc::now().timestamp()
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::accounts::SubscriptionReader;
+    use actix_web::{middleware, test, App};
+
+    #[actix_rt::test]
+    async fn falls_back_to_the_24h_change() {
+        let price = |change_24h| PriceData {
+            symbol: "BTC".to_string(),
+            price: 100.0,
+            timestamp: 0,
+            change_24h,
+            market_cap: None,
+            volume_24h: None,
+            sources: Vec::new(),
+            stale: false,
+        };
+
+        let signals: Vec<String> = [12.0, 6.0, 3.0, 0.0, -3.0, -6.0, -12.0].into_iter()
+            .map(|change| generate_signal(&price(change)).0)
+            .collect();
+        assert_eq!(signals, ["strong_sell", "sell", "weak_sell", "hold", "weak_buy", "buy", "strong_buy"]);
+        assert_eq!(get_action_from_signal("weak_buy"), "HOLD_POSITION");
+        assert_eq!(get_action_from_signal("strong_sell"), "ENTER_SHORT_NOW");
+    }
+
+    #[actix_rt::test]
+    async fn serves_prices_and_signals_offline_through_plan_enforcement() {
+        let config = Config::from_env().unwrap();
+        let reader = SubscriptionReader::from_config(&config).unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(Providers::fixture()))
+                .app_data(web::Data::new(Database::in_memory().unwrap()))
+                .app_data(web::Data::new(reader))
+                .wrap(middleware::from_fn(super::super::plans::enforce))
+                .service(get_prices)
+                .service(get_signals),
+        ).await;
+
+        let prices: serde_json::Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/prices").to_request()).await;
+        assert_eq!(prices["source"], "mock");
+        // Anonymous callers are on the free plan, which covers BTC and ETH
+        let symbols: Vec<&str> = prices["prices"].as_array().unwrap().iter().map(|p| p["symbol"].as_str().unwrap()).collect();
+        assert_eq!(symbols, ["BTC", "ETH"]);
+
+        let signals: serde_json::Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/signals").to_request()).await;
+        assert_eq!(signals["plan"], "free");
+        for entry in signals["signals"].as_array().unwrap() {
+            assert_eq!(entry["method"], "indicators", "{}", entry);
+            assert!(entry["price"].as_f64().unwrap() > 0.0);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn snapshots_are_served_within_each_plans_refresh_interval() {
+        SIGNAL_SNAPSHOTS.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
+            .lock()
+            .unwrap()
+            .insert("SNAPSHOT".to_string(), (json!({ "symbol": "SNAPSHOT" }), SystemTime::now() - Duration::from_secs(90)));
+
+        let served: Vec<(String, bool)> = crate::plans::default_catalogue().into_iter()
+            .map(|plan| (plan.name, signal_snapshot("SNAPSHOT", Duration::from_secs(plan.signal_refresh_seconds)).is_some()))
+            .collect();
+        // Free refreshes every 300s, pro every 60s and elite every 15s
+        assert_eq!(served, [("free".to_string(), true), ("pro".to_string(), false), ("elite".to_string(), false)]);
+        assert!(signal_snapshot("UNKNOWN", Duration::from_secs(300)).is_none());
+    }
+}
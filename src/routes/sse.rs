@@ -0,0 +1,174 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use futures_util::stream;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use super::plans::CallerPlan;
+use crate::events::{self, Event};
+use crate::plans::PlanTier;
+
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize)]
+pub struct StreamQuery {
+    pub symbol: Option<String>,
+}
+
+struct SseState {
+    pattern: String,
+    plan: PlanTier,
+    backlog: VecDeque<Event>,
+    live: Receiver<Event>,
+    last_id: u64,
+    preamble_sent: bool,
+}
+
+fn format_event(event: &Event) -> String {
+    format!("id: {}\nevent: {}\ndata: {}\n\n", event.id, event.event, event.data)
+}
+
+/// Sent without an id, so the client's `Last-Event-ID` still points at the last event it got.
+fn format_lagged(skipped: u64) -> String {
+    format!("event: lagged\ndata: {}\n\n", serde_json::json!({ "skipped": skipped }))
+}
+
+/// Logged `kind` events a client resuming from `last_event_id` has missed, and the id
+/// to continue after. Without the header nothing is replayed.
+fn resume(kind: &str, last_event_id: Option<u64>) -> (VecDeque<Event>, u64) {
+    let Some(last_id) = last_event_id else {
+        return (VecDeque::new(), 0);
+    };
+    // An id above anything issued was handed out before a restart; the log only
+    // holds events of this run, so all of them are new to the client.
+    let last_id = if last_id > events::last_id() { 0 } else { last_id };
+
+    (events::replay_since(kind, last_id).into_iter().collect(), last_id)
+}
+
+/// Streams `kind` events as SSE, first replaying anything newer than the
+/// client's `Last-Event-ID` from the in-memory event log. Symbols outside the
+/// caller's plan are left out of the all-symbols feed.
+fn sse_response(req: &HttpRequest, kind: &str, symbol: Option<&str>, plan: PlanTier) -> HttpResponse {
+    let last_event_id = req.headers()
+        .get("Last-Event-ID")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().parse::<u64>().unwrap_or(0));
+    let pattern = format!("{}:{}", kind, symbol.map(str::to_uppercase).unwrap_or_else(|| "*".to_string()));
+
+    // Subscribe before reading the log so nothing published in between is lost
+    let live = events::subscribe();
+    let (backlog, last_id) = resume(kind, last_event_id);
+
+    let state = SseState {
+        pattern,
+        plan,
+        backlog,
+        live,
+        last_id,
+        preamble_sent: false,
+    };
+
+    let body = stream::unfold(state, |mut state| async move {
+        if !state.preamble_sent {
+            state.preamble_sent = true;
+            return Some((Ok::<_, actix_web::Error>(web::Bytes::from("retry: 3000\n\n")), state));
+        }
+
+        loop {
+            let event = match state.backlog.pop_front() {
+                Some(event) => event,
+                None => {
+                    tokio::select! {
+                        received = state.live.recv() => match received {
+                            Ok(event) => event,
+                            // Tell the client events were dropped rather than skip them silently
+                            Err(RecvError::Lagged(skipped)) => {
+                                return Some((Ok(web::Bytes::from(format_lagged(skipped))), state));
+                            },
+                            Err(RecvError::Closed) => return None,
+                        },
+                        _ = tokio::time::sleep(KEEPALIVE_INTERVAL) => {
+                            return Some((Ok(web::Bytes::from(": keepalive\n\n")), state));
+                        },
+                    }
+                },
+            };
+
+            // Skip duplicates between the replayed log and the live feed
+            if event.id <= state.last_id || !events::channel_matches(&state.pattern, &event.channel) {
+                continue;
+            }
+            if !event.channel.split_once(':').is_some_and(|(_, symbol)| state.plan.allows_symbol(symbol)) {
+                continue;
+            }
+            state.last_id = event.id;
+
+            return Some((Ok(web::Bytes::from(format_event(&event))), state));
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(body)
+}
+
+// ========== SERVER-SENT EVENTS ==========
+#[get("/stream/signals")]
+pub async fn stream_signals(req: HttpRequest, query: web::Query<StreamQuery>, caller: web::ReqData<CallerPlan>) -> impl Responder {
+    sse_response(&req, "signals", query.symbol.as_deref(), caller.into_inner().plan)
+}
+
+#[get("/stream/alerts")]
+pub async fn stream_alerts(req: HttpRequest, query: web::Query<StreamQuery>, caller: web::ReqData<CallerPlan>) -> impl Responder {
+    sse_response(&req, "alerts", query.symbol.as_deref(), caller.into_inner().plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Ids of the backlog on `channel`; other tests publish to the shared log too.
+    fn replayed(kind: &str, channel: &str, last_event_id: Option<u64>) -> (Vec<u64>, u64) {
+        let (backlog, last_id) = resume(kind, last_event_id);
+        (backlog.iter().filter(|e| e.channel == channel).map(|e| e.id).collect(), last_id)
+    }
+
+    #[test]
+    fn resumes_after_the_last_event_id() {
+        for price in [1.0, 2.0, 3.0] {
+            events::publish("alerts", "RESUME", "alert", json!({ "price": price }));
+        }
+        let (all, _) = replayed("alerts", "alerts:RESUME", Some(0));
+        assert_eq!(all.len(), 3);
+        assert!(all.windows(2).all(|pair| pair[1] > pair[0]));
+
+        // Price ticks take ids too, but never push a client's id past the sequence
+        events::publish("prices", "RESUME", "price", json!({ "price": 4.0 }));
+        assert!(events::last_id() > all[2]);
+
+        assert_eq!(replayed("alerts", "alerts:RESUME", Some(all[0])), (all[1..].to_vec(), all[0]));
+        assert_eq!(replayed("alerts", "alerts:RESUME", Some(all[2])).0, Vec::<u64>::new());
+    }
+
+    #[test]
+    fn fresh_connections_replay_nothing() {
+        events::publish("signals", "FRESH", "signal_changed", json!({}));
+
+        assert_eq!(replayed("signals", "signals:FRESH", None), (Vec::new(), 0));
+        assert_eq!(replayed("signals", "signals:FRESH", Some(0)).0.len(), 1);
+    }
+
+    #[test]
+    fn ids_from_a_previous_run_replay_the_whole_log() {
+        events::publish("signals", "RESTART", "signal_changed", json!({}));
+        let (all, _) = replayed("signals", "signals:RESTART", Some(0));
+
+        let (after_restart, last_id) = replayed("signals", "signals:RESTART", Some(events::last_id() + 1_000));
+        assert_eq!((after_restart, last_id), (all, 0));
+    }
+}
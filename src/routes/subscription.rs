@@ -1,40 +1,660 @@
-use actix_web::{HttpResponse, Responder, web};
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use serde::Deserialize;
 use serde_json::json;
-use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::admin::reject_non_admin;
+use super::auth::session_wallet;
+use super::plans::entitled_accounts;
+use crate::blockchain::accounts::{Entitlement, SubscriptionReader};
+use crate::blockchain::errors::{SendError, SubscriptionError};
+use crate::blockchain::payments::{self, PaymentMint, PaymentQuote, PaymentTransaction};
+use crate::blockchain::{indexer, lifecycle};
+use crate::blockchain::subscription::{SubmittedTransaction, SubscriptionInstruction};
+use crate::blockchain::SolanaClient;
+use crate::config::Config;
+use crate::market_data::price_cache;
+use crate::providers::Providers;
+use crate::storage::history::HistoryQuery;
+use crate::storage::lifecycle::AutoRenew;
+use crate::storage::payments::PaymentStatus;
+use crate::storage::Database;
 
 #[derive(Debug, Deserialize)]
 pub struct SubscribeRequest {
-    pub email: String,
-    pub symbols: Vec<String>,
+    pub plan_tier: u8,
+    /// Recorded as the program's `amount_paid`: base units of the mint the plan is paid in.
+    pub amount_paid: u64,
+    pub period_days: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePlanRequest {
+    pub new_tier: u8,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentRequest {
+    /// Recorded as the program's `amount_paid`: base units of the mint the plan is paid in.
+    pub amount_paid: u64,
+    pub period_days: i64,
+}
+
+fn solana_unavailable() -> HttpResponse {
+    HttpResponse::ServiceUnavailable().json(json!({
+        "error": "Solana not configured",
+        "message": "Set SOLANA_KEYPAIR_PATH or SOLANA_WALLET_KEY to enable subscription transactions",
+    }))
+}
+
+fn transaction_response(result: Result<SubmittedTransaction, SendError>) -> HttpResponse {
+    match result {
+        Ok(tx) => {
+            println!("⛓️ {} confirmed: {}", tx.instruction, tx.signature);
+            HttpResponse::Ok().json(json!({
+                "status": "confirmed",
+                "transaction": tx,
+                "timestamp": chrono::Utc::now().timestamp()
+            }))
+        },
+        Err(SendError::Program(e)) => {
+            println!("❌ Subscription transaction rejected: {}", e);
+            let mut response = match e {
+                SubscriptionError::InvalidPayment => HttpResponse::UnprocessableEntity(),
+                SubscriptionError::InvalidStatus | SubscriptionError::AlreadyActive => HttpResponse::Conflict(),
+            };
+            response.json(json!({
+                "status": "error",
+                "error": e.name(),
+                "code": e.code(),
+                "message": e.message(),
+            }))
+        },
+        Err(SendError::Client(e)) => {
+            println!("❌ Subscription transaction failed: {}", e);
+            HttpResponse::BadGateway().json(json!({
+                "status": "error",
+                "message": e,
+            }))
+        },
+    }
+}
+
+fn not_signed_in() -> HttpResponse {
+    HttpResponse::Unauthorized().json(json!({
+        "error": "Not signed in",
+        "message": "Sign in with your wallet via /auth/challenge and /auth/verify, then send the session as a Bearer token",
+    }))
+}
+
+/// Shared handling for instructions on an existing `SubscriptionAccount`.
+async fn update(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    solana: web::Data<Option<SolanaClient>>,
+    address: String,
+    instruction: SubscriptionInstruction,
+) -> HttpResponse {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+    let Some(client) = solana.as_ref() else {
+        return solana_unavailable();
+    };
+    let Ok(subscription) = Pubkey::from_str(&address) else {
+        return HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": format!("Invalid subscription address: {}", address),
+        }));
+    };
+
+    transaction_response(client.update_subscription(subscription, instruction).await)
+}
+
+pub async fn get_subscriptions(
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    reader: web::Data<SubscriptionReader>,
+    wallet: web::Path<String>,
+) -> impl Responder {
+    let wallet = wallet.into_inner();
+    let Ok(pubkey) = Pubkey::from_str(&wallet) else {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid wallet address",
+            "wallet": wallet,
+        }));
+    };
+
+    match reader.subscriptions_for(&pubkey).await {
+        Ok(accounts) => {
+            let now = chrono::Utc::now().timestamp();
+            let entitled = entitled_accounts(&db, &config, &pubkey, accounts.clone());
+            HttpResponse::Ok().json(json!({
+                "wallet": wallet,
+                "entitlement": Entitlement::from_accounts(&entitled, now),
+                "subscriptions": accounts,
+                "count": accounts.len(),
+                "timestamp": now
+            }))
+        },
+        Err(e) => HttpResponse::BadGateway().json(json!({
+            "error": "Failed to read subscriptions",
+            "message": e,
+            "wallet": wallet,
+        })),
+    }
+}
+
+// ========== SUBSCRIPTION TRANSACTIONS ==========
+// Every route signs with the backend wallet, so all of them require the admin key.
+pub async fn subscribe(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    solana: web::Data<Option<SolanaClient>>,
+    body: web::Json<SubscribeRequest>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+    let Some(client) = solana.as_ref() else {
+        return solana_unavailable();
+    };
+
+    transaction_response(client.subscribe(body.plan_tier, body.amount_paid, body.period_days).await)
+}
+
+pub async fn cancel(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    solana: web::Data<Option<SolanaClient>>,
+    address: web::Path<String>,
+) -> impl Responder {
+    update(req, config, solana, address.into_inner(), SubscriptionInstruction::Cancel).await
+}
+
+pub async fn update_plan(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    solana: web::Data<Option<SolanaClient>>,
+    address: web::Path<String>,
+    body: web::Json<UpdatePlanRequest>,
+) -> impl Responder {
+    let instruction = SubscriptionInstruction::UpdatePlan { new_tier: body.new_tier };
+    update(req, config, solana, address.into_inner(), instruction).await
 }
 
-#[derive(Debug, Serialize)]
-pub struct SubscribeResponse {
-    pub status: String,
-    pub message: String,
-    pub subscription_id: String,
+pub async fn retry_payment(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    solana: web::Data<Option<SolanaClient>>,
+    address: web::Path<String>,
+    body: web::Json<PaymentRequest>,
+) -> impl Responder {
+    let instruction = SubscriptionInstruction::RetryPayment {
+        amount_paid: body.amount_paid,
+        period_days: body.period_days,
+    };
+    update(req, config, solana, address.into_inner(), instruction).await
 }
 
-pub async fn subscribe(subscription: web::Json<SubscribeRequest>) -> impl Responder {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    
-    HttpResponse::Ok().json(SubscribeResponse {
-        status: "subscribed".to_string(),
-        message: format!("Subscribed {} to {} symbols", 
-            subscription.email, subscription.symbols.len()),
-        subscription_id: format!("sub-{}", timestamp),
-    })
+pub async fn manual_resubscribe(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    solana: web::Data<Option<SolanaClient>>,
+    address: web::Path<String>,
+    body: web::Json<PaymentRequest>,
+) -> impl Responder {
+    let instruction = SubscriptionInstruction::ManualResubscribe {
+        amount_paid: body.amount_paid,
+        period_days: body.period_days,
+    };
+    update(req, config, solana, address.into_inner(), instruction).await
 }
 
-pub async fn status() -> impl Responder {
+pub async fn status(solana: web::Data<Option<SolanaClient>>) -> impl Responder {
+    let Some(client) = solana.as_ref() else {
+        return HttpResponse::Ok().json(json!({
+            "status": "disabled",
+            "configured": false,
+            "timestamp": chrono::Utc::now().timestamp()
+        }));
+    };
+
+    let (subscriptions, error) = match client.count_subscriptions().await {
+        Ok(count) => (Some(count), None),
+        Err(e) => (None, Some(e)),
+    };
+
     HttpResponse::Ok().json(json!({
-        "status": "ok",
-        "subscriptions": 0,
-        "active": true,
+        "status": if error.is_none() { "ok" } else { "degraded" },
+        "configured": true,
+        "program_id": client.program_id.to_string(),
+        "wallet": client.wallet().to_string(),
+        "cluster": client.cluster,
+        "subscriptions": subscriptions,
+        "error": error,
         "timestamp": chrono::Utc::now().timestamp()
     }))
 }
+
+// ========== TOKEN PAYMENTS ==========
+// Plans are paid in SPL tokens: the user signs one transaction that transfers the
+// quoted amount to the treasury and calls `subscribe`.
+const DEFAULT_PERIOD_DAYS: i64 = 30;
+
+/// What is being bought, from a query string or request body.
+#[derive(Debug, Deserialize)]
+pub struct PaymentTerms {
+    pub plan_tier: u8,
+    /// 30 when unset.
+    pub period_days: Option<i64>,
+    /// Symbol or mint address; the first configured mint when unset.
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentTransactionRequest {
+    /// Pays, signs and owns the new subscription.
+    pub wallet: String,
+    #[serde(flatten)]
+    pub terms: PaymentTerms,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentsQuery {
+    pub user: Option<String>,
+    pub status: Option<String>,
+    pub limit: Option<u32>,
+}
+
+fn payment_error(status: StatusCode, error: &str, message: String) -> HttpResponse {
+    HttpResponse::build(status).json(json!({
+        "error": error,
+        "message": message,
+    }))
+}
+
+/// USD price of one whole token: $1 for pegged mints, else the mint's registry symbol.
+async fn token_price_usd(providers: &Providers, mint: &PaymentMint) -> Result<f64, String> {
+    let Some(symbol) = &mint.price_symbol else {
+        return Ok(1.0);
+    };
+    if let Some(data) = price_cache::get_fresh(symbol, Duration::from_secs(60)) {
+        return Ok(data.price);
+    }
+
+    // Stale prices are not good enough to charge by
+    let data = providers.prices.fetch_price(symbol).await?;
+    price_cache::insert(data.clone());
+    Ok(data.price)
+}
+
+/// Quotes `terms`, with the mint and treasury the payment goes to.
+pub(super) async fn plan_quote(
+    config: &Config,
+    providers: &Providers,
+    terms: &PaymentTerms,
+) -> Result<(PaymentQuote, PaymentMint, Pubkey), HttpResponse> {
+    let Some(treasury) = config.payment_treasury else {
+        return Err(payment_error(StatusCode::SERVICE_UNAVAILABLE, "Payments not configured",
+            "Set PAYMENT_TREASURY, or a backend wallet to receive payments".to_string()));
+    };
+    let Some(plan) = config.plan_tiers.iter().find(|t| t.tier == terms.plan_tier) else {
+        return Err(payment_error(StatusCode::NOT_FOUND, "Unknown plan", format!("No plan with tier {}", terms.plan_tier)));
+    };
+    let mint = match terms.currency.as_deref() {
+        Some(currency) => payments::find_mint(&config.payment_mints, currency),
+        None => config.payment_mints.first(),
+    };
+    let Some(mint) = mint else {
+        let accepted: Vec<&str> = config.payment_mints.iter().map(|m| m.symbol.as_str()).collect();
+        return Err(payment_error(StatusCode::BAD_REQUEST, "Unsupported currency",
+            format!("Accepted: {}", accepted.join(", "))));
+    };
+
+    let token_price = token_price_usd(providers, mint).await
+        .map_err(|e| payment_error(StatusCode::BAD_GATEWAY, "Price unavailable", e))?;
+    let quote = payments::quote(plan, terms.period_days.unwrap_or(DEFAULT_PERIOD_DAYS), mint, token_price, treasury)
+        .map_err(|e| payment_error(StatusCode::BAD_REQUEST, "Invalid quote request", e))?;
+    Ok((quote, mint.clone(), treasury))
+}
+
+pub async fn payment_quote(
+    config: web::Data<Config>,
+    providers: web::Data<Providers>,
+    terms: web::Query<PaymentTerms>,
+) -> impl Responder {
+    match plan_quote(&config, &providers, &terms).await {
+        Ok((quote, _, _)) => HttpResponse::Ok().json(json!({
+            "quote": quote,
+            "timestamp": chrono::Utc::now().timestamp()
+        })),
+        Err(response) => response,
+    }
+}
+
+/// Builds the payment transaction for `user` from a `plan_quote` and records the payment as pending.
+/// A checkout rebuilt for the same reference replaces its pending record; `client` is the
+/// address that opened it.
+pub(super) async fn build_payment(
+    db: &Database,
+    reader: &SubscriptionReader,
+    user: Pubkey,
+    (quote, mint, treasury): (PaymentQuote, PaymentMint, Pubkey),
+    reference: Option<Pubkey>,
+    client: Option<IpAddr>,
+) -> Result<(PaymentTransaction, PaymentQuote), HttpResponse> {
+    let blockhash = reader.latest_blockhash().await
+        .map_err(|e| payment_error(StatusCode::BAD_GATEWAY, "Failed to build payment transaction", e))?;
+    let built = payments::payment_transaction(reader.program_id(), user, &mint, &quote, treasury, reference, blockhash)
+        .map_err(|e| payment_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to build payment transaction", e))?;
+
+    let payment = payments::pending_payment(&quote, user, &built, chrono::Utc::now().timestamp());
+    let saved = match reference {
+        Some(_) => db.save_checkout(&payment, client.map(|ip| ip.to_string()).as_deref()),
+        None => db.insert_payment(&payment).map(|_| true),
+    };
+    match saved {
+        Ok(true) => {},
+        Ok(false) => return Err(payment_error(StatusCode::CONFLICT, "Checkout closed",
+            "This checkout link was already used".to_string())),
+        Err(e) => return Err(payment_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to record payment", e)),
+    }
+    println!("💳 Payment transaction for {}: {} {} for the {} plan", user, quote.ui_amount, quote.currency, quote.plan);
+
+    Ok((built, quote))
+}
+
+/// Only the signed-in wallet can open a pending payment for itself.
+pub async fn payment_transaction(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    providers: web::Data<Providers>,
+    reader: web::Data<SubscriptionReader>,
+    body: web::Json<PaymentTransactionRequest>,
+) -> impl Responder {
+    let Ok(user) = Pubkey::from_str(&body.wallet) else {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid wallet address",
+            "wallet": body.wallet,
+        }));
+    };
+    let Some(wallet) = session_wallet(&req, &db) else {
+        return not_signed_in();
+    };
+    if wallet != user {
+        return HttpResponse::Forbidden().json(json!({
+            "error": "Wallet mismatch",
+            "message": "Payment transactions can only be built for the signed-in wallet",
+            "wallet": body.wallet,
+        }));
+    }
+
+    let quoted = match plan_quote(&config, &providers, &body.terms).await {
+        Ok(quoted) => quoted,
+        Err(response) => return response,
+    };
+    match build_payment(&db, &reader, user, quoted, None, None).await {
+        Ok((built, quote)) => HttpResponse::Ok().json(json!({
+            "transaction": built.transaction,
+            "subscription": built.subscription,
+            "quote": quote,
+            "verify": format!("/subscriptions/{}/verify-payment", built.subscription),
+            "timestamp": chrono::Utc::now().timestamp()
+        })),
+        Err(response) => response,
+    }
+}
+
+/// Checks the chain for a pending payment and activates the subscription once it is verified.
+pub async fn verify_payment(
+    db: web::Data<Database>,
+    reader: web::Data<SubscriptionReader>,
+    address: web::Path<String>,
+) -> impl Responder {
+    let address = address.into_inner();
+    let payment = match db.payment(&address) {
+        Ok(Some(payment)) => payment,
+        Ok(None) => return HttpResponse::NotFound().json(json!({
+            "error": "Payment not found",
+            "subscription": address,
+        })),
+        Err(e) => return HttpResponse::InternalServerError().json(json!({
+            "error": "Failed to load payment",
+            "message": e,
+        })),
+    };
+
+    match payments::verify(&db, &reader, &payment).await {
+        Ok(payment) => HttpResponse::Ok().json(json!({
+            "verified": payment.status == PaymentStatus::Verified,
+            "payment": payment,
+        })),
+        Err(e) => HttpResponse::BadGateway().json(json!({
+            "error": "Failed to check payment",
+            "message": e,
+        })),
+    }
+}
+
+pub async fn list_payments(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    query: web::Query<PaymentsQuery>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+    let status = match query.status.as_deref().map(|s| (s, PaymentStatus::parse(s))) {
+        Some((value, None)) => return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid status",
+            "message": format!("Unknown payment status {}: expected pending, verified, failed or expired", value),
+        })),
+        Some((_, status)) => status,
+        None => None,
+    };
+
+    match db.payments(query.user.as_deref(), status, query.limit.unwrap_or(100).min(1000)) {
+        Ok(payments) => HttpResponse::Ok().json(json!({
+            "payments": payments,
+            "count": payments.len(),
+            "timestamp": chrono::Utc::now().timestamp()
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": "Failed to load payments",
+            "message": e,
+        })),
+    }
+}
+
+// ========== LIFECYCLE ==========
+#[derive(Debug, Deserialize)]
+pub struct LifecycleQuery {
+    pub user: Option<String>,
+    pub limit: Option<u32>,
+}
+
+pub async fn lifecycle_records(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    query: web::Query<LifecycleQuery>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match db.lifecycle_records(query.user.as_deref(), query.limit.unwrap_or(100).min(1000)) {
+        Ok(records) => HttpResponse::Ok().json(json!({
+            "records": records,
+            "count": records.len(),
+            "timestamp": chrono::Utc::now().timestamp()
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": "Failed to load lifecycle records",
+            "message": e,
+        })),
+    }
+}
+
+/// Runs a lifecycle scan now instead of waiting for the next interval.
+pub async fn scan_lifecycle(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    reader: web::Data<SubscriptionReader>,
+    solana: web::Data<Option<SolanaClient>>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match lifecycle::scan(&config, &db, &reader, solana.as_ref().as_ref()).await {
+        Ok(summary) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "summary": summary,
+            "timestamp": chrono::Utc::now().timestamp()
+        })),
+        Err(e) => HttpResponse::BadGateway().json(json!({
+            "status": "error",
+            "message": e,
+        })),
+    }
+}
+
+// ========== TRANSACTION HISTORY ==========
+pub async fn history(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    query: web::Query<HistoryQuery>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return HttpResponse::BadRequest().json(json!({
+                "error": "Invalid time range",
+                "message": "`from` must not be after `to`",
+            }));
+        }
+    }
+
+    match db.query_subscription_events(&query) {
+        Ok((events, totals)) => HttpResponse::Ok().json(json!({
+            "events": events,
+            "count": events.len(),
+            "totals": totals,
+            "limit": query.limit(),
+            "offset": query.offset(),
+            "timestamp": chrono::Utc::now().timestamp()
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": "Failed to load subscription history",
+            "message": e,
+        })),
+    }
+}
+
+/// Indexes new program transactions now instead of waiting for the next interval.
+pub async fn sync_history(
+    req: HttpRequest,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    reader: web::Data<SubscriptionReader>,
+) -> impl Responder {
+    if let Some(response) = reject_non_admin(&req, &config) {
+        return response;
+    }
+
+    match indexer::run(&db, &reader).await {
+        Ok(summary) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "summary": summary,
+            "timestamp": chrono::Utc::now().timestamp()
+        })),
+        Err(e) => HttpResponse::BadGateway().json(json!({
+            "status": "error",
+            "message": e,
+        })),
+    }
+}
+
+// ========== AUTO-RENEW ==========
+// Per signed-in wallet: lapsed subscriptions are renewed with `retry_payment` on these terms.
+pub async fn get_auto_renew(req: HttpRequest, db: web::Data<Database>) -> impl Responder {
+    let Some(wallet) = session_wallet(&req, &db) else {
+        return not_signed_in();
+    };
+
+    match db.auto_renew(&wallet.to_string()) {
+        Ok(auto_renew) => HttpResponse::Ok().json(json!({
+            "wallet": wallet.to_string(),
+            "enabled": auto_renew.is_some(),
+            "auto_renew": auto_renew,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": "Failed to load auto-renew",
+            "message": e,
+        })),
+    }
+}
+
+pub async fn set_auto_renew(
+    req: HttpRequest,
+    db: web::Data<Database>,
+    body: web::Json<PaymentRequest>,
+) -> impl Responder {
+    let Some(wallet) = session_wallet(&req, &db) else {
+        return not_signed_in();
+    };
+    if body.amount_paid == 0 || body.period_days <= 0 {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid auto-renew terms",
+            "message": "amount_paid and period_days must be positive",
+        }));
+    }
+
+    let auto_renew = AutoRenew {
+        wallet: wallet.to_string(),
+        amount_paid: body.amount_paid,
+        period_days: body.period_days,
+        updated_at: chrono::Utc::now().timestamp(),
+    };
+    match db.set_auto_renew(&auto_renew) {
+        Ok(()) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "enabled": true,
+            "auto_renew": auto_renew,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": "Failed to save auto-renew",
+            "message": e,
+        })),
+    }
+}
+
+pub async fn delete_auto_renew(req: HttpRequest, db: web::Data<Database>) -> impl Responder {
+    let Some(wallet) = session_wallet(&req, &db) else {
+        return not_signed_in();
+    };
+
+    match db.delete_auto_renew(&wallet.to_string()) {
+        Ok(removed) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "enabled": false,
+            "removed": removed,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({
+            "error": "Failed to remove auto-renew",
+            "message": e,
+        })),
+    }
+}
@@ -0,0 +1,225 @@
+use serde_json::{Map, Value};
+use std::fmt;
+
+use super::webhook_auth::parse_timestamp;
+use crate::storage::alerts::AlertAction;
+
+/// A validated webhook payload.
+///
+/// TradingView substitutes placeholders as text, so numeric fields are accepted
+/// both as JSON numbers and as numeric strings (`"price": "{{close}}"`).
+#[derive(Debug, Clone)]
+pub struct TradingViewWebhook {
+    pub symbol: String,
+    pub price: f64,
+    pub alert_name: Option<String>,
+    pub action: Option<AlertAction>,
+    pub position_size: Option<f64>,
+    pub interval: Option<String>,
+    pub exchange: Option<String>,
+    pub bar_time: Option<i64>,
+    pub extra: Option<Value>,
+}
+
+/// A payload field that is missing or malformed.
+#[derive(Debug)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+fn field_error(field: &'static str, message: impl Into<String>) -> FieldError {
+    FieldError { field, message: message.into() }
+}
+
+/// Reads the body as a JSON object, or as `key=value` / `key: value` pairs separated
+/// by newlines, commas or semicolons when it is plain text. A comma or semicolon only
+/// separates fields when another `key=` or `key:` follows it, so values such as
+/// `price=1,234.5` stay whole. `extra` runs to the end of its line so it can hold
+/// JSON. Anything else yields an empty object so validation can report the missing fields.
+pub fn parse_body(body: &[u8]) -> Value {
+    if let Ok(value @ Value::Object(_)) = serde_json::from_slice::<Value>(body) {
+        return value;
+    }
+
+    let text = String::from_utf8_lossy(body);
+    let mut fields = Map::new();
+    for line in text.lines() {
+        let mut rest = line.trim();
+        while !rest.is_empty() {
+            let is_extra = rest.split_once(['=', ':']).is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("extra"));
+            let (pair, tail) = match next_separator(rest) {
+                Some(at) if !is_extra => (&rest[..at], &rest[at + 1..]),
+                _ => (rest, ""),
+            };
+            rest = tail.trim();
+
+            let Some((key, value)) = pair.split_once(['=', ':']) else {
+                continue;
+            };
+            let (key, value) = (key.trim().to_lowercase(), value.trim());
+            if key.is_empty() || value.is_empty() {
+                continue;
+            }
+
+            let value = if key == "extra" {
+                serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+            } else {
+                Value::String(value.to_string())
+            };
+            fields.insert(key, value);
+        }
+    }
+
+    Value::Object(fields)
+}
+
+/// Position of the first `,` or `;` in `text` that is followed by another field.
+fn next_separator(text: &str) -> Option<usize> {
+    text.char_indices()
+        .filter(|(_, c)| matches!(c, ',' | ';'))
+        .map(|(at, _)| at)
+        .find(|at| starts_with_key(&text[at + 1..]))
+}
+
+/// Whether `text` opens with `key=` or `key:`, where the key is a plain identifier.
+fn starts_with_key(text: &str) -> bool {
+    let text = text.trim_start();
+    let key_len = text.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(text.len());
+    key_len > 0 && text[key_len..].trim_start().starts_with(['=', ':'])
+}
+
+impl TradingViewWebhook {
+    pub fn from_value(body: &Value) -> Result<Self, FieldError> {
+        let symbol = text(body, "symbol")?
+            .or(text(body, "ticker")?)
+            .ok_or_else(|| field_error("symbol", "is required"))?;
+
+        let price = number(body, "price")?.ok_or_else(|| field_error("price", "is required"))?;
+        if price <= 0.0 {
+            return Err(field_error("price", format!("must be positive, got {}", price)));
+        }
+
+        let action = match text(body, "action")? {
+            Some(action) => Some(AlertAction::parse(&action)
+                .ok_or_else(|| field_error("action", format!("expected buy or sell, got {:?}", action)))?),
+            None => None,
+        };
+
+        let bar_time = match body.get("time").filter(|v| !v.is_null()) {
+            Some(time) => Some(parse_timestamp(time)
+                .ok_or_else(|| field_error("time", format!("expected Unix time or RFC 3339 date, got {}", time)))?),
+            None => None,
+        };
+
+        Ok(Self {
+            symbol,
+            price,
+            alert_name: text(body, "alert_name")?,
+            action,
+            position_size: number(body, "position_size")?,
+            interval: text(body, "interval")?,
+            exchange: text(body, "exchange")?.map(|e| e.to_uppercase()),
+            bar_time,
+            extra: body.get("extra").filter(|v| !v.is_null()).cloned(),
+        })
+    }
+}
+
+fn text(body: &Value, field: &'static str) -> Result<Option<String>, FieldError> {
+    match body.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) if s.trim().is_empty() => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.trim().to_string())),
+        Some(Value::Number(n)) => Ok(Some(n.to_string())),
+        Some(other) => Err(field_error(field, format!("expected a string, got {}", other))),
+    }
+}
+
+fn number(body: &Value, field: &'static str) -> Result<Option<f64>, FieldError> {
+    let value = match body.get(field) {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::Number(n)) => n.as_f64(),
+        // Thousands separators, as in `1,234.5`
+        Some(Value::String(s)) => s.trim().replace(',', "").parse::<f64>().ok(),
+        Some(_) => None,
+    };
+
+    match value {
+        Some(v) if v.is_finite() => Ok(Some(v)),
+        _ => Err(field_error(field, format!("expected a number, got {}", body[field]))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn field_error_of(body: Value) -> (&'static str, String) {
+        let e = TradingViewWebhook::from_value(&body).unwrap_err();
+        (e.field, e.message)
+    }
+
+    #[test]
+    fn reads_plain_text_fields() {
+        let body = parse_body(b"Symbol = BTC; price=1,234.5, action: long\ntime=2024-01-02T03:04:05Z\nextra={\"a\": 1, \"b\": 2}");
+        assert_eq!(body, json!({
+            "symbol": "BTC",
+            "price": "1,234.5",
+            "action": "long",
+            "time": "2024-01-02T03:04:05Z",
+            "extra": { "a": 1, "b": 2 },
+        }));
+
+        let alert = TradingViewWebhook::from_value(&body).unwrap();
+        assert_eq!((alert.symbol.as_str(), alert.price, alert.action), ("BTC", 1234.5, Some(AlertAction::Buy)));
+        assert_eq!(alert.bar_time, Some(1_704_164_645));
+        assert_eq!(alert.extra, Some(json!({ "a": 1, "b": 2 })));
+    }
+
+    #[test]
+    fn only_the_extra_key_runs_to_the_end_of_the_line() {
+        let body = parse_body(b"extra_note=first, symbol=ETH\nnote: a, b; c, price=10");
+        assert_eq!(body, json!({ "extra_note": "first", "symbol": "ETH", "note": "a, b; c", "price": "10" }));
+
+        let body = parse_body(b"EXTRA: not json, symbol=SOL");
+        assert_eq!(body, json!({ "extra": "not json, symbol=SOL" }));
+        assert_eq!(parse_body(b"no fields here"), json!({}));
+    }
+
+    #[test]
+    fn reads_json_bodies() {
+        let body = parse_body(br#"{"ticker": "SOLUSDT", "price": "150.5", "position_size": -2, "exchange": "binance", "time": 1700000000000, "interval": 60}"#);
+        let alert = TradingViewWebhook::from_value(&body).unwrap();
+
+        assert_eq!(alert.symbol, "SOLUSDT");
+        assert_eq!(alert.price, 150.5);
+        assert_eq!(alert.position_size, Some(-2.0));
+        assert_eq!(alert.exchange.as_deref(), Some("BINANCE"));
+        assert_eq!(alert.bar_time, Some(1_700_000_000));
+        assert_eq!(alert.interval.as_deref(), Some("60"));
+        assert_eq!(alert.action, None);
+        // Arrays are not objects, so they fall through to the plain text reader
+        assert_eq!(parse_body(b"[1, 2]"), json!({}));
+    }
+
+    #[test]
+    fn reports_the_offending_field() {
+        assert_eq!(field_error_of(json!({ "price": 1 })), ("symbol", "is required".to_string()));
+        assert_eq!(field_error_of(json!({ "symbol": "BTC" })), ("price", "is required".to_string()));
+        assert_eq!(field_error_of(json!({ "symbol": "BTC", "price": "abc" })).0, "price");
+        assert_eq!(field_error_of(json!({ "symbol": "BTC", "price": -1 })), ("price", "must be positive, got -1".to_string()));
+        assert_eq!(field_error_of(json!({ "symbol": ["BTC"], "price": 1 })).0, "symbol");
+        assert_eq!(field_error_of(json!({ "symbol": "BTC", "price": 1, "action": "hodl" })).0, "action");
+        assert_eq!(field_error_of(json!({ "symbol": "BTC", "price": 1, "time": "soon" })).0, "time");
+        assert_eq!(field_error_of(json!({ "symbol": "BTC", "price": 1, "position_size": "many" })).0, "position_size");
+        assert_eq!(field_error_of(json!({ "symbol": "BTC", "price": 1, "interval": {} })).0, "interval");
+    }
+}
@@ -1,16 +1,73 @@
-use actix_web::{HttpResponse, Responder};
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
 use serde_json::json;
 
-pub async fn wallet_info() -> impl Responder {
-    HttpResponse::Ok().json(json!({
-        "status": "ok",
-        "message": "Wallet info endpoint"
-    }))
+use crate::blockchain::accounts::SubscriptionReader;
+use crate::blockchain::{self, SolanaClient};
+use crate::config::Config;
+
+#[derive(Deserialize)]
+pub struct WalletInfoQuery {
+    /// Recent transactions to list, 10 by default.
+    pub limit: Option<usize>,
+}
+
+/// The service wallet's SOL balance and recent transactions.
+pub async fn wallet_info(
+    solana: web::Data<Option<SolanaClient>>,
+    reader: web::Data<SubscriptionReader>,
+    query: web::Query<WalletInfoQuery>,
+) -> impl Responder {
+    let Some(client) = solana.as_ref() else {
+        return HttpResponse::ServiceUnavailable().json(json!({
+            "error": "Solana not configured",
+            "message": "Set SOLANA_KEYPAIR_PATH or SOLANA_WALLET_KEY to enable the service wallet",
+        }));
+    };
+
+    match reader.wallet_info(&client.wallet(), query.limit.unwrap_or(10).clamp(1, 100)).await {
+        Ok(wallet) => HttpResponse::Ok().json(json!({
+            "status": "ok",
+            "cluster": client.cluster,
+            "wallet": wallet,
+            "timestamp": chrono::Utc::now().timestamp()
+        })),
+        Err(e) => HttpResponse::BadGateway().json(json!({
+            "status": "error",
+            "message": e,
+        })),
+    }
 }
 
-pub async fn test_solana_connection() -> impl Responder {
-    HttpResponse::Ok().json(json!({
-        "status": "ok",
-        "message": "Test connection endpoint"
-    }))
+/// RPC version, slot and latency, and whether the program is deployed. Answers 503
+/// when the node is unreachable or the program is missing.
+pub async fn solana_health(
+    config: web::Data<Config>,
+    reader: web::Data<SubscriptionReader>,
+) -> impl Responder {
+    let rpc = reader.rpc_health().await;
+    let (program, program_error) = if rpc.healthy {
+        match reader.program_status().await {
+            Ok(program) => (Some(program), None),
+            Err(e) => (None, Some(e)),
+        }
+    } else {
+        (None, None)
+    };
+
+    let healthy = rpc.healthy && program.as_ref().is_some_and(|p| p.deployed && p.executable);
+    let body = json!({
+        "status": if healthy { "ok" } else { "degraded" },
+        "cluster": blockchain::cluster_name(config.solana_rpc_url.as_deref().unwrap_or(blockchain::DEFAULT_RPC_URL)),
+        "rpc": rpc,
+        "program": program,
+        "program_error": program_error,
+        "timestamp": chrono::Utc::now().timestamp()
+    });
+
+    if healthy {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
 }
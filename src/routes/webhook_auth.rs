@@ -0,0 +1,302 @@
+use actix_web::HttpRequest;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::plans::{check_rate_limit, client_ip};
+use crate::config::Config;
+use crate::storage::audit::AuditEntry;
+use crate::storage::Database;
+use crate::utils::create_binance_signature;
+
+/// Nonces seen per `source:nonce`, with the time after which they may be forgotten.
+static SEEN_NONCES: OnceLock<Arc<Mutex<HashMap<String, i64>>>> = OnceLock::new();
+
+/// Rejections written to the audit log per client address and minute; the rest are
+/// refused without a record, so a flood cannot grow the log without bound.
+const AUDITED_REJECTIONS_PER_MINUTE: u32 = 20;
+
+/// TradingView cannot set headers, so credentials may also travel in the query string.
+#[derive(Debug, Default, Deserialize)]
+pub struct WebhookAuthQuery {
+    pub token: Option<String>,
+    pub signature: Option<String>,
+}
+
+/// Credentials and replay fields taken from the request body.
+#[derive(Debug, Default)]
+pub struct BodyCredentials {
+    pub token: Option<String>,
+    pub timestamp: Option<Value>,
+    pub nonce: Option<String>,
+}
+
+impl BodyCredentials {
+    pub fn from_json(body: &Value) -> Self {
+        Self {
+            token: body.get("token").and_then(|v| v.as_str()).map(str::to_string),
+            timestamp: body.get("timestamp").filter(|v| !v.is_null()).cloned(),
+            nonce: body.get("nonce").and_then(|v| v.as_str()).map(str::to_string),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Rejection {
+    pub source: Option<String>,
+    pub reason: String,
+}
+
+impl Rejection {
+    fn new(source: Option<&str>, reason: impl Into<String>) -> Self {
+        Self { source: source.map(str::to_string), reason: reason.into() }
+    }
+}
+
+/// Authenticates a webhook request and returns the matching source name, or `None`
+/// when no secrets are configured and unauthenticated webhooks are allowed.
+///
+/// A request is accepted with either a hex HMAC-SHA256 of the raw body (`X-Signature`
+/// header or `?signature=`) or a plain token (`token` in the body or query). Every
+/// request must carry a `timestamp` inside the replay window and a `nonce` that is
+/// only used once within it.
+pub fn authenticate(
+    req: &HttpRequest,
+    query: &WebhookAuthQuery,
+    body: &[u8],
+    credentials: &BodyCredentials,
+    config: &Config,
+) -> Result<Option<String>, Rejection> {
+    if config.webhook_secrets.is_empty() {
+        if config.webhook_allow_unauthenticated {
+            return Ok(None);
+        }
+        return Err(Rejection::new(None, "Webhook authentication is not configured"));
+    }
+
+    let header_signature = req.headers().get("X-Signature").and_then(|v| v.to_str().ok());
+    let signature = header_signature
+        .or(query.signature.as_deref())
+        .map(|s| s.trim().trim_start_matches("sha256=").to_lowercase());
+
+    let source = match signature {
+        Some(signature) => {
+            let body = std::str::from_utf8(body).map_err(|_| Rejection::new(None, "Body is not valid UTF-8"))?;
+            config.webhook_secrets.iter()
+                .find(|(_, secret)| constant_time_eq(&create_binance_signature(body, secret), &signature))
+                .map(|(source, _)| source.as_str())
+                .ok_or_else(|| Rejection::new(None, "Invalid signature"))?
+        },
+        None if config.webhook_require_signature => {
+            return Err(Rejection::new(None, "Missing signature"));
+        },
+        None => {
+            let token = credentials.token.as_deref()
+                .or(query.token.as_deref())
+                .ok_or_else(|| Rejection::new(None, "Missing token"))?;
+            config.webhook_secrets.iter()
+                .find(|(_, secret)| constant_time_eq(secret, token))
+                .map(|(source, _)| source.as_str())
+                .ok_or_else(|| Rejection::new(None, "Invalid token"))?
+        },
+    };
+
+    let now = Utc::now().timestamp();
+    let window = config.webhook_replay_window_seconds;
+
+    let timestamp = credentials.timestamp.as_ref()
+        .ok_or_else(|| Rejection::new(Some(source), "Missing timestamp"))?;
+    let sent_at = parse_timestamp(timestamp)
+        .ok_or_else(|| Rejection::new(Some(source), "Invalid timestamp"))?;
+    if (now - sent_at).abs() > window {
+        return Err(Rejection::new(Some(source), format!("Timestamp outside the {}s replay window", window)));
+    }
+
+    let nonce = credentials.nonce.as_deref()
+        .filter(|nonce| !nonce.trim().is_empty())
+        .ok_or_else(|| Rejection::new(Some(source), "Missing nonce"))?;
+    let mut seen = SEEN_NONCES.get_or_init(|| Arc::new(Mutex::new(HashMap::new()))).lock().unwrap();
+    seen.retain(|_, expires_at| *expires_at > now);
+    let key = format!("{}:{}", source, nonce);
+    if seen.contains_key(&key) {
+        return Err(Rejection::new(Some(source), "Nonce already used"));
+    }
+    seen.insert(key, now + window);
+
+    Ok(Some(source.to_string()))
+}
+
+/// Records a rejected request in the audit log, up to `AUDITED_REJECTIONS_PER_MINUTE`
+/// per client address.
+pub fn audit_rejection(db: &Database, req: &HttpRequest, rejection: &Rejection, config: &Config) {
+    let remote_addr = client_ip(req, &config.trusted_proxies).map(|ip| ip.to_string());
+    let key = format!("webhook-audit:{}", remote_addr.as_deref().unwrap_or("unknown"));
+    if check_rate_limit(&key, AUDITED_REJECTIONS_PER_MINUTE).is_err() {
+        return;
+    }
+
+    let entry = AuditEntry {
+        timestamp: Utc::now().timestamp(),
+        source: rejection.source.clone(),
+        remote_addr,
+        reason: rejection.reason.clone(),
+    };
+
+    println!(
+        "🚫 Rejected TradingView webhook from {}: {}",
+        entry.remote_addr.as_deref().unwrap_or("unknown"),
+        entry.reason
+    );
+    if let Err(e) = db.insert_audit_entry(&entry) {
+        println!("⚠️ Failed to write audit entry: {}", e);
+    }
+}
+
+/// Unix seconds or milliseconds, as a number or string, or an RFC 3339 date
+/// (TradingView's `{{timenow}}`).
+pub fn parse_timestamp(value: &Value) -> Option<i64> {
+    let raw = match value {
+        Value::Number(n) => n.as_i64()?,
+        Value::String(s) => match s.trim().parse::<i64>() {
+            Ok(n) => n,
+            Err(_) => return DateTime::parse_from_rfc3339(s.trim()).ok().map(|t| t.timestamp()),
+        },
+        _ => return None,
+    };
+
+    Some(if raw > 100_000_000_000 { raw / 1000 } else { raw })
+}
+
+/// Compares SHA-256 digests of both sides, so neither the content nor the length of
+/// the secret shows in the timing.
+pub(super) fn constant_time_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (Sha256::digest(a.as_bytes()), Sha256::digest(b.as_bytes()));
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use serde_json::json;
+
+    fn config() -> Config {
+        let mut config = Config::from_env().unwrap();
+        config.webhook_secrets = vec![("tv".to_string(), "s3cret".to_string())];
+        config.webhook_allow_unauthenticated = false;
+        config.webhook_require_signature = false;
+        config.webhook_replay_window_seconds = 300;
+        config
+    }
+
+    fn fresh_body(extra: Value) -> Value {
+        let mut body = json!({ "timestamp": Utc::now().timestamp(), "nonce": uuid::Uuid::new_v4().to_string() });
+        body.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        body
+    }
+
+    fn check(req: &HttpRequest, query: WebhookAuthQuery, body: &Value, config: &Config) -> Result<Option<String>, String> {
+        let raw = body.to_string();
+        authenticate(req, &query, raw.as_bytes(), &BodyCredentials::from_json(body), config).map_err(|r| r.reason)
+    }
+
+    fn signed(body: &Value, secret: &str) -> HttpRequest {
+        TestRequest::default()
+            .insert_header(("X-Signature", format!("sha256={}", create_binance_signature(&body.to_string(), secret))))
+            .to_http_request()
+    }
+
+    #[actix_rt::test]
+    async fn accepts_signatures_of_the_raw_body() {
+        let config = config();
+        let body = fresh_body(json!({ "symbol": "BTC" }));
+
+        assert_eq!(check(&signed(&body, "s3cret"), WebhookAuthQuery::default(), &body, &config), Ok(Some("tv".to_string())));
+        let body = fresh_body(json!({}));
+        assert_eq!(check(&signed(&body, "other"), WebhookAuthQuery::default(), &body, &config), Err("Invalid signature".to_string()));
+
+        let mut strict = config.clone();
+        strict.webhook_require_signature = true;
+        let body = fresh_body(json!({ "token": "s3cret" }));
+        assert_eq!(check(&TestRequest::default().to_http_request(), WebhookAuthQuery::default(), &body, &strict), Err("Missing signature".to_string()));
+    }
+
+    #[actix_rt::test]
+    async fn accepts_tokens_from_the_body_or_query() {
+        let config = config();
+        let req = TestRequest::default().to_http_request();
+
+        let body = fresh_body(json!({ "token": "s3cret" }));
+        assert_eq!(check(&req, WebhookAuthQuery::default(), &body, &config), Ok(Some("tv".to_string())));
+        let query = WebhookAuthQuery { token: Some("s3cret".to_string()), signature: None };
+        assert_eq!(check(&req, query, &fresh_body(json!({})), &config), Ok(Some("tv".to_string())));
+
+        let body = fresh_body(json!({ "token": "s3cre" }));
+        assert_eq!(check(&req, WebhookAuthQuery::default(), &body, &config), Err("Invalid token".to_string()));
+        assert_eq!(check(&req, WebhookAuthQuery::default(), &fresh_body(json!({})), &config), Err("Missing token".to_string()));
+    }
+
+    #[actix_rt::test]
+    async fn requires_a_fresh_timestamp_and_an_unused_nonce() {
+        let config = config();
+        let req = TestRequest::default().to_http_request();
+        let reason = |body: &Value| check(&req, WebhookAuthQuery::default(), body, &config).unwrap_err();
+
+        let stale = fresh_body(json!({ "token": "s3cret", "timestamp": Utc::now().timestamp() - 301 }));
+        assert_eq!(reason(&stale), "Timestamp outside the 300s replay window");
+        let garbled = fresh_body(json!({ "token": "s3cret", "timestamp": "yesterday" }));
+        assert_eq!(reason(&garbled), "Invalid timestamp");
+
+        let mut missing_timestamp = fresh_body(json!({ "token": "s3cret" }));
+        missing_timestamp.as_object_mut().unwrap().remove("timestamp");
+        assert_eq!(reason(&missing_timestamp), "Missing timestamp");
+
+        let mut missing_nonce = fresh_body(json!({ "token": "s3cret" }));
+        missing_nonce.as_object_mut().unwrap().remove("nonce");
+        assert_eq!(reason(&missing_nonce), "Missing nonce");
+        assert_eq!(reason(&fresh_body(json!({ "token": "s3cret", "nonce": " " }))), "Missing nonce");
+
+        // Signed requests are held to the same rules
+        let mut unsigned_nonce = fresh_body(json!({}));
+        unsigned_nonce.as_object_mut().unwrap().remove("nonce");
+        assert_eq!(check(&signed(&unsigned_nonce, "s3cret"), WebhookAuthQuery::default(), &unsigned_nonce, &config), Err("Missing nonce".to_string()));
+
+        let body = fresh_body(json!({ "token": "s3cret" }));
+        assert!(check(&req, WebhookAuthQuery::default(), &body, &config).is_ok());
+        assert_eq!(reason(&body), "Nonce already used");
+    }
+
+    #[actix_rt::test]
+    async fn fails_closed_without_secrets() {
+        let mut config = config();
+        config.webhook_secrets.clear();
+        let req = TestRequest::default().to_http_request();
+
+        assert_eq!(check(&req, WebhookAuthQuery::default(), &json!({}), &config), Err("Webhook authentication is not configured".to_string()));
+        config.webhook_allow_unauthenticated = true;
+        assert_eq!(check(&req, WebhookAuthQuery::default(), &json!({}), &config), Ok(None));
+    }
+
+    #[actix_rt::test]
+    async fn audits_the_client_address_at_a_bounded_rate() {
+        let mut config = config();
+        config.trusted_proxies = vec!["10.0.0.1".parse().unwrap()];
+        let db = Database::in_memory().unwrap();
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.1:443".parse().unwrap())
+            .insert_header(("X-Forwarded-For", "198.51.100.7, 203.0.113.9"))
+            .to_http_request();
+
+        for _ in 0..AUDITED_REJECTIONS_PER_MINUTE + 5 {
+            audit_rejection(&db, &req, &Rejection::new(None, "Invalid token"), &config);
+        }
+
+        let entries = db.recent_audit_entries(100).unwrap();
+        // A minute boundary in the middle of the loop can let a few more through
+        assert!(entries.len() >= AUDITED_REJECTIONS_PER_MINUTE as usize && entries.len() < 2 * AUDITED_REJECTIONS_PER_MINUTE as usize);
+        assert_eq!(entries[0].remote_addr.as_deref(), Some("203.0.113.9"));
+    }
+}
@@ -0,0 +1,201 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_ws::Message;
+use chrono::Utc;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+use super::plans::{caller_plan, CallerPlan};
+use crate::blockchain::accounts::SubscriptionReader;
+use crate::config::Config;
+use crate::events;
+use crate::plans::PlanTier;
+use crate::storage::Database;
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+pub struct WsQuery {
+    /// Comma-separated channels to subscribe to on connect, e.g. `prices:BTC,signals:*`.
+    pub subscribe: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ClientCommand {
+    action: String,
+    #[serde(default)]
+    channels: Vec<String>,
+}
+
+/// Symbol-scoped channels must name a symbol the plan covers; `*` wildcards are
+/// narrowed to the plan's symbols when events are delivered. `subscriptions:<wallet>`
+/// is keyed by wallet rather than symbol and open only to that wallet's session.
+fn channel_allowed(plan: &PlanTier, wallet: Option<&str>, channel: &str) -> bool {
+    if events::is_private(channel) {
+        return channel.split_once(':').is_some_and(|(_, owner)| Some(owner) == wallet);
+    }
+    match channel.split_once(':') {
+        Some((_, "*")) | None => true,
+        Some((_, symbol)) => plan.allows_symbol(symbol),
+    }
+}
+
+/// Adds channels until the plan's `alert_channels` limit, returning those turned away.
+fn subscribe_within_plan(
+    subscriptions: &mut BTreeSet<String>,
+    plan: &PlanTier,
+    wallet: Option<&str>,
+    channels: impl IntoIterator<Item = String>,
+) -> Vec<String> {
+    let mut rejected = Vec::new();
+    for channel in channels {
+        let allowed = is_valid_channel(&channel) && channel_allowed(plan, wallet, &channel);
+        let has_room = subscriptions.contains(&channel) || subscriptions.len() < plan.alert_channels;
+        if allowed && has_room {
+            subscriptions.insert(channel);
+        } else {
+            rejected.push(channel);
+        }
+    }
+    rejected
+}
+
+fn is_valid_channel(channel: &str) -> bool {
+    channel == "*"
+        || matches!(
+            channel.split_once(':'),
+            Some(("prices" | "signals" | "alerts" | "conditions" | "subscriptions", symbol)) if !symbol.is_empty()
+        )
+}
+
+// ========== WEBSOCKET PUSH ==========
+// Clients send {"action": "subscribe" | "unsubscribe", "channels": ["prices:BTC", "signals:*"]}
+// and receive every matching event as {"channel", "event", "data", "timestamp"}.
+// The caller's plan (via a Bearer session token) caps how many channels are open at once,
+// and `subscriptions:<wallet>` needs a session for that wallet.
+pub async fn ws_handler(
+    req: HttpRequest,
+    body: web::Payload,
+    query: web::Query<WsQuery>,
+    config: web::Data<Config>,
+    db: web::Data<Database>,
+    reader: web::Data<SubscriptionReader>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let CallerPlan { wallet, plan } = caller_plan(&req, &db, &reader, &config).await;
+    let (response, mut session, mut messages) = actix_ws::handle(&req, body)?;
+
+    let mut subscriptions = BTreeSet::new();
+    let initial = query.subscribe.as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(str::to_string);
+    let rejected = subscribe_within_plan(&mut subscriptions, &plan, wallet.as_deref(), initial);
+    let mut events = events::subscribe();
+
+    actix_rt::spawn(async move {
+        let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+        let hello = json!({
+            "type": "subscribed",
+            "channels": subscriptions,
+            "rejected": rejected,
+            "plan": plan.name,
+            "max_channels": plan.alert_channels,
+            "timestamp": Utc::now().timestamp()
+        });
+        if session.text(hello.to_string()).await.is_err() {
+            return;
+        }
+
+        let close_reason = loop {
+            tokio::select! {
+                message = messages.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        let reply = match serde_json::from_str::<ClientCommand>(&text) {
+                            Ok(command) => {
+                                let invalid = match command.action.as_str() {
+                                    "subscribe" => subscribe_within_plan(&mut subscriptions, &plan, wallet.as_deref(), command.channels),
+                                    "unsubscribe" => {
+                                        command.channels.iter().for_each(|c| { subscriptions.remove(c); });
+                                        Vec::new()
+                                    },
+                                    other => {
+                                        let error = json!({"type": "error", "message": format!("Unknown action: {}", other)});
+                                        if session.text(error.to_string()).await.is_err() {
+                                            break None;
+                                        }
+                                        continue;
+                                    },
+                                };
+                                json!({"type": "subscribed", "channels": subscriptions, "rejected": invalid, "timestamp": Utc::now().timestamp()})
+                            },
+                            Err(e) => json!({"type": "error", "message": format!("Invalid command: {}", e)}),
+                        };
+                        if session.text(reply.to_string()).await.is_err() {
+                            break None;
+                        }
+                    },
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break None;
+                        }
+                    },
+                    Some(Ok(Message::Close(reason))) => break reason,
+                    Some(Ok(_)) => {},
+                    Some(Err(_)) | None => break None,
+                },
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        let in_plan = channel_allowed(&plan, wallet.as_deref(), &event.channel);
+                        if in_plan && subscriptions.iter().any(|pattern| events::channel_matches(pattern, &event.channel)) {
+                            let payload = serde_json::to_string(&event).unwrap_or_default();
+                            if session.text(payload).await.is_err() {
+                                break None;
+                            }
+                        }
+                    },
+                    Err(RecvError::Lagged(skipped)) => println!("⚠️ WebSocket client lagged, skipped {} events", skipped),
+                    Err(RecvError::Closed) => break None,
+                },
+                _ = heartbeat.tick() => {
+                    if session.ping(b"").await.is_err() {
+                        break None;
+                    }
+                },
+            }
+        };
+
+        let _ = session.close(close_reason).await;
+    });
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plans::default_catalogue;
+
+    #[test]
+    fn subscription_channels_are_private_to_their_wallet() {
+        let plan = &default_catalogue()[2];
+        let owner = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+        let channel = format!("subscriptions:{}", owner);
+        let mut subscriptions = BTreeSet::new();
+
+        let rejected = subscribe_within_plan(&mut subscriptions, plan, None, [channel.clone(), "subscriptions:*".to_string()]);
+        assert_eq!(rejected.len(), 2);
+        let rejected = subscribe_within_plan(&mut subscriptions, plan, Some("SomeoneElse111111111111111111111111111111111"), [channel.clone()]);
+        assert_eq!(rejected, vec![channel.clone()]);
+        assert!(subscribe_within_plan(&mut subscriptions, plan, Some(owner), [channel.clone()]).is_empty());
+
+        // Wildcards never reach a wallet's channel
+        assert!(!events::channel_matches("*", &channel));
+        assert!(!events::channel_matches("subscriptions:*", &channel));
+        assert!(events::channel_matches(&channel, &channel));
+    }
+}
@@ -0,0 +1,202 @@
+use super::{IndicatorValue, SignalGenerator, SignalType, TradingSignal};
+use chrono::Utc;
+use serde::Serialize;
+
+/// One generator's verdict, kept alongside the weight it was merged with.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndicatorResult {
+    pub name: String,
+    pub weight: f64,
+    pub signal: SignalType,
+    pub confidence: f64,
+    pub values: Vec<IndicatorValue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregatedSignal {
+    pub signal: TradingSignal,
+    /// Share of total weight (0..1) that voted in the winning direction.
+    pub agreement: f64,
+    pub components: Vec<IndicatorResult>,
+}
+
+struct WeightedGenerator {
+    name: String,
+    weight: f64,
+    generator: Box<dyn SignalGenerator>,
+}
+
+/// Components that must back a direction before it can be promoted, so a lone
+/// generator never produces a strong signal on its own.
+const MIN_STRONG_VOTERS: usize = 2;
+
+/// Merges several `SignalGenerator`s into a single weighted signal.
+///
+/// Each generator votes +1 (buy), -1 (sell) or 0 (hold) scaled by its weight.
+/// When the winning side holds at least `strong_threshold` of the total weight,
+/// with at least `MIN_STRONG_VOTERS` components behind it, the result is promoted
+/// to `StrongBuy`/`StrongSell`.
+pub struct SignalAggregator {
+    generators: Vec<WeightedGenerator>,
+    strong_threshold: f64,
+}
+
+impl SignalAggregator {
+    pub fn new(strong_threshold: f64) -> Self {
+        Self {
+            generators: Vec::new(),
+            strong_threshold,
+        }
+    }
+
+    pub fn with_generator(mut self, name: &str, weight: f64, generator: Box<dyn SignalGenerator>) -> Self {
+        self.generators.push(WeightedGenerator {
+            name: name.to_string(),
+            weight,
+            generator,
+        });
+        self
+    }
+
+    /// Runs every generator over `prices` (oldest first) and merges the results.
+    /// Generators without enough data are skipped.
+    pub fn aggregate(&self, symbol: &str, prices: &[f64]) -> Result<AggregatedSignal, String> {
+        let mut components = Vec::new();
+
+        for weighted in &self.generators {
+            match weighted.generator.generate_signal(prices) {
+                Ok(signal) => components.push(IndicatorResult {
+                    name: weighted.name.clone(),
+                    weight: weighted.weight,
+                    signal: signal.signal_type,
+                    confidence: signal.confidence,
+                    values: signal.indicators,
+                }),
+                Err(e) => println!("⚠️ {} {} skipped: {}", symbol, weighted.name, e),
+            }
+        }
+
+        if components.is_empty() {
+            return Err(format!("Not enough price history for {} ({} points)", symbol, prices.len()));
+        }
+
+        Ok(self.merge(symbol, prices.last().copied().unwrap_or_default(), components))
+    }
+
+    /// Merges already-computed components into one signal.
+    pub fn merge(&self, symbol: &str, price: f64, components: Vec<IndicatorResult>) -> AggregatedSignal {
+        let total_weight: f64 = components.iter().map(|c| c.weight).sum();
+
+        let side_weight = |pred: fn(&SignalType) -> bool| -> f64 {
+            components.iter()
+                .filter(|c| pred(&c.signal))
+                .map(|c| c.weight)
+                .sum()
+        };
+        let buy_weight = side_weight(|s| matches!(s, SignalType::Buy | SignalType::StrongBuy));
+        let sell_weight = side_weight(|s| matches!(s, SignalType::Sell | SignalType::StrongSell));
+
+        let (direction, winning_weight) = if buy_weight > sell_weight {
+            (SignalType::Buy, buy_weight)
+        } else if sell_weight > buy_weight {
+            (SignalType::Sell, sell_weight)
+        } else {
+            (SignalType::Hold, total_weight - buy_weight - sell_weight)
+        };
+
+        let agreement = if total_weight > 0.0 { winning_weight / total_weight } else { 0.0 };
+
+        // Weighted confidence of the components backing the result, diluted by the
+        // weight that voted otherwise.
+        let confidence = if total_weight > 0.0 {
+            components.iter()
+                .filter(|c| same_side(&c.signal, &direction))
+                .map(|c| c.weight * c.confidence)
+                .sum::<f64>() / total_weight
+        } else {
+            0.0
+        };
+
+        let voters = components.iter().filter(|c| same_side(&c.signal, &direction)).count();
+        let strong = agreement >= self.strong_threshold && voters >= MIN_STRONG_VOTERS;
+        let signal_type = match direction {
+            SignalType::Buy if strong => SignalType::StrongBuy,
+            SignalType::Sell if strong => SignalType::StrongSell,
+            other => other,
+        };
+
+        let signal = TradingSignal {
+            symbol: symbol.to_string(),
+            signal_type,
+            confidence,
+            price,
+            timestamp: Utc::now().timestamp(),
+            indicators: components.iter().flat_map(|c| c.values.clone()).collect(),
+        };
+
+        AggregatedSignal { signal, agreement, components }
+    }
+}
+
+fn same_side(a: &SignalType, b: &SignalType) -> bool {
+    matches!(
+        (a, b),
+        (SignalType::Buy | SignalType::StrongBuy, SignalType::Buy | SignalType::StrongBuy)
+            | (SignalType::Sell | SignalType::StrongSell, SignalType::Sell | SignalType::StrongSell)
+            | (SignalType::Hold, SignalType::Hold)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote(name: &str, weight: f64, signal: SignalType, confidence: f64) -> IndicatorResult {
+        IndicatorResult { name: name.to_string(), weight, signal, confidence, values: Vec::new() }
+    }
+
+    #[test]
+    fn a_lone_voter_is_never_promoted() {
+        let merged = SignalAggregator::new(0.75).merge("BTC", 1.0, vec![vote("ema", 1.0, SignalType::Buy, 80.0)]);
+
+        assert_eq!(merged.agreement, 1.0);
+        assert_eq!(merged.signal.signal_type, SignalType::Buy);
+    }
+
+    #[test]
+    fn promotes_when_enough_weight_agrees() {
+        let aggregator = SignalAggregator::new(0.75);
+
+        let strong = aggregator.merge("BTC", 1.0, vec![
+            vote("ema", 1.0, SignalType::Sell, 60.0),
+            vote("macd", 2.0, SignalType::Sell, 90.0),
+            vote("rsi", 0.5, SignalType::Hold, 10.0),
+        ]);
+        assert!((strong.agreement - 3.0 / 3.5).abs() < 1e-9);
+        assert_eq!(strong.signal.signal_type, SignalType::StrongSell);
+        // (1 * 60 + 2 * 90) / 3.5, the hold vote dilutes the confidence
+        assert!((strong.signal.confidence - 240.0 / 3.5).abs() < 1e-9);
+
+        let split = aggregator.merge("BTC", 1.0, vec![
+            vote("ema", 1.0, SignalType::Buy, 60.0),
+            vote("macd", 1.0, SignalType::Buy, 60.0),
+            vote("rsi", 1.0, SignalType::Sell, 60.0),
+        ]);
+        assert_eq!(split.signal.signal_type, SignalType::Buy);
+        assert!((split.agreement - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn balanced_votes_hold() {
+        let merged = SignalAggregator::new(0.75).merge("BTC", 1.0, vec![
+            vote("ema", 1.0, SignalType::Buy, 60.0),
+            vote("macd", 1.0, SignalType::StrongSell, 60.0),
+            vote("rsi", 1.0, SignalType::Hold, 30.0),
+        ]);
+
+        assert_eq!(merged.signal.signal_type, SignalType::Hold);
+        assert!((merged.agreement - 1.0 / 3.0).abs() < 1e-9);
+        assert!((merged.signal.confidence - 10.0).abs() < 1e-9);
+        assert_eq!(SignalAggregator::new(0.75).merge("BTC", 1.0, Vec::new()).agreement, 0.0);
+    }
+}
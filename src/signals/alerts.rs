@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use super::aggregator::IndicatorResult;
+use super::{SignalType, TradingSignal};
+use crate::storage::alerts::{AlertAction, TradingViewAlert};
+
+/// Confidence given to a fresh strategy alert; its influence fades through the weight.
+const ALERT_CONFIDENCE: f64 = 100.0;
+
+/// Converts an alert carrying a buy/sell action into a signal. Alerts without an
+/// action are informational and produce nothing.
+pub fn to_trading_signal(alert: &TradingViewAlert) -> Option<TradingSignal> {
+    let signal_type = match alert.action? {
+        AlertAction::Buy => SignalType::Buy,
+        AlertAction::Sell => SignalType::Sell,
+    };
+
+    Some(TradingSignal {
+        symbol: alert.symbol.clone(),
+        signal_type,
+        confidence: ALERT_CONFIDENCE,
+        price: alert.price,
+        timestamp: alert.timestamp,
+        indicators: Vec::new(),
+    })
+}
+
+/// Linear decay from 1 at `age == 0` to 0 at `ttl` seconds.
+pub fn decay(age: i64, ttl: i64) -> f64 {
+    if ttl <= 0 || age >= ttl {
+        return 0.0;
+    }
+    1.0 - age.max(0) as f64 / ttl as f64
+}
+
+/// Aggregator components for the actionable alerts still inside `ttl`, weighted by
+/// `weight` scaled with their remaining decay. Only the latest alert of each
+/// `alert_name` counts, so a repeating alert cannot stack up, and the alerts together
+/// weigh at most `max_weight`.
+pub fn components(alerts: &[TradingViewAlert], now: i64, ttl: i64, weight: f64, max_weight: f64) -> Vec<IndicatorResult> {
+    let mut latest: HashMap<&str, TradingSignal> = HashMap::new();
+    for alert in alerts {
+        let Some(signal) = to_trading_signal(alert) else {
+            continue;
+        };
+        match latest.get(alert.alert_name.as_str()) {
+            Some(existing) if existing.timestamp >= signal.timestamp => {},
+            _ => {
+                latest.insert(&alert.alert_name, signal);
+            },
+        }
+    }
+
+    let mut components: Vec<IndicatorResult> = latest.into_iter()
+        .filter_map(|(alert_name, signal)| {
+            let remaining = decay(now - signal.timestamp, ttl);
+            (remaining > 0.0).then(|| IndicatorResult {
+                name: format!("tradingview:{}", alert_name),
+                weight: weight * remaining,
+                signal: signal.signal_type,
+                confidence: signal.confidence,
+                values: signal.indicators,
+            })
+        })
+        .collect();
+    components.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let total: f64 = components.iter().map(|c| c.weight).sum();
+    if total > max_weight {
+        let scale = max_weight.max(0.0) / total;
+        components.iter_mut().for_each(|c| c.weight *= scale);
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(name: &str, action: AlertAction, timestamp: i64) -> TradingViewAlert {
+        TradingViewAlert {
+            symbol: "BTC".to_string(),
+            price: 65_000.0,
+            alert_name: name.to_string(),
+            timestamp,
+            action: Some(action),
+            position_size: None,
+            interval: None,
+            exchange: None,
+            bar_time: None,
+            extra: None,
+        }
+    }
+
+    #[test]
+    fn keeps_only_the_latest_alert_per_name() {
+        let alerts: Vec<_> = (0..50).map(|i| alert("spam", AlertAction::Buy, 1_000 + i))
+            .chain([alert("spam", AlertAction::Sell, 1_060), alert("trend", AlertAction::Buy, 1_000)])
+            .collect();
+
+        let components = components(&alerts, 1_100, 1_000, 1.0, 10.0);
+
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].name, "tradingview:spam");
+        assert_eq!(components[0].signal, SignalType::Sell);
+        assert!((components[0].weight - 0.96).abs() < 1e-9);
+        assert_eq!(components[1].name, "tradingview:trend");
+    }
+
+    #[test]
+    fn caps_the_combined_alert_weight() {
+        let alerts: Vec<_> = (0..8).map(|i| alert(&format!("strategy-{}", i), AlertAction::Buy, 1_000)).collect();
+
+        let components = components(&alerts, 1_000, 1_000, 1.0, 2.0);
+
+        assert_eq!(components.len(), 8);
+        let total: f64 = components.iter().map(|c| c.weight).sum();
+        assert!((total - 2.0).abs() < 1e-9);
+    }
+}
@@ -60,10 +60,6 @@ impl SignalGenerator for EMASignal {
             indicators,
         })
     }
-
-    fn calculate(&self, prices: &[f64]) -> Vec<f64> {
-        self.calculate_ema(prices, self.short_period)
-    }
 }
 
 impl EMASignal {
@@ -80,11 +76,36 @@ impl EMASignal {
         emas.push(first_sma);
         
         // Calculate EMA for remaining values
-        for i in period..prices.len() {
-            let ema = (prices[i] - emas.last().unwrap()) * multiplier + emas.last().unwrap();
+        for price in &prices[period..] {
+            let ema = (price - emas.last().unwrap()) * multiplier + emas.last().unwrap();
             emas.push(ema);
         }
         
         emas
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ema(short_period: usize, long_period: usize) -> EMASignal {
+        EMASignal { short_period, long_period, symbol: "BTC".to_string() }
+    }
+
+    #[test]
+    fn seeds_with_the_sma_then_smooths() {
+        // Multiplier 2 / (3 + 1) = 0.5 after an SMA of 2
+        assert_eq!(ema(3, 5).calculate_ema(&[1.0, 2.0, 3.0, 4.0, 5.0], 3), [2.0, 3.0, 4.0]);
+        assert!(ema(3, 5).calculate_ema(&[1.0, 2.0], 3).is_empty());
+    }
+
+    #[test]
+    fn short_ema_above_long_is_a_buy() {
+        let rising: Vec<f64> = (1..=20).map(f64::from).collect();
+        let falling: Vec<f64> = rising.iter().rev().copied().collect();
+
+        assert_eq!(ema(3, 10).generate_signal(&rising).unwrap().signal_type, SignalType::Buy);
+        assert_eq!(ema(3, 10).generate_signal(&falling).unwrap().signal_type, SignalType::Sell);
+        assert!(ema(3, 10).generate_signal(&rising[..9]).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+use super::aggregator::SignalAggregator;
+use super::ema::EMASignal;
+use super::macd::MACDSignal;
+use super::rsi::RSISignal;
+use super::SignalGenerator;
+use crate::config::Config;
+
+/// Builds the generator registered under `name` from the configured periods.
+fn build_generator(name: &str, symbol: &str, config: &Config) -> Option<Box<dyn SignalGenerator>> {
+    match name {
+        "ema" => Some(Box::new(EMASignal {
+            short_period: config.ema_short_period,
+            long_period: config.ema_long_period,
+            symbol: symbol.to_string(),
+        })),
+        "rsi" => Some(Box::new(RSISignal {
+            period: config.rsi_period,
+            overbought: config.rsi_overbought,
+            oversold: config.rsi_oversold,
+            symbol: symbol.to_string(),
+        })),
+        "macd" => Some(Box::new(MACDSignal {
+            fast_period: config.macd_fast,
+            slow_period: config.macd_slow,
+            signal_period: config.macd_signal,
+            symbol: symbol.to_string(),
+        })),
+        _ => None,
+    }
+}
+
+/// Aggregator over the generators listed in `Config::signal_weights`.
+pub fn build_aggregator(symbol: &str, config: &Config) -> SignalAggregator {
+    let mut aggregator = SignalAggregator::new(config.strong_signal_threshold);
+
+    for (name, weight) in &config.signal_weights {
+        match build_generator(name, symbol, config) {
+            Some(generator) => aggregator = aggregator.with_generator(name, *weight, generator),
+            None => println!("⚠️ Unknown signal generator in config: {}", name),
+        }
+    }
+
+    aggregator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_configured_generators() {
+        let mut config = Config::from_env().unwrap();
+        config.signal_weights = vec![("ema".to_string(), 1.0), ("unknown".to_string(), 1.0), ("rsi".to_string(), 0.5)];
+
+        let prices: Vec<f64> = (1..=40).map(f64::from).collect();
+        let analysis = build_aggregator("BTC", &config).aggregate("BTC", &prices).unwrap();
+        let names: Vec<&str> = analysis.components.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["ema", "rsi"]);
+        assert_eq!(analysis.components[1].weight, 0.5);
+    }
+}
@@ -70,11 +70,6 @@ impl SignalGenerator for MACDSignal {
             indicators,
         })
     }
-
-    fn calculate(&self, prices: &[f64]) -> Vec<f64> {
-        let (macd_line, _, _) = self.calculate_macd(prices);
-        macd_line
-    }
 }
 
 impl MACDSignal {
@@ -131,11 +126,41 @@ impl MACDSignal {
         emas.push(first_sma);
         
         // Calculate EMA for remaining values
-        for i in period..prices.len() {
-            let ema = (prices[i] - emas.last().unwrap()) * multiplier + emas.last().unwrap();
+        for price in &prices[period..] {
+            let ema = (price - emas.last().unwrap()) * multiplier + emas.last().unwrap();
             emas.push(ema);
         }
         
         emas
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn macd() -> MACDSignal {
+        MACDSignal { fast_period: 2, slow_period: 3, signal_period: 2, symbol: "BTC".to_string() }
+    }
+
+    #[test]
+    fn linear_prices_have_a_flat_macd() {
+        let prices: Vec<f64> = (1..=6).map(f64::from).collect();
+        let (macd_line, signal_line, histogram) = macd().calculate_macd(&prices);
+
+        // EMA(2) runs half a step above EMA(3) on a straight line
+        assert_eq!(macd_line, [0.5, 0.5, 0.5, 0.5]);
+        assert_eq!(signal_line, [0.5, 0.5, 0.5]);
+        assert_eq!(histogram, [0.0, 0.0, 0.0]);
+        assert_eq!(macd().generate_signal(&prices).unwrap().signal_type, SignalType::Hold);
+    }
+
+    #[test]
+    fn accelerating_prices_cross_the_signal_line() {
+        let accelerating: Vec<f64> = (1..=12).map(|i| f64::from(i * i)).collect();
+        let collapsing: Vec<f64> = (1..=12).map(|i| 1_000.0 - f64::from(i * i)).collect();
+
+        assert_eq!(macd().generate_signal(&accelerating).unwrap().signal_type, SignalType::Buy);
+        assert_eq!(macd().generate_signal(&collapsing).unwrap().signal_type, SignalType::Sell);
+        assert!(macd().generate_signal(&accelerating[..4]).is_err());
+    }
+}
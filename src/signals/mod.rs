@@ -1,10 +1,13 @@
+pub mod aggregator;
+pub mod alerts;
 pub mod ema;
+pub mod engine;
 pub mod macd;
 pub mod rsi;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SignalType {
     Buy,
     Sell,
@@ -13,6 +16,19 @@ pub enum SignalType {
     StrongSell,
 }
 
+impl SignalType {
+    /// Snake-case name used by the HTTP API and the AI explainer.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignalType::Buy => "buy",
+            SignalType::Sell => "sell",
+            SignalType::Hold => "hold",
+            SignalType::StrongBuy => "strong_buy",
+            SignalType::StrongSell => "strong_sell",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradingSignal {
     pub symbol: String,
@@ -32,5 +48,4 @@ pub struct IndicatorValue {
 
 pub trait SignalGenerator {
     fn generate_signal(&self, prices: &[f64]) -> Result<TradingSignal, Box<dyn std::error::Error>>;
-    fn calculate(&self, prices: &[f64]) -> Vec<f64>;
-}
\ No newline at end of file
+}
@@ -70,10 +70,6 @@ impl SignalGenerator for RSISignal {
             indicators,
         })
     }
-
-    fn calculate(&self, prices: &[f64]) -> Vec<f64> {
-        self.calculate_rsi(prices)
-    }
 }
 
 impl RSISignal {
@@ -125,4 +121,34 @@ impl RSISignal {
         
         rsi_values
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rsi(period: usize) -> RSISignal {
+        RSISignal { period, overbought: 70.0, oversold: 30.0, symbol: "BTC".to_string() }
+    }
+
+    #[test]
+    fn uses_wilder_smoothing() {
+        let values = rsi(3).calculate_rsi(&[1.0, 2.0, 1.0, 2.0, 1.0]);
+        // Gains 2/3 and losses 1/3, then (2/3 * 2 + 0) / 3 against (1/3 * 2 + 1) / 3
+        assert_eq!(values.len(), 2);
+        assert!((values[0] - 200.0 / 3.0).abs() < 1e-9);
+        assert!((values[1] - 400.0 / 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn extremes_signal_against_the_move() {
+        let rising: Vec<f64> = (1..=10).map(f64::from).collect();
+        let falling: Vec<f64> = rising.iter().rev().copied().collect();
+
+        let overbought = rsi(5).generate_signal(&rising).unwrap();
+        assert_eq!((overbought.signal_type, overbought.indicators[0].value), (SignalType::Sell, 100.0));
+        let oversold = rsi(5).generate_signal(&falling).unwrap();
+        assert_eq!((oversold.signal_type, oversold.confidence), (SignalType::Buy, 100.0));
+        assert_eq!(rsi(3).generate_signal(&[1.0, 2.0, 1.0, 2.0]).unwrap().signal_type, SignalType::Hold);
+        assert!(rsi(5).generate_signal(&rising[..5]).is_err());
+    }
+}
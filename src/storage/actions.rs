@@ -0,0 +1,365 @@
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use super::Database;
+
+/// An on-chain action as created through `/conditions/{id}/actions`. String values in
+/// `accounts` and `args` are templates, see `blockchain::actions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionSpec {
+    /// `subscription_program` instruction name, e.g. `subscribe`.
+    pub instruction: String,
+    /// Instruction account name to pubkey template. Accounts with a fixed IDL address may be left out.
+    #[serde(default)]
+    pub accounts: BTreeMap<String, String>,
+    #[serde(default)]
+    pub args: Map<String, Value>,
+    /// Only simulate the transaction when the condition fires.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Template of the key that stops the action from being sent twice.
+    pub idempotency_key: Option<String>,
+    /// Length of the `{{window}}` bucket, for custom idempotency keys.
+    pub idempotency_window_seconds: Option<i64>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConditionAction {
+    pub id: i64,
+    pub condition_id: i64,
+    #[serde(flatten)]
+    pub spec: ActionSpec,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunMode {
+    Simulate,
+    Send,
+}
+
+impl RunMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunMode::Simulate => "simulate",
+            RunMode::Send => "send",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "simulate" => Some(RunMode::Simulate),
+            "send" => Some(RunMode::Send),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Pending,
+    Simulated,
+    Submitted,
+    /// Not executed on-chain: building, simulating or the program rejected it.
+    Failed,
+    /// Sending failed in a way that may still have landed; never retried automatically.
+    Unconfirmed,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Pending => "pending",
+            RunStatus::Simulated => "simulated",
+            RunStatus::Submitted => "submitted",
+            RunStatus::Failed => "failed",
+            RunStatus::Unconfirmed => "unconfirmed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(RunStatus::Pending),
+            "simulated" => Some(RunStatus::Simulated),
+            "submitted" => Some(RunStatus::Submitted),
+            "failed" => Some(RunStatus::Failed),
+            "unconfirmed" => Some(RunStatus::Unconfirmed),
+            _ => None,
+        }
+    }
+}
+
+/// One execution of an action, simulated or sent.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionRun {
+    pub id: i64,
+    pub action_id: i64,
+    pub condition_id: i64,
+    /// Unset for simulations, which cannot spend anything.
+    pub idempotency_key: Option<String>,
+    pub mode: RunMode,
+    pub status: RunStatus,
+    pub instruction: String,
+    /// Rendered accounts and args.
+    pub args: Value,
+    /// Price the condition fired at.
+    pub price: Option<f64>,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+    /// Program logs of a simulation.
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    pub attempts: u32,
+    /// Later triggers that reused the idempotency key and were skipped.
+    pub duplicates: u32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Filters for `/action-log`. Results are newest first.
+#[derive(Debug, Default, Deserialize)]
+pub struct ActionRunQuery {
+    pub action_id: Option<i64>,
+    pub condition_id: Option<i64>,
+    pub status: Option<String>,
+    pub limit: Option<u32>,
+}
+
+const ACTION_COLUMNS: &str = "id, condition_id, instruction, accounts, args, dry_run, idempotency_key,
+    idempotency_window_seconds, label, created_at";
+
+const RUN_COLUMNS: &str = "id, action_id, condition_id, idempotency_key, mode, status, instruction, args, price,
+    signature, error, logs, units_consumed, attempts, duplicates, created_at, updated_at";
+
+fn json_column<T: serde::de::DeserializeOwned + Default>(row: &Row, index: usize) -> rusqlite::Result<T> {
+    let text: Option<String> = row.get(index)?;
+    Ok(text.and_then(|t| serde_json::from_str(&t).ok()).unwrap_or_default())
+}
+
+fn action_from_row(row: &Row) -> rusqlite::Result<ConditionAction> {
+    Ok(ConditionAction {
+        id: row.get(0)?,
+        condition_id: row.get(1)?,
+        spec: ActionSpec {
+            instruction: row.get(2)?,
+            accounts: json_column(row, 3)?,
+            args: json_column(row, 4)?,
+            dry_run: row.get(5)?,
+            idempotency_key: row.get(6)?,
+            idempotency_window_seconds: row.get(7)?,
+            label: row.get(8)?,
+        },
+        created_at: row.get(9)?,
+    })
+}
+
+fn run_from_row(row: &Row) -> rusqlite::Result<ActionRun> {
+    let mode: String = row.get(4)?;
+    let status: String = row.get(5)?;
+    Ok(ActionRun {
+        id: row.get(0)?,
+        action_id: row.get(1)?,
+        condition_id: row.get(2)?,
+        idempotency_key: row.get(3)?,
+        mode: RunMode::parse(&mode).unwrap_or(RunMode::Send),
+        status: RunStatus::parse(&status).unwrap_or(RunStatus::Pending),
+        instruction: row.get(6)?,
+        args: json_column(row, 7)?,
+        price: row.get(8)?,
+        signature: row.get(9)?,
+        error: row.get(10)?,
+        logs: json_column(row, 11)?,
+        units_consumed: row.get::<_, Option<i64>>(12)?.map(|u| u as u64),
+        attempts: row.get(13)?,
+        duplicates: row.get(14)?,
+        created_at: row.get(15)?,
+        updated_at: row.get(16)?,
+    })
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+impl Database {
+    pub fn insert_action(&self, condition_id: i64, spec: &ActionSpec, now: i64) -> Result<i64, String> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO condition_actions
+             (condition_id, instruction, accounts, args, dry_run, idempotency_key, idempotency_window_seconds, label, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                condition_id,
+                spec.instruction,
+                to_json(&spec.accounts),
+                to_json(&spec.args),
+                spec.dry_run,
+                spec.idempotency_key,
+                spec.idempotency_window_seconds,
+                spec.label,
+                now,
+            ],
+        ).map_err(|e| format!("Insert action failed: {}", e))?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn update_action(&self, id: i64, spec: &ActionSpec) -> Result<bool, String> {
+        self.conn()
+            .execute(
+                "UPDATE condition_actions
+                 SET instruction = ?2, accounts = ?3, args = ?4, dry_run = ?5, idempotency_key = ?6,
+                     idempotency_window_seconds = ?7, label = ?8
+                 WHERE id = ?1",
+                params![
+                    id,
+                    spec.instruction,
+                    to_json(&spec.accounts),
+                    to_json(&spec.args),
+                    spec.dry_run,
+                    spec.idempotency_key,
+                    spec.idempotency_window_seconds,
+                    spec.label,
+                ],
+            )
+            .map(|updated| updated > 0)
+            .map_err(|e| format!("Update action failed: {}", e))
+    }
+
+    /// Removes the action. Its runs stay in the action log.
+    pub fn delete_action(&self, id: i64) -> Result<bool, String> {
+        self.conn()
+            .execute("DELETE FROM condition_actions WHERE id = ?1", params![id])
+            .map(|removed| removed > 0)
+            .map_err(|e| format!("Delete action failed: {}", e))
+    }
+
+    pub fn action(&self, id: i64) -> Result<Option<ConditionAction>, String> {
+        self.conn()
+            .query_row(
+                &format!("SELECT {} FROM condition_actions WHERE id = ?1", ACTION_COLUMNS),
+                params![id],
+                action_from_row,
+            )
+            .optional()
+            .map_err(|e| format!("Query action failed: {}", e))
+    }
+
+    /// Actions of `condition_id` (all conditions when `None`), oldest first.
+    pub fn actions(&self, condition_id: Option<i64>) -> Result<Vec<ConditionAction>, String> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM condition_actions WHERE ?1 IS NULL OR condition_id = ?1 ORDER BY id ASC",
+                ACTION_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let actions = stmt.query_map(params![condition_id], action_from_row)
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Query actions failed: {}", e));
+        actions
+    }
+
+    /// Starts `run`, returning its id, unless its idempotency key was already used.
+    /// A key whose run `Failed` (nothing reached the chain) may be claimed again. An
+    /// `Unconfirmed` send may still have landed, so like every other reuse it is
+    /// counted as a duplicate and returns `None`.
+    pub fn claim_action_run(&self, run: &ActionRun) -> Result<Option<i64>, String> {
+        let conn = self.conn();
+        let inserted = conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO action_runs ({}) VALUES (NULL, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, NULL, NULL, NULL, NULL, 1, 0, ?9, ?9)",
+                RUN_COLUMNS
+            ),
+            params![
+                run.action_id,
+                run.condition_id,
+                run.idempotency_key,
+                run.mode.as_str(),
+                run.status.as_str(),
+                run.instruction,
+                to_json(&run.args),
+                run.price,
+                run.created_at,
+            ],
+        ).map_err(|e| format!("Insert action run failed: {}", e))?;
+        if inserted > 0 {
+            return Ok(Some(conn.last_insert_rowid()));
+        }
+
+        let retried = conn.query_row(
+            "UPDATE action_runs
+             SET status = ?2, args = ?3, price = ?4, signature = NULL, error = NULL, logs = NULL, units_consumed = NULL,
+                 attempts = attempts + 1, updated_at = ?5
+             WHERE idempotency_key = ?1 AND status = 'failed'
+             RETURNING id",
+            params![run.idempotency_key, run.status.as_str(), to_json(&run.args), run.price, run.created_at],
+            |row| row.get(0),
+        ).optional().map_err(|e| format!("Retry action run failed: {}", e))?;
+        if retried.is_some() {
+            return Ok(retried);
+        }
+
+        conn.execute(
+            "UPDATE action_runs SET duplicates = duplicates + 1, updated_at = ?2 WHERE idempotency_key = ?1",
+            params![run.idempotency_key, run.created_at],
+        ).map_err(|e| format!("Update action run failed: {}", e))?;
+        Ok(None)
+    }
+
+    /// Saves the outcome of a claimed run.
+    pub fn finish_action_run(&self, run: &ActionRun) -> Result<(), String> {
+        self.conn()
+            .execute(
+                "UPDATE action_runs
+                 SET status = ?2, args = ?3, signature = ?4, error = ?5, logs = ?6, units_consumed = ?7, updated_at = ?8
+                 WHERE id = ?1",
+                params![
+                    run.id,
+                    run.status.as_str(),
+                    to_json(&run.args),
+                    run.signature,
+                    run.error,
+                    to_json(&run.logs),
+                    run.units_consumed.map(|u| u as i64),
+                    run.updated_at,
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Update action run failed: {}", e))
+    }
+
+    pub fn action_run(&self, id: i64) -> Result<Option<ActionRun>, String> {
+        self.conn()
+            .query_row(
+                &format!("SELECT {} FROM action_runs WHERE id = ?1", RUN_COLUMNS),
+                params![id],
+                run_from_row,
+            )
+            .optional()
+            .map_err(|e| format!("Query action run failed: {}", e))
+    }
+
+    pub fn action_runs(&self, query: &ActionRunQuery) -> Result<Vec<ActionRun>, String> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM action_runs
+                 WHERE (?1 IS NULL OR action_id = ?1) AND (?2 IS NULL OR condition_id = ?2) AND (?3 IS NULL OR status = ?3)
+                 ORDER BY id DESC LIMIT ?4",
+                RUN_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let runs = stmt.query_map(
+                params![query.action_id, query.condition_id, query.status, query.limit.unwrap_or(100).clamp(1, 1000)],
+                run_from_row,
+            )
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Query action runs failed: {}", e));
+        runs
+    }
+}
@@ -0,0 +1,274 @@
+use rusqlite::{params, params_from_iter, types::Value};
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+/// Order side from a strategy alert (`{{strategy.order.action}}`).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertAction {
+    Buy,
+    Sell,
+}
+
+impl AlertAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertAction::Buy => "buy",
+            AlertAction::Sell => "sell",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "buy" | "long" => Some(AlertAction::Buy),
+            "sell" | "short" => Some(AlertAction::Sell),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Deserialize)]
+pub struct TradingViewAlert {
+    pub symbol: String,
+    pub price: f64,
+    pub alert_name: String,
+    /// When the alert was received (Unix seconds).
+    pub timestamp: i64,
+    pub action: Option<AlertAction>,
+    /// Strategy position after the order (`{{strategy.position_size}}`), signed.
+    pub position_size: Option<f64>,
+    /// Chart interval, e.g. `60` or `1D`.
+    pub interval: Option<String>,
+    pub exchange: Option<String>,
+    /// Bar time from `{{time}}` (Unix seconds).
+    pub bar_time: Option<i64>,
+    /// Free-form JSON passed through from the alert message.
+    pub extra: Option<serde_json::Value>,
+}
+
+/// Filters for listing alerts. Results are newest first.
+#[derive(Debug, Default, Deserialize)]
+pub struct AlertQuery {
+    pub symbol: Option<String>,
+    /// Unix seconds, inclusive.
+    pub from: Option<i64>,
+    /// Unix seconds, inclusive.
+    pub to: Option<i64>,
+    pub alert_name: Option<String>,
+    pub action: Option<AlertAction>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    /// Restricts results to these symbols, e.g. those a caller's plan covers. Empty means all.
+    #[serde(skip)]
+    pub symbols: Vec<String>,
+}
+
+impl AlertQuery {
+    pub const DEFAULT_LIMIT: u32 = 50;
+    pub const MAX_LIMIT: u32 = 500;
+
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT).clamp(1, Self::MAX_LIMIT)
+    }
+
+    pub fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+
+    fn where_clause(&self) -> (String, Vec<Value>) {
+        let mut conditions: Vec<String> = Vec::new();
+        let mut values = Vec::new();
+
+        if let Some(symbol) = &self.symbol {
+            conditions.push("symbol = ?".into());
+            values.push(Value::Text(symbol.to_uppercase()));
+        }
+        if let Some(from) = self.from {
+            conditions.push("timestamp >= ?".into());
+            values.push(Value::Integer(from));
+        }
+        if let Some(to) = self.to {
+            conditions.push("timestamp <= ?".into());
+            values.push(Value::Integer(to));
+        }
+        if let Some(name) = &self.alert_name {
+            conditions.push("alert_name = ?".into());
+            values.push(Value::Text(name.clone()));
+        }
+        if let Some(action) = self.action {
+            conditions.push("action = ?".into());
+            values.push(Value::Text(action.as_str().to_string()));
+        }
+        if !self.symbols.is_empty() {
+            conditions.push(format!("symbol IN ({})", vec!["?"; self.symbols.len()].join(", ")));
+            values.extend(self.symbols.iter().map(|s| Value::Text(s.to_uppercase())));
+        }
+
+        if conditions.is_empty() {
+            (String::new(), values)
+        } else {
+            (format!("WHERE {}", conditions.join(" AND ")), values)
+        }
+    }
+}
+
+impl Database {
+    pub fn insert_alert(&self, alert: &TradingViewAlert) -> Result<i64, String> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO alerts (symbol, price, alert_name, timestamp, action, position_size, interval, exchange, bar_time, extra)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                alert.symbol,
+                alert.price,
+                alert.alert_name,
+                alert.timestamp,
+                alert.action.map(|a| a.as_str()),
+                alert.position_size,
+                alert.interval,
+                alert.exchange,
+                alert.bar_time,
+                alert.extra.as_ref().map(|e| e.to_string()),
+            ],
+        ).map_err(|e| format!("Insert alert failed: {}", e))?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Matching alerts (newest first) and the total number of matches.
+    pub fn query_alerts(&self, query: &AlertQuery) -> Result<(Vec<TradingViewAlert>, u64), String> {
+        let (where_clause, mut values) = query.where_clause();
+        let conn = self.conn();
+
+        let total: u64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM alerts {}", where_clause),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        ).map_err(|e| format!("Count alerts failed: {}", e))?;
+
+        values.push(Value::Integer(query.limit() as i64));
+        values.push(Value::Integer(query.offset() as i64));
+
+        let mut statement = conn.prepare(&format!(
+            "SELECT symbol, price, alert_name, timestamp, action, position_size, interval, exchange, bar_time, extra
+             FROM alerts {}
+             ORDER BY timestamp DESC, id DESC LIMIT ? OFFSET ?",
+            where_clause
+        )).map_err(|e| format!("Query alerts failed: {}", e))?;
+
+        let alerts = statement.query_map(params_from_iter(values.iter()), |row| {
+            Ok(TradingViewAlert {
+                symbol: row.get(0)?,
+                price: row.get(1)?,
+                alert_name: row.get(2)?,
+                timestamp: row.get(3)?,
+                action: row.get::<_, Option<String>>(4)?.as_deref().and_then(AlertAction::parse),
+                position_size: row.get(5)?,
+                interval: row.get(6)?,
+                exchange: row.get(7)?,
+                bar_time: row.get(8)?,
+                extra: row.get::<_, Option<String>>(9)?.and_then(|e| serde_json::from_str(&e).ok()),
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Query alerts failed: {}", e))?;
+
+        Ok((alerts, total))
+    }
+
+    pub fn count_alerts(&self) -> Result<u64, String> {
+        self.conn()
+            .query_row("SELECT COUNT(*) FROM alerts", [], |row| row.get(0))
+            .map_err(|e| format!("Count alerts failed: {}", e))
+    }
+
+    pub fn clear_alerts(&self) -> Result<usize, String> {
+        self.conn()
+            .execute("DELETE FROM alerts", [])
+            .map_err(|e| format!("Clear alerts failed: {}", e))
+    }
+
+    /// Deletes alerts older than `cutoff` (Unix seconds). Returns how many were removed.
+    pub fn prune_alerts(&self, cutoff: i64) -> Result<usize, String> {
+        self.conn()
+            .execute("DELETE FROM alerts WHERE timestamp < ?1", params![cutoff])
+            .map_err(|e| format!("Prune alerts failed: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(symbol: &str, timestamp: i64, alert_name: &str, action: Option<AlertAction>) -> TradingViewAlert {
+        TradingViewAlert {
+            symbol: symbol.to_string(),
+            price: 100.0,
+            alert_name: alert_name.to_string(),
+            timestamp,
+            action,
+            position_size: None,
+            interval: Some("60".to_string()),
+            exchange: None,
+            bar_time: None,
+            extra: Some(serde_json::json!({ "note": timestamp })),
+        }
+    }
+
+    fn seeded() -> Database {
+        let db = Database::in_memory().unwrap();
+        for alert in [
+            alert("BTC", 100, "breakout", Some(AlertAction::Buy)),
+            alert("ETH", 200, "breakout", Some(AlertAction::Sell)),
+            alert("BTC", 300, "reversal", Some(AlertAction::Sell)),
+            alert("SOL", 400, "reversal", None),
+        ] {
+            db.insert_alert(&alert).unwrap();
+        }
+        db
+    }
+
+    fn timestamps(db: &Database, query: &AlertQuery) -> (Vec<i64>, u64) {
+        let (alerts, total) = db.query_alerts(query).unwrap();
+        (alerts.iter().map(|a| a.timestamp).collect(), total)
+    }
+
+    #[test]
+    fn filters_newest_first() {
+        let db = seeded();
+
+        assert_eq!(timestamps(&db, &AlertQuery::default()), (vec![400, 300, 200, 100], 4));
+        assert_eq!(timestamps(&db, &AlertQuery { symbol: Some("btc".into()), ..Default::default() }), (vec![300, 100], 2));
+        assert_eq!(timestamps(&db, &AlertQuery { from: Some(200), to: Some(300), ..Default::default() }), (vec![300, 200], 2));
+        assert_eq!(timestamps(&db, &AlertQuery { alert_name: Some("breakout".into()), ..Default::default() }), (vec![200, 100], 2));
+        assert_eq!(timestamps(&db, &AlertQuery { action: Some(AlertAction::Sell), ..Default::default() }), (vec![300, 200], 2));
+        assert_eq!(timestamps(&db, &AlertQuery { symbols: vec!["eth".into(), "SOL".into()], ..Default::default() }), (vec![400, 200], 2));
+
+        let (alerts, _) = db.query_alerts(&AlertQuery { symbol: Some("SOL".into()), ..Default::default() }).unwrap();
+        assert_eq!(alerts[0].action, None);
+        assert_eq!(alerts[0].extra, Some(serde_json::json!({ "note": 400 })));
+    }
+
+    #[test]
+    fn pages_with_a_clamped_limit() {
+        let db = seeded();
+
+        let page = AlertQuery { limit: Some(2), offset: Some(1), ..Default::default() };
+        assert_eq!(timestamps(&db, &page), (vec![300, 200], 4));
+        assert_eq!(timestamps(&db, &AlertQuery { offset: Some(10), ..Default::default() }), (vec![], 4));
+
+        assert_eq!(AlertQuery::default().limit(), AlertQuery::DEFAULT_LIMIT);
+        assert_eq!(AlertQuery { limit: Some(0), ..Default::default() }.limit(), 1);
+        assert_eq!(AlertQuery { limit: Some(10_000), ..Default::default() }.limit(), AlertQuery::MAX_LIMIT);
+    }
+
+    #[test]
+    fn prunes_alerts_before_the_cutoff() {
+        let db = seeded();
+
+        assert_eq!(db.prune_alerts(300).unwrap(), 2);
+        assert_eq!(timestamps(&db, &AlertQuery::default()), (vec![400, 300], 2));
+        assert_eq!(db.clear_alerts().unwrap(), 2);
+        assert_eq!(db.count_alerts().unwrap(), 0);
+    }
+}
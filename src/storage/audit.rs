@@ -0,0 +1,52 @@
+use rusqlite::params;
+use serde::Serialize;
+
+use super::Database;
+
+/// A rejected webhook request.
+#[derive(Debug, Serialize, Clone)]
+pub struct AuditEntry {
+    pub timestamp: i64,
+    /// Source whose secret matched, when the failure happened after identification.
+    pub source: Option<String>,
+    pub remote_addr: Option<String>,
+    pub reason: String,
+}
+
+impl Database {
+    pub fn insert_audit_entry(&self, entry: &AuditEntry) -> Result<(), String> {
+        self.conn()
+            .execute(
+                "INSERT INTO webhook_audit (timestamp, source, remote_addr, reason) VALUES (?1, ?2, ?3, ?4)",
+                params![entry.timestamp, entry.source, entry.remote_addr, entry.reason],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Insert audit entry failed: {}", e))
+    }
+
+    /// Most recent entries first.
+    pub fn recent_audit_entries(&self, limit: u32) -> Result<Vec<AuditEntry>, String> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT timestamp, source, remote_addr, reason FROM webhook_audit
+             ORDER BY timestamp DESC, id DESC LIMIT ?1",
+        ).map_err(|e| format!("Query audit log failed: {}", e))?;
+
+        statement.query_map(params![limit], |row| {
+            Ok(AuditEntry {
+                timestamp: row.get(0)?,
+                source: row.get(1)?,
+                remote_addr: row.get(2)?,
+                reason: row.get(3)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Query audit log failed: {}", e))
+    }
+
+    pub fn prune_audit_entries(&self, cutoff: i64) -> Result<usize, String> {
+        self.conn()
+            .execute("DELETE FROM webhook_audit WHERE timestamp < ?1", params![cutoff])
+            .map_err(|e| format!("Prune audit log failed: {}", e))
+    }
+}
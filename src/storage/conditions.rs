@@ -0,0 +1,261 @@
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOperator {
+    #[serde(rename = ">", alias = "above")]
+    Above,
+    #[serde(rename = "<", alias = "below")]
+    Below,
+    CrossesAbove,
+    CrossesBelow,
+    /// Change over `window_seconds` of at least `value` percent; negative values watch for drops.
+    #[serde(alias = "pct_change")]
+    PercentChange,
+}
+
+impl ConditionOperator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConditionOperator::Above => ">",
+            ConditionOperator::Below => "<",
+            ConditionOperator::CrossesAbove => "crosses_above",
+            ConditionOperator::CrossesBelow => "crosses_below",
+            ConditionOperator::PercentChange => "percent_change",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            ">" | "above" => Some(ConditionOperator::Above),
+            "<" | "below" => Some(ConditionOperator::Below),
+            "crosses_above" => Some(ConditionOperator::CrossesAbove),
+            "crosses_below" => Some(ConditionOperator::CrossesBelow),
+            "percent_change" | "pct_change" => Some(ConditionOperator::PercentChange),
+            _ => None,
+        }
+    }
+}
+
+/// When a condition that has fired may fire again.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RearmPolicy {
+    /// Never, unless re-armed through the API.
+    #[default]
+    Once,
+    /// After `cooldown_seconds`.
+    Cooldown,
+    /// Once the condition stops holding, e.g. the price falls back below the level.
+    Reset,
+}
+
+impl RearmPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RearmPolicy::Once => "once",
+            RearmPolicy::Cooldown => "cooldown",
+            RearmPolicy::Reset => "reset",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "once" => Some(RearmPolicy::Once),
+            "cooldown" => Some(RearmPolicy::Cooldown),
+            "reset" => Some(RearmPolicy::Reset),
+            _ => None,
+        }
+    }
+}
+
+/// A price condition as created through `/conditions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionSpec {
+    pub symbol: String,
+    pub operator: ConditionOperator,
+    /// Price level, or percentage for `percent_change`.
+    pub value: f64,
+    /// Lookback for `percent_change`.
+    pub window_seconds: Option<i64>,
+    #[serde(default)]
+    pub rearm: RearmPolicy,
+    pub cooldown_seconds: Option<i64>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceCondition {
+    pub id: i64,
+    #[serde(flatten)]
+    pub spec: ConditionSpec,
+    /// Whether the condition can fire on the next price update.
+    pub armed: bool,
+    pub trigger_count: u32,
+    pub last_triggered_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConditionTrigger {
+    /// One per firing, so actions key their sends on it. 0 until recorded.
+    pub id: i64,
+    pub condition_id: i64,
+    pub symbol: String,
+    pub price: f64,
+    /// The previous price for crossings, the window's first price for `percent_change`.
+    pub reference_price: Option<f64>,
+    pub triggered_at: i64,
+}
+
+const CONDITION_COLUMNS: &str = "id, symbol, operator, value, window_seconds, rearm, cooldown_seconds, label,
+    armed, trigger_count, last_triggered_at, created_at";
+
+fn condition_from_row(row: &Row) -> rusqlite::Result<PriceCondition> {
+    let operator: String = row.get(2)?;
+    let rearm: String = row.get(5)?;
+    Ok(PriceCondition {
+        id: row.get(0)?,
+        spec: ConditionSpec {
+            symbol: row.get(1)?,
+            operator: ConditionOperator::parse(&operator).unwrap_or(ConditionOperator::Above),
+            value: row.get(3)?,
+            window_seconds: row.get(4)?,
+            rearm: RearmPolicy::parse(&rearm).unwrap_or_default(),
+            cooldown_seconds: row.get(6)?,
+            label: row.get(7)?,
+        },
+        armed: row.get(8)?,
+        trigger_count: row.get(9)?,
+        last_triggered_at: row.get(10)?,
+        created_at: row.get(11)?,
+    })
+}
+
+impl Database {
+    pub fn insert_condition(&self, spec: &ConditionSpec, now: i64) -> Result<i64, String> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO price_conditions (symbol, operator, value, window_seconds, rearm, cooldown_seconds, label, armed, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1, ?8)",
+            params![
+                spec.symbol,
+                spec.operator.as_str(),
+                spec.value,
+                spec.window_seconds,
+                spec.rearm.as_str(),
+                spec.cooldown_seconds,
+                spec.label,
+                now,
+            ],
+        ).map_err(|e| format!("Insert condition failed: {}", e))?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Replaces the spec of condition `id` and re-arms it.
+    pub fn update_condition(&self, id: i64, spec: &ConditionSpec) -> Result<bool, String> {
+        self.conn()
+            .execute(
+                "UPDATE price_conditions
+                 SET symbol = ?2, operator = ?3, value = ?4, window_seconds = ?5, rearm = ?6, cooldown_seconds = ?7, label = ?8, armed = 1
+                 WHERE id = ?1",
+                params![
+                    id,
+                    spec.symbol,
+                    spec.operator.as_str(),
+                    spec.value,
+                    spec.window_seconds,
+                    spec.rearm.as_str(),
+                    spec.cooldown_seconds,
+                    spec.label,
+                ],
+            )
+            .map(|updated| updated > 0)
+            .map_err(|e| format!("Update condition failed: {}", e))
+    }
+
+    pub fn set_condition_armed(&self, id: i64, armed: bool) -> Result<bool, String> {
+        self.conn()
+            .execute("UPDATE price_conditions SET armed = ?2 WHERE id = ?1", params![id, armed])
+            .map(|updated| updated > 0)
+            .map_err(|e| format!("Update condition failed: {}", e))
+    }
+
+    pub fn delete_condition(&self, id: i64) -> Result<bool, String> {
+        let conn = self.conn();
+        conn.execute("DELETE FROM condition_triggers WHERE condition_id = ?1", params![id])
+            .map_err(|e| format!("Delete condition failed: {}", e))?;
+        conn.execute("DELETE FROM condition_actions WHERE condition_id = ?1", params![id])
+            .map_err(|e| format!("Delete condition failed: {}", e))?;
+        conn.execute("DELETE FROM price_conditions WHERE id = ?1", params![id])
+            .map(|removed| removed > 0)
+            .map_err(|e| format!("Delete condition failed: {}", e))
+    }
+
+    pub fn condition(&self, id: i64) -> Result<Option<PriceCondition>, String> {
+        self.conn()
+            .query_row(
+                &format!("SELECT {} FROM price_conditions WHERE id = ?1", CONDITION_COLUMNS),
+                params![id],
+                condition_from_row,
+            )
+            .optional()
+            .map_err(|e| format!("Query condition failed: {}", e))
+    }
+
+    /// Conditions on `symbol` (all symbols when `None`), oldest first.
+    pub fn conditions(&self, symbol: Option<&str>) -> Result<Vec<PriceCondition>, String> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM price_conditions WHERE ?1 IS NULL OR symbol = ?1 ORDER BY id ASC",
+                CONDITION_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let conditions = stmt.query_map(params![symbol], condition_from_row)
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Query conditions failed: {}", e));
+        conditions
+    }
+
+    /// Records a trigger and disarms the condition until its re-arm policy allows another.
+    /// Returns the trigger's id.
+    pub fn record_trigger(&self, trigger: &ConditionTrigger) -> Result<i64, String> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO condition_triggers (condition_id, symbol, price, reference_price, triggered_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![trigger.condition_id, trigger.symbol, trigger.price, trigger.reference_price, trigger.triggered_at],
+        ).map_err(|e| format!("Insert trigger failed: {}", e))?;
+        let id = conn.last_insert_rowid();
+        conn.execute(
+            "UPDATE price_conditions SET armed = 0, trigger_count = trigger_count + 1, last_triggered_at = ?2 WHERE id = ?1",
+            params![trigger.condition_id, trigger.triggered_at],
+        ).map_err(|e| format!("Update condition failed: {}", e))?;
+        Ok(id)
+    }
+
+    /// Triggers of condition `id`, newest first.
+    pub fn condition_triggers(&self, id: i64, limit: u32) -> Result<Vec<ConditionTrigger>, String> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, condition_id, symbol, price, reference_price, triggered_at FROM condition_triggers
+                 WHERE condition_id = ?1 ORDER BY triggered_at DESC, id DESC LIMIT ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        let triggers = stmt.query_map(params![id, limit], |row| Ok(ConditionTrigger {
+                id: row.get(0)?,
+                condition_id: row.get(1)?,
+                symbol: row.get(2)?,
+                price: row.get(3)?,
+                reference_price: row.get(4)?,
+                triggered_at: row.get(5)?,
+            }))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Query triggers failed: {}", e));
+        triggers
+    }
+}
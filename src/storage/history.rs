@@ -0,0 +1,309 @@
+use rusqlite::{params, params_from_iter, types::Value, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+/// One decoded `subscription_program` instruction from an indexed transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionEvent {
+    pub signature: String,
+    /// Position of the instruction within its transaction.
+    pub instruction_index: u32,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// IDL instruction name, e.g. `subscribe`.
+    pub instruction: String,
+    pub user: Option<String>,
+    pub subscription: Option<String>,
+    /// `plan_tier` of `subscribe` or `new_tier` of `update_plan`.
+    pub plan_tier: Option<u8>,
+    /// Base units of the mint the subscription was paid in.
+    pub amount_paid: Option<u64>,
+    pub period_days: Option<i64>,
+    /// Whether the transaction succeeded. Failed ones are indexed too.
+    pub success: bool,
+}
+
+/// Signatures older than `before` and newer than `until` (or back to the first
+/// transaction when `until` is `None`) that the indexer has not reached yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexerGap {
+    pub before: String,
+    pub until: Option<String>,
+}
+
+/// Filters for `/admin/subscriptions/history`. Results are newest first.
+#[derive(Debug, Default, Deserialize)]
+pub struct HistoryQuery {
+    pub user: Option<String>,
+    pub subscription: Option<String>,
+    pub instruction: Option<String>,
+    pub plan_tier: Option<u8>,
+    /// Block time in Unix seconds, inclusive.
+    pub from: Option<i64>,
+    /// Block time in Unix seconds, inclusive.
+    pub to: Option<i64>,
+    pub success: Option<bool>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+impl HistoryQuery {
+    pub const DEFAULT_LIMIT: u32 = 50;
+    pub const MAX_LIMIT: u32 = 500;
+
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT).clamp(1, Self::MAX_LIMIT)
+    }
+
+    pub fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+
+    fn where_clause(&self) -> (String, Vec<Value>) {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        if let Some(user) = &self.user {
+            conditions.push("user = ?");
+            values.push(Value::Text(user.clone()));
+        }
+        if let Some(subscription) = &self.subscription {
+            conditions.push("subscription = ?");
+            values.push(Value::Text(subscription.clone()));
+        }
+        if let Some(instruction) = &self.instruction {
+            conditions.push("instruction = ?");
+            values.push(Value::Text(instruction.clone()));
+        }
+        if let Some(tier) = self.plan_tier {
+            conditions.push("plan_tier = ?");
+            values.push(Value::Integer(tier.into()));
+        }
+        if let Some(from) = self.from {
+            conditions.push("block_time >= ?");
+            values.push(Value::Integer(from));
+        }
+        if let Some(to) = self.to {
+            conditions.push("block_time <= ?");
+            values.push(Value::Integer(to));
+        }
+        if let Some(success) = self.success {
+            conditions.push("success = ?");
+            values.push(Value::Integer(success.into()));
+        }
+
+        if conditions.is_empty() {
+            (String::new(), values)
+        } else {
+            (format!("WHERE {}", conditions.join(" AND ")), values)
+        }
+    }
+}
+
+/// Totals over every event matching a `HistoryQuery`, ignoring `limit`/`offset`.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryTotals {
+    pub events: u64,
+    /// `amount_paid` of successful events, summed per payment mint.
+    pub amount_paid: Vec<MintTotal>,
+}
+
+/// `amount_paid` is in base units of `mint`, so amounts are only summed within one mint.
+#[derive(Debug, Clone, Serialize)]
+pub struct MintTotal {
+    /// `None` for subscriptions without a recorded token payment, whose unit is unknown.
+    pub mint: Option<String>,
+    pub amount_paid: u64,
+}
+
+impl Database {
+    /// Stores `event` unless that instruction of that transaction is already indexed.
+    pub fn insert_subscription_event(&self, event: &SubscriptionEvent) -> Result<bool, String> {
+        self.conn()
+            .execute(
+                "INSERT OR IGNORE INTO subscription_events
+                 (signature, instruction_index, slot, block_time, instruction, user, subscription, plan_tier, amount_paid, period_days, success)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    event.signature,
+                    event.instruction_index,
+                    event.slot as i64,
+                    event.block_time,
+                    event.instruction,
+                    event.user,
+                    event.subscription,
+                    event.plan_tier,
+                    event.amount_paid.map(|a| a as i64),
+                    event.period_days,
+                    event.success,
+                ],
+            )
+            .map(|inserted| inserted > 0)
+            .map_err(|e| format!("Insert subscription event failed: {}", e))
+    }
+
+    pub fn query_subscription_events(&self, query: &HistoryQuery) -> Result<(Vec<SubscriptionEvent>, HistoryTotals), String> {
+        let (where_clause, mut values) = query.where_clause();
+        let conn = self.conn();
+
+        let events = conn.query_row(
+            &format!("SELECT COUNT(*) FROM subscription_events {}", where_clause),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        ).map_err(|e| format!("Count subscription events failed: {}", e))?;
+
+        // The mint comes from the payment recorded for the event's subscription
+        let amount_paid = conn.prepare(&format!(
+            "SELECT (SELECT mint FROM subscription_payments p WHERE p.subscription = subscription_events.subscription) AS mint,
+                    SUM(amount_paid)
+             FROM subscription_events {} {} success AND amount_paid IS NOT NULL
+             GROUP BY mint ORDER BY mint",
+            where_clause,
+            if where_clause.is_empty() { "WHERE" } else { "AND" },
+        ))
+        .and_then(|mut statement| {
+            statement.query_map(params_from_iter(values.iter()), |row| Ok(MintTotal {
+                mint: row.get(0)?,
+                amount_paid: row.get::<_, i64>(1)? as u64,
+            }))?
+            .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| format!("Sum subscription payments failed: {}", e))?;
+        let totals = HistoryTotals { events, amount_paid };
+
+        values.push(Value::Integer(query.limit() as i64));
+        values.push(Value::Integer(query.offset() as i64));
+
+        let mut statement = conn.prepare(&format!(
+            "SELECT signature, instruction_index, slot, block_time, instruction, user, subscription, plan_tier, amount_paid, period_days, success
+             FROM subscription_events {}
+             ORDER BY slot DESC, instruction_index ASC LIMIT ? OFFSET ?",
+            where_clause
+        )).map_err(|e| format!("Query subscription events failed: {}", e))?;
+
+        let events = statement.query_map(params_from_iter(values.iter()), |row| {
+            Ok(SubscriptionEvent {
+                signature: row.get(0)?,
+                instruction_index: row.get(1)?,
+                slot: row.get::<_, i64>(2)? as u64,
+                block_time: row.get(3)?,
+                instruction: row.get(4)?,
+                user: row.get(5)?,
+                subscription: row.get(6)?,
+                plan_tier: row.get(7)?,
+                amount_paid: row.get::<_, Option<i64>>(8)?.map(|a| a as u64),
+                period_days: row.get(9)?,
+                success: row.get(10)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Query subscription events failed: {}", e))?;
+
+        Ok((events, totals))
+    }
+
+    /// Newest signature already indexed for `address`.
+    pub fn indexer_cursor(&self, address: &str) -> Result<Option<String>, String> {
+        self.conn()
+            .query_row(
+                "SELECT last_signature FROM indexer_cursors WHERE address = ?1",
+                params![address],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Query indexer cursor failed: {}", e))
+    }
+
+    pub fn set_indexer_cursor(&self, address: &str, signature: &str, slot: u64, now: i64) -> Result<(), String> {
+        self.conn()
+            .execute(
+                "INSERT OR REPLACE INTO indexer_cursors (address, last_signature, last_slot, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![address, signature, slot as i64, now],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Save indexer cursor failed: {}", e))
+    }
+
+    pub fn indexer_gap(&self, address: &str) -> Result<Option<IndexerGap>, String> {
+        self.conn()
+            .query_row(
+                "SELECT before_signature, until_signature FROM indexer_gaps WHERE address = ?1",
+                params![address],
+                |row| Ok(IndexerGap { before: row.get(0)?, until: row.get(1)? }),
+            )
+            .optional()
+            .map_err(|e| format!("Query indexer gap failed: {}", e))
+    }
+
+    /// Records the unindexed range still to backfill, or clears it with `None`.
+    pub fn set_indexer_gap(&self, address: &str, gap: Option<&IndexerGap>, now: i64) -> Result<(), String> {
+        let conn = self.conn();
+        match gap {
+            Some(gap) => conn.execute(
+                "INSERT OR REPLACE INTO indexer_gaps (address, before_signature, until_signature, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![address, gap.before, gap.until, now],
+            ),
+            None => conn.execute("DELETE FROM indexer_gaps WHERE address = ?1", params![address]),
+        }
+        .map(|_| ())
+        .map_err(|e| format!("Save indexer gap failed: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::payments::{PaymentStatus, SubscriptionPayment};
+
+    fn event(signature: &str, subscription: &str, amount_paid: u64, success: bool) -> SubscriptionEvent {
+        SubscriptionEvent {
+            signature: signature.to_string(),
+            instruction_index: 0,
+            slot: 1,
+            block_time: Some(1_700_000_000),
+            instruction: "subscribe".to_string(),
+            user: Some("user".to_string()),
+            subscription: Some(subscription.to_string()),
+            plan_tier: Some(1),
+            amount_paid: Some(amount_paid),
+            period_days: Some(30),
+            success,
+        }
+    }
+
+    #[test]
+    fn sums_amounts_per_payment_mint() {
+        let db = Database::in_memory().unwrap();
+        db.insert_payment(&SubscriptionPayment {
+            subscription: "paid".to_string(),
+            user: "user".to_string(),
+            plan_tier: 1,
+            period_days: 30,
+            currency: "USDC".to_string(),
+            mint: "usdc-mint".to_string(),
+            treasury: "treasury".to_string(),
+            amount: 29_000_000,
+            price_usd: 29.0,
+            status: PaymentStatus::Verified,
+            signature: None,
+            error: None,
+            created_at: 0,
+            updated_at: 0,
+            reference: None,
+        }).unwrap();
+        for event in [
+            event("a", "paid", 29_000_000, true),
+            event("b", "paid", 29_000_000, false),
+            event("c", "admin", 1_000_000_000, true),
+        ] {
+            db.insert_subscription_event(&event).unwrap();
+        }
+
+        let (_, totals) = db.query_subscription_events(&HistoryQuery::default()).unwrap();
+
+        assert_eq!(totals.events, 3);
+        let sums: Vec<(Option<&str>, u64)> = totals.amount_paid.iter().map(|t| (t.mint.as_deref(), t.amount_paid)).collect();
+        assert_eq!(sums, [(None, 1_000_000_000), (Some("usdc-mint"), 29_000_000)]);
+    }
+}
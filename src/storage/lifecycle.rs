@@ -0,0 +1,174 @@
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+/// Where a `SubscriptionAccount` stands from the lifecycle watcher's point of view.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleStatus {
+    Active,
+    /// Cancelled on-chain before `end_time`.
+    Cancelled,
+    /// `end_time` has passed.
+    Lapsed,
+    /// A `retry_payment` was sent or handed to the user to sign.
+    RenewalPending,
+    /// The program rejected the renewal; not retried until the account changes.
+    RenewalFailed,
+}
+
+impl LifecycleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LifecycleStatus::Active => "active",
+            LifecycleStatus::Cancelled => "cancelled",
+            LifecycleStatus::Lapsed => "lapsed",
+            LifecycleStatus::RenewalPending => "renewal_pending",
+            LifecycleStatus::RenewalFailed => "renewal_failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "active" => Some(LifecycleStatus::Active),
+            "cancelled" => Some(LifecycleStatus::Cancelled),
+            "lapsed" => Some(LifecycleStatus::Lapsed),
+            "renewal_pending" => Some(LifecycleStatus::RenewalPending),
+            "renewal_failed" => Some(LifecycleStatus::RenewalFailed),
+            _ => None,
+        }
+    }
+}
+
+/// Watcher state for one `SubscriptionAccount`. Reset whenever its `end_time` moves.
+#[derive(Debug, Clone, Serialize)]
+pub struct LifecycleRecord {
+    pub address: String,
+    pub user: String,
+    pub plan_tier: u8,
+    pub end_time: i64,
+    pub status: LifecycleStatus,
+    pub reminder_sent_at: Option<i64>,
+    pub lapsed_at: Option<i64>,
+    pub renewal_attempted_at: Option<i64>,
+    pub renewal_signature: Option<String>,
+    pub last_error: Option<String>,
+    pub updated_at: i64,
+}
+
+/// A wallet's standing instruction to renew lapsed subscriptions with `retry_payment`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoRenew {
+    #[serde(default)]
+    pub wallet: String,
+    /// Recorded as the program's `amount_paid`: base units of the mint the plan is paid in.
+    pub amount_paid: u64,
+    pub period_days: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+const RECORD_COLUMNS: &str = "address, user, plan_tier, end_time, status, reminder_sent_at, lapsed_at,
+    renewal_attempted_at, renewal_signature, last_error, updated_at";
+
+fn record_from_row(row: &Row) -> rusqlite::Result<LifecycleRecord> {
+    let status: String = row.get(4)?;
+    Ok(LifecycleRecord {
+        address: row.get(0)?,
+        user: row.get(1)?,
+        plan_tier: row.get(2)?,
+        end_time: row.get(3)?,
+        status: LifecycleStatus::parse(&status).unwrap_or(LifecycleStatus::Active),
+        reminder_sent_at: row.get(5)?,
+        lapsed_at: row.get(6)?,
+        renewal_attempted_at: row.get(7)?,
+        renewal_signature: row.get(8)?,
+        last_error: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+impl Database {
+    pub fn lifecycle_record(&self, address: &str) -> Result<Option<LifecycleRecord>, String> {
+        self.conn()
+            .query_row(
+                &format!("SELECT {} FROM subscription_lifecycle WHERE address = ?1", RECORD_COLUMNS),
+                params![address],
+                record_from_row,
+            )
+            .optional()
+            .map_err(|e| format!("Query lifecycle record failed: {}", e))
+    }
+
+    pub fn save_lifecycle_record(&self, record: &LifecycleRecord) -> Result<(), String> {
+        self.conn()
+            .execute(
+                &format!("INSERT OR REPLACE INTO subscription_lifecycle ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)", RECORD_COLUMNS),
+                params![
+                    record.address,
+                    record.user,
+                    record.plan_tier,
+                    record.end_time,
+                    record.status.as_str(),
+                    record.reminder_sent_at,
+                    record.lapsed_at,
+                    record.renewal_attempted_at,
+                    record.renewal_signature,
+                    record.last_error,
+                    record.updated_at,
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Save lifecycle record failed: {}", e))
+    }
+
+    /// Records for `user` (all users when `None`), soonest `end_time` first.
+    pub fn lifecycle_records(&self, user: Option<&str>, limit: u32) -> Result<Vec<LifecycleRecord>, String> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM subscription_lifecycle WHERE ?1 IS NULL OR user = ?1 ORDER BY end_time ASC LIMIT ?2",
+                RECORD_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let records = stmt.query_map(params![user, limit], record_from_row)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Query lifecycle records failed: {}", e));
+        records
+    }
+
+    pub fn auto_renew(&self, wallet: &str) -> Result<Option<AutoRenew>, String> {
+        self.conn()
+            .query_row(
+                "SELECT wallet, amount_paid, period_days, updated_at FROM auto_renew WHERE wallet = ?1",
+                params![wallet],
+                |row| Ok(AutoRenew {
+                    wallet: row.get(0)?,
+                    amount_paid: row.get::<_, i64>(1)? as u64,
+                    period_days: row.get(2)?,
+                    updated_at: row.get(3)?,
+                }),
+            )
+            .optional()
+            .map_err(|e| format!("Query auto-renew failed: {}", e))
+    }
+
+    pub fn set_auto_renew(&self, auto_renew: &AutoRenew) -> Result<(), String> {
+        self.conn()
+            .execute(
+                "INSERT OR REPLACE INTO auto_renew (wallet, amount_paid, period_days, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![auto_renew.wallet, auto_renew.amount_paid as i64, auto_renew.period_days, auto_renew.updated_at],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Save auto-renew failed: {}", e))
+    }
+
+    pub fn delete_auto_renew(&self, wallet: &str) -> Result<bool, String> {
+        self.conn()
+            .execute("DELETE FROM auto_renew WHERE wallet = ?1", params![wallet])
+            .map(|removed| removed > 0)
+            .map_err(|e| format!("Delete auto-renew failed: {}", e))
+    }
+}
@@ -0,0 +1,218 @@
+pub mod actions;
+pub mod alerts;
+pub mod audit;
+pub mod conditions;
+pub mod history;
+pub mod lifecycle;
+pub mod payments;
+pub mod sessions;
+
+use rusqlite::Connection;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// Schema migrations, applied in order and tracked with `PRAGMA user_version`.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE alerts (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        symbol TEXT NOT NULL,
+        price REAL NOT NULL,
+        alert_name TEXT NOT NULL,
+        timestamp INTEGER NOT NULL
+    );
+    CREATE INDEX idx_alerts_symbol_timestamp ON alerts (symbol, timestamp);
+    CREATE INDEX idx_alerts_timestamp ON alerts (timestamp);",
+    "CREATE TABLE webhook_audit (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp INTEGER NOT NULL,
+        source TEXT,
+        remote_addr TEXT,
+        reason TEXT NOT NULL
+    );
+    CREATE INDEX idx_webhook_audit_timestamp ON webhook_audit (timestamp);",
+    "ALTER TABLE alerts ADD COLUMN action TEXT;
+    ALTER TABLE alerts ADD COLUMN position_size REAL;
+    ALTER TABLE alerts ADD COLUMN interval TEXT;
+    ALTER TABLE alerts ADD COLUMN exchange TEXT;
+    ALTER TABLE alerts ADD COLUMN bar_time INTEGER;
+    ALTER TABLE alerts ADD COLUMN extra TEXT;",
+    "CREATE TABLE sessions (
+        token_hash TEXT PRIMARY KEY,
+        wallet TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        expires_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_sessions_expires_at ON sessions (expires_at);",
+    "CREATE TABLE subscription_lifecycle (
+        address TEXT PRIMARY KEY,
+        user TEXT NOT NULL,
+        plan_tier INTEGER NOT NULL,
+        end_time INTEGER NOT NULL,
+        status TEXT NOT NULL,
+        reminder_sent_at INTEGER,
+        lapsed_at INTEGER,
+        renewal_attempted_at INTEGER,
+        renewal_signature TEXT,
+        last_error TEXT,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_subscription_lifecycle_user ON subscription_lifecycle (user);
+    CREATE TABLE auto_renew (
+        wallet TEXT PRIMARY KEY,
+        amount_paid INTEGER NOT NULL,
+        period_days INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );",
+    "CREATE TABLE subscription_events (
+        signature TEXT NOT NULL,
+        instruction_index INTEGER NOT NULL,
+        slot INTEGER NOT NULL,
+        block_time INTEGER,
+        instruction TEXT NOT NULL,
+        user TEXT,
+        subscription TEXT,
+        plan_tier INTEGER,
+        amount_paid INTEGER,
+        period_days INTEGER,
+        success INTEGER NOT NULL,
+        PRIMARY KEY (signature, instruction_index)
+    );
+    CREATE INDEX idx_subscription_events_block_time ON subscription_events (block_time);
+    CREATE INDEX idx_subscription_events_user ON subscription_events (user);
+    CREATE TABLE indexer_cursors (
+        address TEXT PRIMARY KEY,
+        last_signature TEXT NOT NULL,
+        last_slot INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );",
+    "CREATE TABLE price_conditions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        symbol TEXT NOT NULL,
+        operator TEXT NOT NULL,
+        value REAL NOT NULL,
+        window_seconds INTEGER,
+        rearm TEXT NOT NULL,
+        cooldown_seconds INTEGER,
+        label TEXT,
+        armed INTEGER NOT NULL,
+        trigger_count INTEGER NOT NULL DEFAULT 0,
+        last_triggered_at INTEGER,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_price_conditions_symbol ON price_conditions (symbol);
+    CREATE TABLE condition_triggers (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        condition_id INTEGER NOT NULL,
+        symbol TEXT NOT NULL,
+        price REAL NOT NULL,
+        reference_price REAL,
+        triggered_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_condition_triggers_condition ON condition_triggers (condition_id, triggered_at);",
+    "CREATE TABLE condition_actions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        condition_id INTEGER NOT NULL,
+        instruction TEXT NOT NULL,
+        accounts TEXT NOT NULL,
+        args TEXT NOT NULL,
+        dry_run INTEGER NOT NULL,
+        idempotency_key TEXT,
+        idempotency_window_seconds INTEGER,
+        label TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_condition_actions_condition ON condition_actions (condition_id);
+    CREATE TABLE action_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        action_id INTEGER NOT NULL,
+        condition_id INTEGER NOT NULL,
+        idempotency_key TEXT UNIQUE,
+        mode TEXT NOT NULL,
+        status TEXT NOT NULL,
+        instruction TEXT NOT NULL,
+        args TEXT NOT NULL,
+        price REAL,
+        signature TEXT,
+        error TEXT,
+        logs TEXT,
+        units_consumed INTEGER,
+        attempts INTEGER NOT NULL DEFAULT 1,
+        duplicates INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_action_runs_action ON action_runs (action_id, created_at);",
+    "CREATE TABLE subscription_payments (
+        subscription TEXT PRIMARY KEY,
+        user TEXT NOT NULL,
+        plan_tier INTEGER NOT NULL,
+        period_days INTEGER NOT NULL,
+        currency TEXT NOT NULL,
+        mint TEXT NOT NULL,
+        treasury TEXT NOT NULL,
+        amount INTEGER NOT NULL,
+        price_usd REAL NOT NULL,
+        status TEXT NOT NULL,
+        signature TEXT,
+        error TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_subscription_payments_user ON subscription_payments (user, status);",
+    "ALTER TABLE subscription_payments ADD COLUMN reference TEXT;
+    CREATE UNIQUE INDEX idx_subscription_payments_reference ON subscription_payments (reference);",
+    "CREATE TABLE indexer_gaps (
+        address TEXT PRIMARY KEY,
+        before_signature TEXT NOT NULL,
+        until_signature TEXT,
+        updated_at INTEGER NOT NULL
+    );",
+    "ALTER TABLE subscription_payments ADD COLUMN client TEXT;",
+];
+
+/// SQLite database shared by the storage modules.
+pub struct Database {
+    conn: Mutex<Connection>,
+}
+
+impl Database {
+    /// Opens (creating if needed) the database file and applies pending migrations.
+    pub fn open(path: &str) -> Result<Self, String> {
+        if let Some(parent) = Path::new(path).parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Cannot create database directory {}: {}", parent.display(), e))?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Cannot open database {}: {}", path, e))?;
+        Self::with_connection(conn)
+    }
+
+    /// A private in-memory database with every migration applied.
+    #[cfg(test)]
+    pub fn in_memory() -> Result<Self, String> {
+        Self::with_connection(Connection::open_in_memory().map_err(|e| e.to_string())?)
+    }
+
+    fn with_connection(mut conn: Connection) -> Result<Self, String> {
+        conn.pragma_update(None, "journal_mode", "WAL").map_err(|e| e.to_string())?;
+
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        // Each migration commits together with its version bump, so a failed one
+        // leaves neither half-applied schema nor a skipped step behind
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let fail = |e: rusqlite::Error| format!("Migration {} failed: {}", index + 1, e);
+            let tx = conn.transaction().map_err(fail)?;
+            tx.execute_batch(migration).map_err(fail)?;
+            tx.pragma_update(None, "user_version", index + 1).map_err(fail)?;
+            tx.commit().map_err(fail)?;
+        }
+
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap()
+    }
+}
@@ -0,0 +1,265 @@
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::Database;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentStatus {
+    /// The transaction was handed to the user and has not been seen on-chain yet.
+    Pending,
+    /// `subscribe` landed together with a transfer of at least `amount` to the treasury.
+    Verified,
+    /// `subscribe` landed without the full transfer.
+    Failed,
+    /// A checkout that was not seen on-chain in time.
+    Expired,
+}
+
+impl PaymentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentStatus::Pending => "pending",
+            PaymentStatus::Verified => "verified",
+            PaymentStatus::Failed => "failed",
+            PaymentStatus::Expired => "expired",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(PaymentStatus::Pending),
+            "verified" => Some(PaymentStatus::Verified),
+            "failed" => Some(PaymentStatus::Failed),
+            "expired" => Some(PaymentStatus::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// An SPL token payment for one `subscribe`, keyed by the new subscription's address.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionPayment {
+    pub subscription: String,
+    pub user: String,
+    pub plan_tier: u8,
+    pub period_days: i64,
+    /// Configured symbol of the mint, e.g. `USDC`.
+    pub currency: String,
+    pub mint: String,
+    /// Wallet owning the receiving token account.
+    pub treasury: String,
+    /// Base units of `mint`.
+    pub amount: u64,
+    pub price_usd: f64,
+    pub status: PaymentStatus,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Solana Pay reference key, set for checkouts.
+    pub reference: Option<String>,
+}
+
+const PAYMENT_COLUMNS: &str = "subscription, user, plan_tier, period_days, currency, mint, treasury, amount,
+    price_usd, status, signature, error, created_at, updated_at, reference";
+
+fn payment_from_row(row: &Row) -> rusqlite::Result<SubscriptionPayment> {
+    let status: String = row.get(9)?;
+    Ok(SubscriptionPayment {
+        subscription: row.get(0)?,
+        user: row.get(1)?,
+        plan_tier: row.get(2)?,
+        period_days: row.get(3)?,
+        currency: row.get(4)?,
+        mint: row.get(5)?,
+        treasury: row.get(6)?,
+        amount: row.get::<_, i64>(7)? as u64,
+        price_usd: row.get(8)?,
+        status: PaymentStatus::parse(&status).unwrap_or(PaymentStatus::Pending),
+        signature: row.get(10)?,
+        error: row.get(11)?,
+        created_at: row.get(12)?,
+        updated_at: row.get(13)?,
+        reference: row.get(14)?,
+    })
+}
+
+impl Database {
+    pub fn insert_payment(&self, payment: &SubscriptionPayment) -> Result<(), String> {
+        self.conn()
+            .execute(
+                &format!("INSERT INTO subscription_payments ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)", PAYMENT_COLUMNS),
+                params![
+                    payment.subscription,
+                    payment.user,
+                    payment.plan_tier,
+                    payment.period_days,
+                    payment.currency,
+                    payment.mint,
+                    payment.treasury,
+                    payment.amount as i64,
+                    payment.price_usd,
+                    payment.status.as_str(),
+                    payment.signature,
+                    payment.error,
+                    payment.created_at,
+                    payment.updated_at,
+                    payment.reference,
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Save payment failed: {}", e))
+    }
+
+    pub fn payment(&self, subscription: &str) -> Result<Option<SubscriptionPayment>, String> {
+        self.conn()
+            .query_row(
+                &format!("SELECT {} FROM subscription_payments WHERE subscription = ?1", PAYMENT_COLUMNS),
+                params![subscription],
+                payment_from_row,
+            )
+            .optional()
+            .map_err(|e| format!("Query payment failed: {}", e))
+    }
+
+    pub fn payment_by_reference(&self, reference: &str) -> Result<Option<SubscriptionPayment>, String> {
+        self.conn()
+            .query_row(
+                &format!("SELECT {} FROM subscription_payments WHERE reference = ?1", PAYMENT_COLUMNS),
+                params![reference],
+                payment_from_row,
+            )
+            .optional()
+            .map_err(|e| format!("Query payment failed: {}", e))
+    }
+
+    /// Records a checkout opened from `client`, or replaces the one under the same reference
+    /// while it is still pending for the same user. Returns `false` when the reference is
+    /// taken otherwise.
+    pub fn save_checkout(&self, payment: &SubscriptionPayment, client: Option<&str>) -> Result<bool, String> {
+        self.conn()
+            .execute(
+                &format!(
+                    "INSERT INTO subscription_payments ({}, client) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+                     ON CONFLICT (reference) DO UPDATE SET
+                        subscription = excluded.subscription, plan_tier = excluded.plan_tier, period_days = excluded.period_days,
+                        currency = excluded.currency, mint = excluded.mint, treasury = excluded.treasury, amount = excluded.amount,
+                        price_usd = excluded.price_usd, created_at = excluded.created_at, updated_at = excluded.updated_at
+                     WHERE status = 'pending' AND user = excluded.user",
+                    PAYMENT_COLUMNS
+                ),
+                params![
+                    payment.subscription,
+                    payment.user,
+                    payment.plan_tier,
+                    payment.period_days,
+                    payment.currency,
+                    payment.mint,
+                    payment.treasury,
+                    payment.amount as i64,
+                    payment.price_usd,
+                    payment.status.as_str(),
+                    payment.signature,
+                    payment.error,
+                    payment.created_at,
+                    payment.updated_at,
+                    payment.reference,
+                    client,
+                ],
+            )
+            .map(|saved| saved > 0)
+            .map_err(|e| format!("Save checkout failed: {}", e))
+    }
+
+    /// Points a pending checkout at the subscription its landed transaction created, which
+    /// may be from an earlier build than the one recorded.
+    pub fn set_checkout_subscription(&self, reference: &str, subscription: &str) -> Result<(), String> {
+        self.conn()
+            .execute(
+                "UPDATE subscription_payments SET subscription = ?2 WHERE reference = ?1 AND status = 'pending'",
+                params![reference, subscription],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Update checkout failed: {}", e))
+    }
+
+    /// Pending checkouts built since `since`: of `user`, opened from `client`, and of everyone.
+    pub fn pending_checkout_counts(&self, user: &str, client: Option<&str>, since: i64) -> Result<(u32, u32, u32), String> {
+        self.conn()
+            .query_row(
+                "SELECT COALESCE(SUM(user = ?1), 0), COALESCE(SUM(client = ?2), 0), COUNT(*) FROM subscription_payments
+                 WHERE status = 'pending' AND reference IS NOT NULL AND created_at >= ?3",
+                params![user, client, since],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|e| format!("Count pending checkouts failed: {}", e))
+    }
+
+    /// Checkouts still waiting for their transaction, oldest first.
+    pub fn pending_checkouts(&self) -> Result<Vec<SubscriptionPayment>, String> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM subscription_payments WHERE status = 'pending' AND reference IS NOT NULL ORDER BY created_at ASC",
+                PAYMENT_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let payments = stmt.query_map([], payment_from_row)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Query pending checkouts failed: {}", e));
+        payments
+    }
+
+    /// Payments of `user` and/or in `status` (all when `None`), newest first.
+    pub fn payments(&self, user: Option<&str>, status: Option<PaymentStatus>, limit: u32) -> Result<Vec<SubscriptionPayment>, String> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM subscription_payments
+                 WHERE (?1 IS NULL OR user = ?1) AND (?2 IS NULL OR status = ?2)
+                 ORDER BY created_at DESC LIMIT ?3",
+                PAYMENT_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let payments = stmt.query_map(params![user, status.map(|s| s.as_str()), limit], payment_from_row)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Query payments failed: {}", e));
+        payments
+    }
+
+    pub fn set_payment_status(
+        &self,
+        subscription: &str,
+        status: PaymentStatus,
+        signature: Option<&str>,
+        error: Option<&str>,
+        now: i64,
+    ) -> Result<(), String> {
+        self.conn()
+            .execute(
+                "UPDATE subscription_payments SET status = ?2, signature = ?3, error = ?4, updated_at = ?5 WHERE subscription = ?1",
+                params![subscription, status.as_str(), signature, error, now],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Update payment failed: {}", e))
+    }
+
+    /// `user`'s verified payments by subscription address. A verified payment is never
+    /// updated again, so its `updated_at` is when it was verified.
+    pub fn verified_payments(&self, user: &str) -> Result<HashMap<String, SubscriptionPayment>, String> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(&format!("SELECT {} FROM subscription_payments WHERE user = ?1 AND status = 'verified'", PAYMENT_COLUMNS))
+            .map_err(|e| e.to_string())?;
+        let payments = stmt.query_map(params![user], payment_from_row)
+            .map_err(|e| e.to_string())?
+            .map(|payment| payment.map(|p| (p.subscription.clone(), p)))
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| format!("Query verified payments failed: {}", e));
+        payments
+    }
+}
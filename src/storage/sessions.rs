@@ -0,0 +1,75 @@
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+
+use super::Database;
+
+/// A signed-in wallet. Only the SHA-256 of the bearer token is stored.
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pub wallet: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+impl Database {
+    pub fn insert_session(&self, token_hash: &str, session: &Session) -> Result<(), String> {
+        self.conn()
+            .execute(
+                "INSERT INTO sessions (token_hash, wallet, created_at, expires_at) VALUES (?1, ?2, ?3, ?4)",
+                params![token_hash, session.wallet, session.created_at, session.expires_at],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Insert session failed: {}", e))
+    }
+
+    /// The session for `token_hash` if it has not expired at `now`.
+    pub fn find_session(&self, token_hash: &str, now: i64) -> Result<Option<Session>, String> {
+        self.conn()
+            .query_row(
+                "SELECT wallet, created_at, expires_at FROM sessions WHERE token_hash = ?1 AND expires_at > ?2",
+                params![token_hash, now],
+                |row| Ok(Session {
+                    wallet: row.get(0)?,
+                    created_at: row.get(1)?,
+                    expires_at: row.get(2)?,
+                }),
+            )
+            .optional()
+            .map_err(|e| format!("Query session failed: {}", e))
+    }
+
+    pub fn delete_session(&self, token_hash: &str) -> Result<bool, String> {
+        self.conn()
+            .execute("DELETE FROM sessions WHERE token_hash = ?1", params![token_hash])
+            .map(|removed| removed > 0)
+            .map_err(|e| format!("Delete session failed: {}", e))
+    }
+
+    pub fn prune_sessions(&self, now: i64) -> Result<usize, String> {
+        self.conn()
+            .execute("DELETE FROM sessions WHERE expires_at <= ?1", params![now])
+            .map_err(|e| format!("Prune sessions failed: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_sessions_until_they_expire() {
+        let db = Database::in_memory().unwrap();
+        let session = |wallet: &str, expires_at| Session { wallet: wallet.to_string(), created_at: 0, expires_at };
+        db.insert_session("hash-a", &session("alice", 100)).unwrap();
+        db.insert_session("hash-b", &session("bob", 200)).unwrap();
+
+        assert_eq!(db.find_session("hash-a", 99).unwrap().map(|s| s.wallet), Some("alice".to_string()));
+        assert!(db.find_session("hash-a", 100).unwrap().is_none());
+        assert!(db.find_session("unknown", 0).unwrap().is_none());
+
+        assert_eq!(db.prune_sessions(150).unwrap(), 1);
+        assert!(db.delete_session("hash-b").unwrap());
+        assert!(!db.delete_session("hash-b").unwrap());
+        assert!(db.find_session("hash-b", 0).unwrap().is_none());
+    }
+}
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{OnceLock, RwLock};
+use tokio::sync::watch;
+
+static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
+/// Bumped on every change, so background tasks can follow the registry.
+static CHANGES: OnceLock<watch::Sender<u64>> = OnceLock::new();
+
+/// One tracked asset and the identifiers each upstream uses for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolInfo {
+    pub symbol: String,
+    pub coingecko_id: Option<String>,
+    pub binance_pair: Option<String>,
+    pub kraken_pair: Option<String>,
+    /// TradingView tickers that map to this symbol, e.g. `BINANCE:BTCUSDT`.
+    #[serde(default)]
+    pub tradingview_tickers: Vec<String>,
+}
+
+#[derive(Default)]
+struct Registry {
+    symbols: Vec<SymbolInfo>,
+    /// Where admin edits are written back, if the registry came from a file.
+    path: Option<String>,
+}
+
+fn entry(symbol: &str, coingecko_id: &str, kraken_pair: &str) -> SymbolInfo {
+    SymbolInfo {
+        symbol: symbol.to_string(),
+        coingecko_id: Some(coingecko_id.to_string()),
+        binance_pair: Some(format!("{}USDT", symbol)),
+        kraken_pair: Some(kraken_pair.to_string()),
+        tradingview_tickers: vec![format!("BINANCE:{}USDT", symbol), format!("COINBASE:{}USD", symbol)],
+    }
+}
+
+fn defaults() -> Vec<SymbolInfo> {
+    vec![
+        entry("BTC", "bitcoin", "XBTUSD"),
+        entry("ETH", "ethereum", "ETHUSD"),
+        entry("SOL", "solana", "SOLUSD"),
+        entry("PAXG", "pax-gold", "PAXGUSD"),
+    ]
+}
+
+fn normalize(mut info: SymbolInfo) -> SymbolInfo {
+    info.symbol = info.symbol.trim().to_uppercase();
+    info.binance_pair = info.binance_pair.map(|p| p.to_uppercase());
+    info.tradingview_tickers = info.tradingview_tickers.into_iter().map(|t| t.to_uppercase()).collect();
+    info
+}
+
+fn registry() -> &'static RwLock<Registry> {
+    REGISTRY.get_or_init(|| RwLock::new(Registry {
+        symbols: defaults(),
+        path: None,
+    }))
+}
+
+impl Registry {
+    /// Reads a JSON array of `SymbolInfo`, falling back to the defaults when the
+    /// file does not exist yet, and writes the result back.
+    fn load(path: &str) -> Result<Self, String> {
+        let symbols = match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str::<Vec<SymbolInfo>>(&contents)
+                .map_err(|e| format!("Invalid symbols file {}: {}", path, e))?
+                .into_iter()
+                .map(normalize)
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => defaults(),
+            Err(e) => return Err(format!("Cannot read symbols file {}: {}", path, e)),
+        };
+
+        save(Some(path), &symbols)?;
+        Ok(Self {
+            symbols,
+            path: Some(path.to_string()),
+        })
+    }
+
+    fn upsert(&mut self, info: SymbolInfo) -> Result<SymbolInfo, String> {
+        let info = normalize(info);
+        if info.symbol.is_empty() || !info.symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("Invalid symbol: {:?}", info.symbol));
+        }
+
+        // Written to disk first, so a failed save leaves the registry as it was
+        let mut symbols = self.symbols.clone();
+        match symbols.iter_mut().find(|s| s.symbol == info.symbol) {
+            Some(existing) => *existing = info.clone(),
+            None => symbols.push(info.clone()),
+        }
+        save(self.path.as_deref(), &symbols)?;
+        self.symbols = symbols;
+
+        Ok(info)
+    }
+
+    fn remove(&mut self, symbol: &str) -> Result<bool, String> {
+        let symbol_upper = symbol.to_uppercase();
+        let symbols: Vec<SymbolInfo> = self.symbols.iter().filter(|s| s.symbol != symbol_upper).cloned().collect();
+        if symbols.len() == self.symbols.len() {
+            return Ok(false);
+        }
+        save(self.path.as_deref(), &symbols)?;
+        self.symbols = symbols;
+        Ok(true)
+    }
+}
+
+fn changes() -> &'static watch::Sender<u64> {
+    CHANGES.get_or_init(|| watch::channel(0).0)
+}
+
+fn notify() {
+    changes().send_modify(|version| *version += 1);
+}
+
+/// Notified whenever symbols are loaded, added, changed or removed.
+pub fn subscribe() -> watch::Receiver<u64> {
+    changes().subscribe()
+}
+
+/// Loads the registry from a JSON array of `SymbolInfo`, creating the file from
+/// the defaults when it does not exist yet.
+pub fn init(path: Option<&str>) -> Result<(), String> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+
+    let loaded = Registry::load(path)?;
+    *registry().write().unwrap() = loaded;
+    notify();
+    Ok(())
+}
+
+fn save(path: Option<&str>, symbols: &[SymbolInfo]) -> Result<(), String> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let contents = serde_json::to_string_pretty(symbols).map_err(|e| e.to_string())?;
+    std::fs::write(path, contents).map_err(|e| format!("Cannot write symbols file {}: {}", path, e))
+}
+
+pub fn all() -> Vec<SymbolInfo> {
+    registry().read().unwrap().symbols.clone()
+}
+
+/// Registered symbols, in registry order.
+pub fn symbols() -> Vec<String> {
+    registry().read().unwrap().symbols.iter().map(|s| s.symbol.clone()).collect()
+}
+
+pub fn get(symbol: &str) -> Option<SymbolInfo> {
+    let symbol_upper = symbol.to_uppercase();
+    registry().read().unwrap().symbols.iter().find(|s| s.symbol == symbol_upper).cloned()
+}
+
+pub fn is_supported(symbol: &str) -> bool {
+    get(symbol).is_some()
+}
+
+/// Binance pairs of the registered symbols that have one, in registry order.
+pub fn binance_pairs() -> Vec<String> {
+    registry().read().unwrap().symbols.iter().filter_map(|s| s.binance_pair.clone()).collect()
+}
+
+/// Adds or replaces a symbol. Returns the stored entry.
+pub fn upsert(info: SymbolInfo) -> Result<SymbolInfo, String> {
+    let stored = registry().write().unwrap().upsert(info)?;
+    notify();
+    Ok(stored)
+}
+
+pub fn remove(symbol: &str) -> Result<bool, String> {
+    let removed = registry().write().unwrap().remove(symbol)?;
+    if removed {
+        notify();
+    }
+    Ok(removed)
+}
+
+/// Matches a TradingView ticker (`BINANCE:SOLUSDT`) against the registered tickers.
+pub fn find_by_tradingview_ticker(ticker: &str) -> Option<String> {
+    let ticker_upper = ticker.trim().to_uppercase();
+    registry().read().unwrap().symbols.iter()
+        .find(|s| s.tradingview_tickers.contains(&ticker_upper))
+        .map(|s| s.symbol.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path() -> String {
+        std::env::temp_dir()
+            .join(format!("symbols-{}.json", uuid::Uuid::new_v4()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn info(symbol: &str, binance_pair: Option<&str>) -> SymbolInfo {
+        SymbolInfo {
+            symbol: symbol.to_string(),
+            coingecko_id: None,
+            binance_pair: binance_pair.map(str::to_string),
+            kraken_pair: None,
+            tradingview_tickers: vec![format!("binance:{}", symbol)],
+        }
+    }
+
+    #[test]
+    fn creates_the_file_from_the_defaults() {
+        let path = temp_path();
+        let registry = Registry::load(&path).unwrap();
+
+        let symbols: Vec<&str> = registry.symbols.iter().map(|s| s.symbol.as_str()).collect();
+        assert_eq!(symbols, ["BTC", "ETH", "SOL", "PAXG"]);
+        let saved: Vec<SymbolInfo> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.len(), 4);
+
+        std::fs::write(&path, "{").unwrap();
+        assert!(Registry::load(&path).err().unwrap().starts_with("Invalid symbols file"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn edits_are_normalized_and_written_back() {
+        let path = temp_path();
+        std::fs::write(&path, serde_json::to_string(&[info(" avax ", Some("avaxusdt"))]).unwrap()).unwrap();
+        let mut registry = Registry::load(&path).unwrap();
+        assert_eq!(registry.symbols[0].symbol, "AVAX");
+        assert_eq!(registry.symbols[0].binance_pair.as_deref(), Some("AVAXUSDT"));
+
+        let stored = registry.upsert(info("jup", None)).unwrap();
+        assert_eq!(stored.tradingview_tickers, ["BINANCE:JUP"]);
+        registry.upsert(info("avax", Some("AVAXUSDC"))).unwrap();
+        assert!(registry.upsert(info("BAD-1", None)).unwrap_err().starts_with("Invalid symbol"));
+
+        let reloaded = Registry::load(&path).unwrap();
+        let pairs: Vec<(&str, Option<&str>)> = reloaded.symbols.iter()
+            .map(|s| (s.symbol.as_str(), s.binance_pair.as_deref()))
+            .collect();
+        assert_eq!(pairs, [("AVAX", Some("AVAXUSDC")), ("JUP", None)]);
+
+        assert!(registry.remove("jup").unwrap());
+        assert!(!registry.remove("jup").unwrap());
+        assert_eq!(Registry::load(&path).unwrap().symbols.len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn changes_notify_subscribers() {
+        let mut changes = subscribe();
+        changes.borrow_and_update();
+
+        upsert(info("NOTIFY", Some("NOTIFYUSDT"))).unwrap();
+        assert!(changes.has_changed().unwrap());
+        assert!(binance_pairs().contains(&"NOTIFYUSDT".to_string()));
+        assert_eq!(find_by_tradingview_ticker(" binance:notify "), Some("NOTIFY".to_string()));
+
+        changes.borrow_and_update();
+        assert!(remove("notify").unwrap());
+        assert!(changes.has_changed().unwrap());
+        assert!(!is_supported("NOTIFY"));
+    }
+}